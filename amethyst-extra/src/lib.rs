@@ -4,9 +4,7 @@ extern crate serde;
 extern crate ron;
 #[macro_use]
 extern crate log;
-//extern crate crossterm;
 extern crate dirty;
-extern crate fern;
 extern crate partial_function;
 extern crate rand;
 
@@ -25,13 +23,19 @@ use amethyst::ecs::*;
 use amethyst::input::*;
 use amethyst::prelude::*;
 use amethyst::renderer::*;
-use amethyst::ui::{UiBundle, UiText};
+use amethyst::shrev::{EventChannel, ReaderId};
+use amethyst::ui::{
+    Anchor, FontHandle, Stretch, TextEditing, UiBundle, UiFocused, UiImage, UiText, UiTransform,
+};
+use amethyst::LogBuffer;
 use amethyst::Result;
 use dirty::Dirty;
+use log::Level;
 use partial_function::*;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Display;
 use std::fs;
 use std::fs::File;
 use std::hash::Hash;
@@ -41,18 +45,17 @@ use std::iter::Cycle;
 use std::marker::PhantomData;
 use std::ops::{Add, Sub};
 use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
 use std::vec::IntoIter;
 
-/*use crossterm::cursor::*;
-use crossterm::input::*;
-use crossterm::raw::*;
-use crossterm::terminal::*;
-use crossterm::*;
-use crossterm::terminal::terminal::Terminal;*/
-
 /// Loads asset from the so-called asset packs
 /// It caches assets which you can manually load or unload on demand.
 ///
+/// Packs are resolved following the load order: the default pack always comes first
+/// and every enabled pack placed after it overrides the files of the ones before.
+/// Unless a different order is applied, packs are ordered alphabetically.
+///
 /// Example:
 /// If the folder structure looks like this
 /// /assets/base/sprites/player.png
@@ -64,11 +67,38 @@ use crossterm::terminal::terminal::Terminal;*/
 ///
 /// resolve_path("sprites/player.png") -> /assets/mod1/sprites/player.png
 /// resolve_path("models/cube.obj") -> /assets/base/models/cube.obj
-/// resolve_path("sounds/click.ogg") -> Unknown.
+/// resolve_path("sounds/click.ogg") -> /assets/mod2/sounds/click.ogg
 pub struct AssetLoader {
     base_path: String,
     default_pack: String,
+    /// Asset pack names, in load order.
     asset_packs: Vec<String>,
+    /// Asset packs ignored when resolving paths.
+    disabled_packs: Vec<String>,
+    /// Metadata of the packs that have a `pack.ron` file.
+    pack_infos: HashMap<String, AssetPackInfo>,
+}
+
+/// Metadata of an asset pack, read from the optional `pack.ron` file at the root of the pack.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AssetPackInfo {
+    pub name: String,
+    #[serde(default)]
+    pub version: String,
+    #[serde(default)]
+    pub author: String,
+    /// Hidden packs are not listed to the player until the game unlocks them.
+    #[serde(default)]
+    pub hidden: bool,
+}
+
+/// The load order and the enabled state of the asset packs, as saved on disk.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AssetPackSettings {
+    /// Asset pack names, from the lowest to the highest priority.
+    pub order: Vec<String>,
+    /// Asset packs that are not used when resolving paths.
+    pub disabled: Vec<String>,
 }
 
 impl AssetLoader {
@@ -77,6 +107,8 @@ impl AssetLoader {
             base_path: AssetLoader::sanitize_path_trail_only(&base_path),
             default_pack: AssetLoader::sanitize_path(&default_pack),
             asset_packs: Vec::new(),
+            disabled_packs: Vec::new(),
+            pack_infos: HashMap::new(),
         };
         al.get_asset_packs();
         al
@@ -109,19 +141,23 @@ impl AssetLoader {
     }
 
     pub fn resolve_path(&self, path: &str) -> Option<String> {
-        // Try to get from default path
-        let mut res = self.resolve_path_for_pack(path, &self.default_pack);
+        self.resolve_path_and_pack(path).map(|(_, abs)| abs)
+    }
 
-        // Try to find overrides
-        for p in &self.asset_packs {
-            if p != &self.default_pack {
-                if let Some(r) = self.resolve_path_for_pack(path, &p) {
-                    res = Some(r);
-                }
+    /// Resolves the path and returns the name of the pack it was found in along with the absolute path.
+    pub fn resolve_path_and_pack(&self, path: &str) -> Option<(String, String)> {
+        // The last enabled pack of the load order wins.
+        for p in self.asset_packs.iter().rev() {
+            if !self.is_pack_enabled(p) {
+                continue;
+            }
+            if let Some(r) = self.resolve_path_for_pack(path, &p) {
+                return Some((p.clone(), r));
             }
         }
 
-        res
+        warn!("Failed to find file {} in any of the enabled asset packs", path);
+        None
     }
 
     fn resolve_path_for_pack(&self, path: &str, pack: &str) -> Option<String> {
@@ -134,24 +170,62 @@ impl AssetLoader {
         if path.exists() {
             Some(abs.clone())
         } else {
-            warn!("Failed to find file at path: {}", abs);
             None
         }
     }
 
+    /// Lists every file provided by the enabled asset packs, along with the pack it resolves to.
+    /// Keys are paths relative to the pack root, using '/' as separator.
+    pub fn resolved_files(&self) -> BTreeMap<String, String> {
+        let mut files = BTreeMap::new();
+        for p in &self.asset_packs {
+            if !self.is_pack_enabled(p) {
+                continue;
+            }
+            let root = format!("{}/{}", self.base_path, p);
+            for file in AssetLoader::list_files_recursive(Path::new(&root), "") {
+                files.insert(file, p.clone());
+            }
+        }
+        files
+    }
+
+    fn list_files_recursive(dir: &Path, prefix: &str) -> Vec<String> {
+        let mut out = Vec::new();
+        if let Ok(elems) = fs::read_dir(dir) {
+            for e in elems.filter_map(|e| e.ok()) {
+                let name = e.file_name().to_string_lossy().into_owned();
+                let rel = if prefix.is_empty() {
+                    name
+                } else {
+                    format!("{}/{}", prefix, name)
+                };
+                let path = e.path();
+                if path.is_dir() {
+                    out.extend(AssetLoader::list_files_recursive(&path, &rel));
+                } else {
+                    out.push(rel);
+                }
+            }
+        }
+        out
+    }
+
     pub fn get_asset_packs(&mut self) -> &Vec<String> {
         let mut buf: Option<Vec<String>> = None;
         if self.asset_packs.len() == 0 {
             if let Ok(elems) = fs::read_dir(&self.base_path) {
-                buf = Some(
-                    elems
-                        .map(|e| {
-                            let path = &e.unwrap().path();
-                            let tmp = &path.to_str().unwrap()[self.base_path.len()..];
-                            AssetLoader::sanitize_path(&tmp)
-                        })
-                        .collect(),
-                );
+                let mut packs = elems
+                    .filter_map(|e| e.ok())
+                    .filter(|e| e.path().is_dir())
+                    .map(|e| {
+                        let path = &e.path();
+                        let tmp = &path.to_str().unwrap()[self.base_path.len()..];
+                        AssetLoader::sanitize_path(&tmp)
+                    })
+                    .collect::<Vec<String>>();
+                packs.sort();
+                buf = Some(packs);
             } else {
                 error!(
                     "Failed to find base_path directory for asset loading: {}",
@@ -160,13 +234,161 @@ impl AssetLoader {
             }
         }
 
-        if let Some(v) = buf {
+        if let Some(mut v) = buf {
+            // The default pack is always loaded first.
+            if let Some(idx) = v.iter().position(|p| p == &self.default_pack) {
+                let default = v.remove(idx);
+                v.insert(0, default);
+            }
+            self.pack_infos = v
+                .iter()
+                .filter_map(|p| self.read_pack_info(p).map(|i| (p.clone(), i)))
+                .collect();
             self.asset_packs = v;
         }
 
         &self.asset_packs
     }
 
+    fn read_pack_info(&self, pack: &str) -> Option<AssetPackInfo> {
+        let path = format!("{}/{}/pack.ron", self.base_path, pack);
+        let mut f = File::open(&path).ok()?;
+        let mut c = String::new();
+        if f.read_to_string(&mut c).is_err() {
+            error!("Failed to read content of asset pack info: {}", path);
+            return None;
+        }
+        match ron::de::from_str::<AssetPackInfo>(&c) {
+            Ok(info) => Some(info),
+            Err(e) => {
+                error!("Failed to deserialize asset pack info {}: {:?}", path, e);
+                None
+            }
+        }
+    }
+
+    /// Returns the metadata of the pack, if it has a `pack.ron` file.
+    pub fn pack_info(&self, pack: &str) -> Option<&AssetPackInfo> {
+        self.pack_infos.get(pack)
+    }
+
+    pub fn default_pack(&self) -> &str {
+        &self.default_pack
+    }
+
+    pub fn is_pack_enabled(&self, pack: &str) -> bool {
+        !self.disabled_packs.iter().any(|p| p == pack)
+    }
+
+    /// Enables or disables an asset pack. The default pack can't be disabled.
+    pub fn set_pack_enabled(&mut self, pack: &str, enabled: bool) {
+        if pack == self.default_pack {
+            warn!("The default asset pack {} can't be disabled", pack);
+            return;
+        }
+        self.disabled_packs.retain(|p| p != pack);
+        if !enabled {
+            self.disabled_packs.push(pack.to_owned());
+        }
+    }
+
+    /// Moves a pack to a new position in the load order.
+    /// The default pack stays first, so the index is clamped after it.
+    pub fn move_pack(&mut self, pack: &str, index: usize) {
+        if pack == self.default_pack {
+            return;
+        }
+        if let Some(idx) = self.asset_packs.iter().position(|p| p == pack) {
+            let p = self.asset_packs.remove(idx);
+            let min = if self.asset_packs.contains(&self.default_pack) {
+                1
+            } else {
+                0
+            };
+            let index = index.max(min).min(self.asset_packs.len());
+            self.asset_packs.insert(index, p);
+        }
+    }
+
+    /// Returns the current load order and enabled state.
+    pub fn pack_settings(&self) -> AssetPackSettings {
+        AssetPackSettings {
+            order: self.asset_packs.clone(),
+            disabled: self.disabled_packs.clone(),
+        }
+    }
+
+    /// Applies a load order and enabled state.
+    /// Packs missing from the settings keep their relative order and are placed after the known ones.
+    /// Packs that no longer exist are ignored.
+    pub fn apply_pack_settings(&mut self, settings: &AssetPackSettings) {
+        let mut order = settings
+            .order
+            .iter()
+            .filter(|p| self.asset_packs.contains(p))
+            .cloned()
+            .collect::<Vec<String>>();
+        for p in &self.asset_packs {
+            if !order.contains(p) {
+                order.push(p.clone());
+            }
+        }
+        self.asset_packs = order;
+        // Re-pin the default pack.
+        let default = self.default_pack.clone();
+        self.move_pack_first(&default);
+
+        self.disabled_packs.clear();
+        for p in &settings.disabled {
+            self.set_pack_enabled(p, false);
+        }
+    }
+
+    fn move_pack_first(&mut self, pack: &str) {
+        if let Some(idx) = self.asset_packs.iter().position(|p| p == pack) {
+            let p = self.asset_packs.remove(idx);
+            self.asset_packs.insert(0, p);
+        }
+    }
+
+    /// Loads the pack settings from a ron file and applies them.
+    /// Keeps the current settings if the file can't be loaded.
+    pub fn load_pack_settings(&mut self, path: &str) {
+        if let Ok(mut f) = File::open(path) {
+            let mut c = String::new();
+            if let Ok(_) = f.read_to_string(&mut c) {
+                match ron::de::from_str::<AssetPackSettings>(&c) {
+                    Ok(settings) => self.apply_pack_settings(&settings),
+                    Err(e) => error!("Failed to deserialize asset pack settings {}: {:?}", path, e),
+                }
+            } else {
+                error!("Failed to read content of asset pack settings: {}", path);
+            }
+        } else {
+            warn!(
+                "Failed to load asset pack settings: {}. The default load order will be used.",
+                path
+            );
+        }
+    }
+
+    /// Saves the current pack settings to a ron file.
+    pub fn save_pack_settings(&self, path: &str) {
+        let s = ron::ser::to_string_pretty(&self.pack_settings(), Default::default())
+            .expect("Unable to serialize the asset pack settings");
+        match File::create(path) {
+            Ok(mut f) => {
+                if let Err(e) = f.write_all(s.as_bytes()) {
+                    error!("Failed to write asset pack settings to {}: {:?}", path, e);
+                }
+            }
+            Err(e) => error!(
+                "Failed to create the asset pack settings file \"{}\". Error: {:?}",
+                path, e
+            ),
+        }
+    }
+
     pub fn get_asset_handle<T>(path: &str, ali: &AssetLoaderInternal<T>) -> Option<Handle<T>> {
         ali.assets.get(path).cloned()
     }
@@ -289,32 +511,6 @@ where
     type Storage = VecStorage<Self>;
 }
 
-/*pub struct CustomTerminal{
-    pub terminal: Box<Terminal>,
-    pub raw_terminal: RawTerminal,
-    pub cursor: Box<TerminalCursor>,
-    pub input: AsyncReader,
-}
-
-impl CustomTerminal{
-    pub fn new() -> Self{
-        let context = Context::new();
-        let mut terminal = terminal(&context);
-        let mut raw_terminal = stdout().into_raw_mode(context.clone()).unwrap();
-        let mut input = input(&context).read_async();
-        let mut cursor = cursor(&context);
-        CustomTerminal{
-            terminal,
-            raw_terminal,
-            cursor,
-            input,
-        }
-    }
-}
-
-// Safe because context is never leaked out
-unsafe impl Send for CustomTerminal{}*/
-
 #[cfg(test)]
 mod test {
     use *;
@@ -359,100 +555,179 @@ mod test {
         );
     }
 
-    /*#[test]
+    fn pack_file(pack: &str, file: &str) -> Option<String> {
+        let path = format!(
+            "{}/test/assets/{}/{}",
+            env!("CARGO_MANIFEST_DIR"),
+            pack,
+            file
+        );
+        if cfg!(windows) {
+            Some(path.replace("/", "\\"))
+        } else {
+            Some(path)
+        }
+    }
+
+    #[test]
     fn asset_loader_resolve_unique_other() {
         let asset_loader = load_asset_loader();
-        assert_eq!(asset_loader.resolve_path("config/uniqueother"),Some(format!("{}/test/assets/mod1/config/uniqueother",env!("CARGO_MANIFEST_DIR")).to_string()))
+        assert_eq!(
+            asset_loader.resolve_path("config/uniqueother"),
+            pack_file("mod1", "config/uniqueother")
+        );
     }
 
     #[test]
     fn asset_loader_resolve_path_override_single() {
         let asset_loader = load_asset_loader();
-        assert_eq!(asset_loader.resolve_path("config/ov1"),Some(format!("{}/test/assets/mod1/config/ov1",env!("CARGO_MANIFEST_DIR")).to_string()))
+        assert_eq!(
+            asset_loader.resolve_path("config/ov1"),
+            pack_file("mod1", "config/ov1")
+        );
     }
 
     #[test]
     fn asset_loader_resolve_path_override_all() {
         let asset_loader = load_asset_loader();
-        assert_eq!(asset_loader.resolve_path("config/ovall"),Some(format!("{}/test/assets/mod2/config/ovall",env!("CARGO_MANIFEST_DIR")).to_string()))
-    }*/
+        assert_eq!(
+            asset_loader.resolve_path("config/ovall"),
+            pack_file("mod2", "config/ovall")
+        );
+    }
 
-    /*#[test]
-    pub fn crossterm() {
-        let terminal = Arc::new(Mutex::new(CustomTerminal::new()));
-
-        let mut input_buf = Arc::new(Mutex::new(String::new()));
-        let mut key_buf = [0 as u8; 32];
-        
-        start_logger(terminal.clone(),input_buf.clone());
-        
-        loop {
-            let (term_width, term_height) = terminal.lock().unwrap().terminal.terminal_size();
-            //swap_write(&mut term, "random stuff", &input_buf.lock().unwrap());
-            info!("random stuff");
-            // NO LOG CAN HAPPEN IN THIS SCOPE! (deadlock)
-            {
-            let mut term = terminal.lock().unwrap();
-            if let Ok(count) = term.input.read(&mut key_buf) {
-                for idx in 0..count {
-                    let b = key_buf.get(idx).unwrap();
-                    //info!("{:?} <- Char entered!",b);
-                    if *b == 3 {
-                        //drop(out);
-                        std::process::exit(0); // Ctrl+C = exit immediate
-                    } else if *b == 13 {
-                        //println!("BUFFER: {:?}", &input_buf.lock().unwrap());
-                        input_buf.lock().unwrap().clear();
-                    } else {
-                        input_buf.lock().unwrap().push(*b as char);
-                    }
-                }
-            }
+    #[test]
+    fn asset_loader_default_pack_first() {
+        let mut asset_loader = load_asset_loader();
+        assert_eq!(
+            asset_loader.get_asset_packs(),
+            &vec!["main".to_string(), "mod1".to_string(), "mod2".to_string()]
+        );
+        asset_loader.move_pack("mod2", 0);
+        assert_eq!(
+            asset_loader.get_asset_packs(),
+            &vec!["main".to_string(), "mod2".to_string(), "mod1".to_string()]
+        );
+    }
+
+    #[test]
+    fn asset_loader_reorder_override() {
+        let mut asset_loader = load_asset_loader();
+        asset_loader.move_pack("mod1", 2);
+        assert_eq!(
+            asset_loader.resolve_path("config/ovall"),
+            pack_file("mod1", "config/ovall")
+        );
+        assert_eq!(
+            asset_loader.resolve_path_and_pack("config/ovall").map(|(p, _)| p),
+            Some("mod1".to_string())
+        );
+    }
+
+    #[test]
+    fn asset_loader_disabled_pack() {
+        let mut asset_loader = load_asset_loader();
+        asset_loader.set_pack_enabled("mod1", false);
+        assert_eq!(
+            asset_loader.resolve_path("config/ov1"),
+            pack_file("main", "config/ov1")
+        );
+        assert_eq!(asset_loader.resolve_path("config/uniqueother"), None);
+
+        // The default pack always stays enabled.
+        asset_loader.set_pack_enabled("main", false);
+        assert!(asset_loader.is_pack_enabled("main"));
+    }
+
+    #[test]
+    fn asset_loader_apply_settings() {
+        let mut asset_loader = load_asset_loader();
+        asset_loader.apply_pack_settings(&AssetPackSettings {
+            order: vec!["mod2".to_string(), "gone".to_string(), "main".to_string()],
+            disabled: vec!["mod1".to_string()],
+        });
+        assert_eq!(
+            asset_loader.pack_settings(),
+            AssetPackSettings {
+                order: vec!["main".to_string(), "mod2".to_string(), "mod1".to_string()],
+                disabled: vec!["mod1".to_string()],
             }
-            sleep(Duration::from_millis(100));
-        }
+        );
     }
 
-    pub fn swap_write(
-        terminal: &mut CustomTerminal,
-        msg: &str,
-        input_buf: &String,
-    ) {
-        let (term_width, term_height) = terminal.terminal.terminal_size();
-        terminal.cursor.goto(0, term_height);
-        terminal.terminal.clear(ClearType::CurrentLine);
-        terminal.terminal.write(format!("{}\n\r>{}", msg, input_buf));
-    }
-
-    pub fn start_logger(terminal: Arc<Mutex<CustomTerminal>>, input_buf: Arc<Mutex<String>>) {
-        let color_config = fern::colors::ColoredLevelConfig::new();
-
-        fern::Dispatch::new()
-            .format(move |out, message, record| {
-                out.finish(format_args!(
-                    "{color}[{level}][{target}] {message}{color_reset}",
-                    color = format!(
-                        "\x1B[{}m",
-                        color_config.get_color(&record.level()).to_fg_str()
-                    ),
-                    level = record.level(),
-                    target = record.target(),
-                    message = message,
-                    color_reset = "\x1B[0m",
-                ))
+    #[test]
+    fn asset_loader_pack_info() {
+        let asset_loader = load_asset_loader();
+        assert_eq!(
+            asset_loader.pack_info("mod1"),
+            Some(&AssetPackInfo {
+                name: "First Mod".to_string(),
+                version: "1.0.0".to_string(),
+                author: "amethyst".to_string(),
+                hidden: false,
             })
-            .level(log::LevelFilter::Debug)
-            .chain(fern::Output::call(move |record| {
-                //let color = color_config.get_color(&record.level()).to_fg_str();
-                //println!("\x1B[{}m[{}][{}] {}\x1B[0m",color,record.level(),record.target(),record.args());
-                //println!("{}",record.args());
-                swap_write(&mut terminal.lock().unwrap(),&format!("{}",record.args()),&input_buf.lock().unwrap());
-            }))
-            .apply()
-            .unwrap_or_else(|_| {
-                error!("Global logger already set, amethyst-extra logger not used!")
-            });
-    }*/
+        );
+        assert_eq!(asset_loader.pack_info("mod2"), None);
+    }
+
+    #[test]
+    fn asset_loader_resolved_files() {
+        let asset_loader = load_asset_loader();
+        let files = asset_loader.resolved_files();
+        assert_eq!(files.get("config/unique"), Some(&"main".to_string()));
+        assert_eq!(files.get("config/ov1"), Some(&"mod1".to_string()));
+        assert_eq!(files.get("config/ovall"), Some(&"mod2".to_string()));
+        assert_eq!(files.get("pack.ron"), Some(&"mod1".to_string()));
+    }
+
+    #[test]
+    fn console_args() {
+        let args = ConsoleArgs::new("  set_res  volume (level: 0.5) ");
+        assert_eq!(args.command(), Some("set_res"));
+        assert_eq!(args.len(), 3);
+        assert_eq!(args.get(0), Ok("volume"));
+        assert_eq!(args.rest(1), Ok("(level: 0.5)"));
+        assert!(args.parse::<f32>(0).is_err());
+        assert!(args.get(3).is_err());
+        assert_eq!(ConsoleArgs::new(" ").command(), None);
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct Volume {
+        level: f32,
+    }
+
+    #[test]
+    fn console_commands() {
+        let mut world = World::new();
+        world.add_resource(Time::default());
+        world.add_resource(Clocks::default());
+        world.add_resource(Volume { level: 1.0 });
+        let mut commands = ConsoleCommands::default();
+        commands.register_resource::<Volume>("volume");
+        world.add_resource(commands);
+
+        assert_eq!(run_console_command("time_scale 0.5", &mut world), Ok(String::new()));
+        assert_eq!(world.read_resource::<Time>().time_scale(), 0.5);
+        assert!(run_console_command("time_scale -1", &mut world).is_err());
+        assert!(run_console_command("unknown", &mut world).is_err());
+
+        run_console_command("set_res volume (level: 0.25)", &mut world).unwrap();
+        assert_eq!(world.read_resource::<Volume>().level, 0.25);
+        assert!(
+            run_console_command("get_res volume", &mut world)
+                .unwrap()
+                .contains("0.25")
+        );
+
+        let commands = world.read_resource::<ConsoleCommands>();
+        assert_eq!(commands.complete("time", &world), vec!["time_scale ".to_string()]);
+        assert_eq!(
+            commands.complete("get_res vo", &world),
+            vec!["get_res volume".to_string()]
+        );
+        assert!(commands.complete("time_scale ", &world).is_empty());
+    }
 }
 
 /*pub trait AssetToFormat<T> where T: Sized{
@@ -785,6 +1060,7 @@ impl<'a> System<'a> for NormalOrthoCameraSystem {
         }*/    }
 }
 
+/// Shows the seconds elapsed on the `ui` clock since `start`.
 pub struct UiTimer {
     pub start: f64,
 }
@@ -799,7 +1075,7 @@ impl<'a> System<'a> for UiTimerSystem {
     type SystemData = (
         ReadStorage<'a, UiTimer>,
         WriteStorage<'a, UiText>,
-        Read<'a, Time>,
+        ReadClock<'a, UiClock>,
     );
     fn run(&mut self, (timers, mut texts, time): Self::SystemData) {
         for (timer, mut text) in (&timers, &mut texts).join() {
@@ -889,6 +1165,645 @@ fn run(&mut self, (follow_mouses,mut transforms, global_transforms, dimension,in
     }
 }
 
+/// Result of a console command: the text printed in the console, or the error printed instead.
+pub type ConsoleResult = ::std::result::Result<String, String>;
+
+/// A line typed in the console, split into the command name and its arguments.
+pub struct ConsoleArgs<'s> {
+    line: &'s str,
+    /// Byte ranges of the words of the line, the command name first.
+    words: Vec<(usize, usize)>,
+}
+
+impl<'s> ConsoleArgs<'s> {
+    /// Splits a line into words separated by whitespace.
+    pub fn new(line: &'s str) -> Self {
+        let mut words = vec![];
+        let mut start = None;
+        for (i, c) in line.char_indices() {
+            match (c.is_whitespace(), start) {
+                (true, Some(s)) => {
+                    words.push((s, i));
+                    start = None;
+                }
+                (false, None) => start = Some(i),
+                _ => {}
+            }
+        }
+        if let Some(s) = start {
+            words.push((s, line.len()));
+        }
+        ConsoleArgs { line, words }
+    }
+
+    /// Gets the name of the command, if the line isn't blank.
+    pub fn command(&self) -> Option<&'s str> {
+        self.words.first().map(|&(s, e)| &self.line[s..e])
+    }
+
+    /// Gets the number of arguments.
+    pub fn len(&self) -> usize {
+        self.words.len().saturating_sub(1)
+    }
+
+    /// Whether the command was typed without arguments.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Gets the argument at `index`.
+    pub fn get(&self, index: usize) -> ::std::result::Result<&'s str, String> {
+        self.words
+            .get(index + 1)
+            .map(|&(s, e)| &self.line[s..e])
+            .ok_or_else(|| format!("Missing argument {}", index + 1))
+    }
+
+    /// Parses the argument at `index`, e.g. `args.parse::<f32>(0)`.
+    pub fn parse<T>(&self, index: usize) -> ::std::result::Result<T, String>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        let arg = self.get(index)?;
+        arg.parse()
+            .map_err(|e| format!("Invalid argument {} `{}`: {}", index + 1, arg, e))
+    }
+
+    /// Gets the rest of the line from the argument at `index`, e.g. a RON value with spaces.
+    pub fn rest(&self, index: usize) -> ::std::result::Result<&'s str, String> {
+        self.words
+            .get(index + 1)
+            .map(|&(s, _)| self.line[s..].trim_right())
+            .ok_or_else(|| format!("Missing argument {}", index + 1))
+    }
+}
+
+/// A command which can be typed in the console.
+pub struct ConsoleCommand {
+    /// The arguments of the command shown by `help`, e.g. `<scale>`.
+    pub usage: String,
+    /// What the command does, shown by `help`.
+    pub help: String,
+    run: Box<Fn(&ConsoleArgs, &mut World) -> ConsoleResult + Send + Sync>,
+}
+
+/// A resource which can be read and written as RON with `get_res` and `set_res`.
+struct ConsoleResource {
+    get: Box<Fn(&World) -> ConsoleResult + Send + Sync>,
+    set: Box<Fn(&mut World, &str) -> ConsoleResult + Send + Sync>,
+}
+
+/// The commands of the console by name, added to by the game with `register`.
+///
+/// The default commands are `help`, `clear`, `time_scale`, `get_res` and `set_res`.
+pub struct ConsoleCommands {
+    commands: BTreeMap<String, Arc<ConsoleCommand>>,
+    completions: BTreeMap<String, Box<Fn(&World) -> Vec<String> + Send + Sync>>,
+    resources: BTreeMap<String, Arc<ConsoleResource>>,
+}
+
+impl ConsoleCommands {
+    /// Creates a registry without any command.
+    pub fn empty() -> Self {
+        ConsoleCommands {
+            commands: BTreeMap::new(),
+            completions: BTreeMap::new(),
+            resources: BTreeMap::new(),
+        }
+    }
+
+    /// Adds a command, replacing the one with the same name.
+    pub fn register<F>(&mut self, name: &str, usage: &str, help: &str, run: F)
+    where
+        F: Fn(&ConsoleArgs, &mut World) -> ConsoleResult + Send + Sync + 'static,
+    {
+        self.commands.insert(
+            name.to_string(),
+            Arc::new(ConsoleCommand {
+                usage: usage.to_string(),
+                help: help.to_string(),
+                run: Box::new(run),
+            }),
+        );
+    }
+
+    /// Sets the candidates completing the arguments of a registered command.
+    ///
+    /// The function must not write the `ConsoleCommands`, which it is called from.
+    pub fn set_completion<F>(&mut self, name: &str, completion: F)
+    where
+        F: Fn(&World) -> Vec<String> + Send + Sync + 'static,
+    {
+        if !self.commands.contains_key(name) {
+            error!("Can't set the completion of unknown console command {}", name);
+            return;
+        }
+        self.completions
+            .insert(name.to_string(), Box::new(completion));
+    }
+
+    /// Lets `get_res` and `set_res` read and write the resource `T` as RON under `name`.
+    pub fn register_resource<T>(&mut self, name: &str)
+    where
+        T: Serialize + DeserializeOwned + Send + Sync + 'static,
+    {
+        self.resources.insert(
+            name.to_string(),
+            Arc::new(ConsoleResource {
+                get: Box::new(|world| {
+                    if !world.res.has_value::<T>() {
+                        return Err("The resource wasn't added yet".to_string());
+                    }
+                    ron::ser::to_string(&*world.read_resource::<T>())
+                        .map_err(|e| format!("Failed to serialize the resource: {:?}", e))
+                }),
+                set: Box::new(|world, value| {
+                    let value = ron::de::from_str::<T>(value)
+                        .map_err(|e| format!("Failed to deserialize the resource: {:?}", e))?;
+                    world.add_resource(value);
+                    Ok(String::new())
+                }),
+            }),
+        );
+    }
+
+    /// Gets the command with the given name.
+    pub fn get(&self, name: &str) -> Option<Arc<ConsoleCommand>> {
+        self.commands.get(name).cloned()
+    }
+
+    /// Gets the names of the commands, in alphabetical order.
+    pub fn names(&self) -> Vec<&str> {
+        self.commands.keys().map(|name| name.as_str()).collect()
+    }
+
+    /// Gets the lines completing the last word of `line`: the command name, or else an argument
+    /// using the completion of the command.
+    pub fn complete(&self, line: &str, world: &World) -> Vec<String> {
+        let args = ConsoleArgs::new(line);
+        let new_word = line.is_empty() || line.ends_with(char::is_whitespace);
+        if args.words.len() <= 1 && !new_word {
+            let prefix = args.command().unwrap_or("");
+            return self.commands
+                .keys()
+                .filter(|name| name.starts_with(prefix))
+                .map(|name| format!("{} ", name))
+                .collect();
+        }
+
+        let completion = match args.command().and_then(|name| self.completions.get(name)) {
+            Some(completion) => completion,
+            None => return vec![],
+        };
+        let start = if new_word {
+            line.len()
+        } else {
+            args.words.last().map(|&(s, _)| s).unwrap_or(0)
+        };
+        let (head, prefix) = line.split_at(start);
+        completion(world)
+            .into_iter()
+            .filter(|candidate| candidate.starts_with(prefix))
+            .map(|candidate| format!("{}{}", head, candidate))
+            .collect()
+    }
+}
+
+impl Default for ConsoleCommands {
+    fn default() -> Self {
+        let mut commands = ConsoleCommands::empty();
+        commands.register("help", "[command]", "Lists the commands.", |args, world| {
+            let commands = world.read_resource::<ConsoleCommands>();
+            let names = match args.get(0) {
+                Ok(name) => vec![name],
+                Err(_) => commands.names(),
+            };
+            names
+                .into_iter()
+                .map(|name| match commands.commands.get(name) {
+                    Some(command) => Ok(format!("{} {}: {}", name, command.usage, command.help)),
+                    None => Err(format!("Unknown command `{}`", name)),
+                })
+                .collect::<::std::result::Result<Vec<_>, _>>()
+                .map(|lines| lines.join("\n"))
+        });
+        commands.register("clear", "", "Clears the log lines.", |_, world| {
+            if world.res.has_value::<LogBuffer>() {
+                world.read_resource::<LogBuffer>().clear();
+            }
+            Ok(String::new())
+        });
+        commands.register(
+            "time_scale",
+            "<scale>",
+            "Sets the speed of the game time and of the game clock.",
+            |args, world| {
+                let scale = args.parse::<f32>(0)?;
+                if !scale.is_finite() || scale < 0.0 {
+                    return Err("The scale must be positive".to_string());
+                }
+                world.write_resource::<Time>().set_time_scale(scale);
+                if let Some(clock) = world.write_resource::<Clocks>().get_mut(GAME_CLOCK) {
+                    clock.set_time_scale(scale);
+                }
+                Ok(String::new())
+            },
+        );
+        commands.register(
+            "get_res",
+            "<name>",
+            "Shows a registered resource as RON.",
+            |args, world| {
+                let name = args.get(0)?;
+                let resource = world.read_resource::<ConsoleCommands>().resource(name)?;
+                (resource.get)(world)
+            },
+        );
+        commands.register(
+            "set_res",
+            "<name> <ron>",
+            "Replaces a registered resource with the given RON value.",
+            |args, world| {
+                let name = args.get(0)?;
+                let value = args.rest(1)?;
+                let resource = world.read_resource::<ConsoleCommands>().resource(name)?;
+                (resource.set)(world, value)
+            },
+        );
+        let resource_names = |world: &World| {
+            world
+                .read_resource::<ConsoleCommands>()
+                .resources
+                .keys()
+                .cloned()
+                .collect()
+        };
+        commands.set_completion("get_res", resource_names);
+        commands.set_completion("set_res", resource_names);
+        commands
+    }
+}
+
+impl ConsoleCommands {
+    fn resource(&self, name: &str) -> ::std::result::Result<Arc<ConsoleResource>, String> {
+        self.resources
+            .get(name)
+            .cloned()
+            .ok_or_else(|| format!("Unknown resource `{}`", name))
+    }
+}
+
+/// Runs a line typed in the console with the command it names.
+pub fn run_console_command(line: &str, world: &mut World) -> ConsoleResult {
+    let args = ConsoleArgs::new(line);
+    let name = match args.command() {
+        Some(name) => name,
+        None => return Ok(String::new()),
+    };
+    let command = world
+        .read_resource::<ConsoleCommands>()
+        .get(name)
+        .ok_or_else(|| format!("Unknown command `{}`, see `help`", name))?;
+    (command.run)(&args, world)
+}
+
+/// The developer console shown by the `ConsoleSystem`: the last log lines and a command input.
+pub struct Console {
+    /// Whether the console is shown.
+    pub open: bool,
+    /// Font of the console, which can't be shown without one.
+    pub font: Option<FontHandle>,
+    /// Number of log lines shown above the input.
+    pub lines: usize,
+    /// The commands typed, from the oldest to the newest.
+    pub history: Vec<String>,
+    history_index: Option<usize>,
+    next_input: Option<String>,
+    background: Option<Entity>,
+    line_entities: Vec<Entity>,
+    input: Option<Entity>,
+}
+
+impl Console {
+    /// Replaces the typed text at the next frame.
+    pub fn set_input(&mut self, text: String) {
+        self.next_input = Some(text);
+    }
+}
+
+impl Default for Console {
+    fn default() -> Self {
+        Console {
+            open: false,
+            font: None,
+            lines: 12,
+            history: vec![],
+            history_index: None,
+            next_input: None,
+            background: None,
+            line_entities: vec![],
+            input: None,
+        }
+    }
+}
+
+/// Height of a line of the console, in pixels.
+const CONSOLE_LINE_HEIGHT: f32 = 22.0;
+
+/// Shows the `Console` when the given input action is pressed, and runs the commands typed in it
+/// at the end of the frame.
+///
+/// Enter runs the typed command, Tab completes it and Up and Down browse the history. The lines
+/// shown come from the `LogBuffer` resource; the console logs the commands and their results.
+pub struct ConsoleSystem {
+    action: String,
+    action_down: bool,
+    reader: Option<ReaderId<Event>>,
+}
+
+impl ConsoleSystem {
+    /// Creates the system, toggling the console with the action named `action`.
+    pub fn new(action: &str) -> Self {
+        ConsoleSystem {
+            action: action.to_string(),
+            action_down: false,
+            reader: None,
+        }
+    }
+}
+
+impl<'a> System<'a> for ConsoleSystem {
+    type SystemData = (
+        Entities<'a>,
+        Write<'a, Console>,
+        Read<'a, InputHandler<String, String>>,
+        Read<'a, EventChannel<Event>>,
+        Read<'a, LogBuffer>,
+        Read<'a, LazyUpdate>,
+        Write<'a, UiFocused>,
+        ReadExpect<'a, Loader>,
+        Read<'a, AssetStorage<Texture>>,
+        WriteStorage<'a, UiTransform>,
+        WriteStorage<'a, UiText>,
+        WriteStorage<'a, TextEditing>,
+        WriteStorage<'a, UiImage>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            mut console,
+            input,
+            events,
+            log,
+            lazy,
+            mut focused,
+            loader,
+            textures,
+            mut transforms,
+            mut texts,
+            mut editing,
+            mut images,
+        ): Self::SystemData,
+    ) {
+        let console = &mut *console;
+        let down = input.action_is_down(&self.action).unwrap_or(false);
+        if down && !self.action_down {
+            console.open = !console.open;
+        }
+        self.action_down = down;
+
+        let mut keys = vec![];
+        for event in events.read(self.reader.as_mut().unwrap()) {
+            if let Event::WindowEvent {
+                event:
+                    WindowEvent::KeyboardInput {
+                        input:
+                            KeyboardInput {
+                                state: ElementState::Pressed,
+                                virtual_keycode: Some(key),
+                                ..
+                            },
+                        ..
+                    },
+                ..
+            } = *event
+            {
+                keys.push(key);
+            }
+        }
+
+        // Scene changes may delete the console with the other entities.
+        let alive = console.input.map(|e| entities.is_alive(e)).unwrap_or(false)
+            && console.line_entities.iter().all(|e| entities.is_alive(*e));
+        if !console.open || !alive {
+            for entity in console
+                .background
+                .take()
+                .into_iter()
+                .chain(console.input.take())
+                .chain(console.line_entities.drain(..))
+            {
+                if entities.is_alive(entity) {
+                    if let Err(err) = entities.delete(entity) {
+                        error!("Failed to delete console entity: {:?}", err);
+                    }
+                }
+            }
+            console.history_index = None;
+        }
+        if !console.open {
+            return;
+        }
+
+        let input_entity = match console.input {
+            Some(input_entity) => input_entity,
+            None => {
+                // The console is shown from the next frame, so that the key opening it isn't
+                // typed in the input.
+                if let Some(font) = console.font.clone() {
+                    let height = CONSOLE_LINE_HEIGHT * (console.lines + 1) as f32 + 10.0;
+                    let background = entities.create();
+                    transforms
+                        .insert(
+                            background,
+                            UiTransform::new(
+                                "console_background".to_string(),
+                                Anchor::TopMiddle,
+                                0.0,
+                                height / 2.0,
+                                50.0,
+                                0.0,
+                                height,
+                                0,
+                            ).with_stretch(Stretch::X { x_margin: 0.0 })
+                                .as_transparent(),
+                        )
+                        .expect("Failed to insert console background transform.");
+                    images
+                        .insert(
+                            background,
+                            UiImage {
+                                texture: loader.load_from_data(
+                                    TextureData::color([0.0, 0.0, 0.0, 0.8]),
+                                    (),
+                                    &textures,
+                                ),
+                            },
+                        )
+                        .expect("Failed to insert console background image.");
+                    console.background = Some(background);
+
+                    for i in 0..console.lines + 1 {
+                        let line = entities.create();
+                        transforms
+                            .insert(
+                                line,
+                                UiTransform::new(
+                                    format!("console_line_{}", i),
+                                    Anchor::TopMiddle,
+                                    0.0,
+                                    CONSOLE_LINE_HEIGHT * (i as f32 + 0.5) + 5.0,
+                                    51.0,
+                                    0.0,
+                                    CONSOLE_LINE_HEIGHT,
+                                    0,
+                                ).with_stretch(Stretch::X { x_margin: 20.0 })
+                                    .as_transparent(),
+                            )
+                            .expect("Failed to insert console line transform.");
+                        texts
+                            .insert(
+                                line,
+                                UiText::new(font.clone(), String::new(), [1.0; 4], 18.0),
+                            )
+                            .expect("Failed to insert console line text.");
+                        if i < console.lines {
+                            console.line_entities.push(line);
+                        } else {
+                            editing
+                                .insert(
+                                    line,
+                                    TextEditing::new(
+                                        200,
+                                        [0.0, 0.0, 0.0, 1.0],
+                                        [1.0, 1.0, 1.0, 1.0],
+                                        false,
+                                    ),
+                                )
+                                .expect("Failed to insert console input editing.");
+                            console.input = Some(line);
+                        }
+                    }
+                }
+                return;
+            }
+        };
+        // Tab may have moved the focus to another element of the user interface.
+        focused.entity = Some(input_entity);
+
+        let mut line = texts
+            .get(input_entity)
+            .map(|text| text.text.trim().to_string())
+            .unwrap_or_default();
+        let mut next_input = console.next_input.take();
+        for key in keys {
+            match key {
+                VirtualKeyCode::Return | VirtualKeyCode::NumpadEnter if !line.is_empty() => {
+                    info!("> {}", line);
+                    if console.history.last() != Some(&line) {
+                        console.history.push(line.clone());
+                    }
+                    console.history_index = None;
+                    next_input = Some(String::new());
+                    let command = ::std::mem::replace(&mut line, String::new());
+                    lazy.exec_mut(move |world| match run_console_command(&command, world) {
+                        Ok(ref output) if output.is_empty() => {}
+                        Ok(output) => info!("{}", output),
+                        Err(err) => warn!("{}", err),
+                    });
+                }
+                VirtualKeyCode::Tab => {
+                    let prefix = line.clone();
+                    lazy.exec_mut(move |world| {
+                        let completions = world
+                            .read_resource::<ConsoleCommands>()
+                            .complete(&prefix, world);
+                        if completions.len() > 1 {
+                            info!("{}", completions.join("  "));
+                        }
+                        let mut completed = match completions.first() {
+                            Some(first) => first.clone(),
+                            None => return,
+                        };
+                        for completion in &completions[1..] {
+                            while !completion.starts_with(&completed) {
+                                completed.pop();
+                            }
+                        }
+                        world.write_resource::<Console>().set_input(completed);
+                    });
+                }
+                VirtualKeyCode::Up if !console.history.is_empty() => {
+                    let index = console
+                        .history_index
+                        .map(|i| i.saturating_sub(1))
+                        .unwrap_or(console.history.len() - 1);
+                    console.history_index = Some(index);
+                    next_input = Some(console.history[index].clone());
+                }
+                VirtualKeyCode::Down => {
+                    if let Some(index) = console.history_index {
+                        if index + 1 < console.history.len() {
+                            console.history_index = Some(index + 1);
+                            next_input = Some(console.history[index + 1].clone());
+                        } else {
+                            console.history_index = None;
+                            next_input = Some(String::new());
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        if let Some(next_input) = next_input {
+            if let Some(edit) = editing.get_mut(input_entity) {
+                edit.cursor_position = next_input.chars().count() as isize;
+                edit.highlight_vector = 0;
+            }
+            if let Some(text) = texts.get_mut(input_entity) {
+                text.text = next_input;
+            }
+        }
+
+        let lines = log.lines();
+        let shown = &lines[lines.len().saturating_sub(console.lines)..];
+        for (i, entity) in console.line_entities.iter().enumerate() {
+            if let Some(text) = texts.get_mut(*entity) {
+                // The newest line is right above the input.
+                let log_line = (shown.len() + i)
+                    .checked_sub(console.lines)
+                    .map(|j| &shown[j]);
+                text.text = log_line
+                    .map(|l| format!("[{}] {}", l.level, l.message))
+                    .unwrap_or_default();
+                text.color = match log_line.map(|l| l.level) {
+                    Some(Level::Error) => [1.0, 0.3, 0.3, 1.0],
+                    Some(Level::Warn) => [1.0, 1.0, 0.3, 1.0],
+                    _ => [1.0; 4],
+                };
+            }
+        }
+    }
+
+    fn setup(&mut self, res: &mut amethyst::ecs::Resources) {
+        Self::SystemData::setup(res);
+        self.reader = Some(res.fetch_mut::<EventChannel<Event>>().register_reader());
+    }
+}
+
 #[derive(Deserialize)]
 pub struct LootTreeNode<R> {
     pub chances: i32,
@@ -955,6 +1870,10 @@ impl<I> Removal<I> {
     pub fn new(id: I) -> Self {
         Removal { id }
     }
+
+    pub fn id(&self) -> &I {
+        &self.id
+    }
 }
 
 impl<I: Send + Sync + 'static> Component for Removal<I> {
@@ -11,7 +11,7 @@ use {Asset, Directory, ErrorKind, Format, FormatValue, Progress, ResultExt, Sour
 
 /// The asset loader, holding the sources and a reference to the `ThreadPool`.
 pub struct Loader {
-    directory: Arc<Directory>,
+    default_source: Arc<Source>,
     hot_reload: bool,
     pool: Arc<ThreadPool>,
     sources: FnvHashMap<String, Arc<Source>>,
@@ -25,7 +25,7 @@ impl Loader {
         P: Into<PathBuf>,
     {
         Loader {
-            directory: Arc::new(Directory::new(directory)),
+            default_source: Arc::new(Directory::new(directory)) as Arc<Source>,
             hot_reload: true,
             pool,
             sources: Default::default(),
@@ -42,6 +42,17 @@ impl Loader {
             .insert(id.into(), Arc::new(source) as Arc<Source>);
     }
 
+    /// Replaces the default source, used by `load` and by the assets referenced from prefabs.
+    ///
+    /// The new source usually wraps a `Directory`, to add other ways of storing the assets
+    /// while keeping the same paths.
+    pub fn set_default_source<S>(&mut self, source: S)
+    where
+        S: Source,
+    {
+        self.default_source = Arc::new(source) as Arc<Source>;
+    }
+
     /// If set to `true`, this `Loader` will ask formats to
     /// generate "reload instructions" which *allow* reloading.
     /// Calling `set_hot_reload(true)` does not actually enable
@@ -131,7 +142,7 @@ impl Loader {
         );
 
         let source = match source {
-            "" => self.directory.clone(),
+            "" => self.default_source.clone(),
             source => self.source(source),
         };
 
@@ -50,6 +50,9 @@ impl<'a, 'b> SystemBundle<'a, 'b> for HotReloadBundle {
 #[derive(Clone)]
 pub struct HotReloadStrategy {
     inner: HotReloadStrategyInner,
+    /// Set by `reload_once`, whatever the strategy is.
+    once: bool,
+    once_frame_number: u64,
 }
 
 impl HotReloadStrategy {
@@ -63,6 +66,8 @@ impl HotReloadStrategy {
                 last: Instant::now(),
                 frame_number: MAX,
             },
+            once: false,
+            once_frame_number: MAX,
         }
     }
 
@@ -75,13 +80,19 @@ impl HotReloadStrategy {
                 triggered: false,
                 frame_number: MAX,
             },
+            once: false,
+            once_frame_number: MAX,
         }
     }
 
     /// Never do any hot-reloading.
     pub fn never() -> Self {
+        use std::u64::MAX;
+
         HotReloadStrategy {
             inner: HotReloadStrategyInner::Never,
+            once: false,
+            once_frame_number: MAX,
         }
     }
 
@@ -96,10 +107,18 @@ impl HotReloadStrategy {
         }
     }
 
-    /// Crate-internal method to check if reload is necessary.
-    /// `reload_counter` is a per-storage value which is only used
-    /// for and by this method.
-    pub(crate) fn needs_reload(&self, current_frame: u64) -> bool {
+    /// The frame after calling this, all changed assets will be reloaded.
+    /// Unlike `trigger`, this works with any strategy and doesn't change it.
+    pub fn reload_once(&mut self) {
+        self.once = true;
+    }
+
+    /// Checks if changed assets are reloaded on this frame.
+    /// Lets data loaded outside of the `Loader` follow the same schedule.
+    pub fn needs_reload(&self, current_frame: u64) -> bool {
+        if self.once_frame_number == current_frame {
+            return true;
+        }
         match self.inner {
             HotReloadStrategyInner::Every { frame_number, .. } => frame_number == current_frame,
             HotReloadStrategyInner::Trigger { frame_number, .. } => frame_number == current_frame,
@@ -146,6 +165,10 @@ impl<'a> System<'a> for HotReloadSystem {
     type SystemData = (Read<'a, Time>, Write<'a, HotReloadStrategy>);
 
     fn run(&mut self, (time, mut strategy): Self::SystemData) {
+        if strategy.once {
+            strategy.once_frame_number = time.frame_number() + 1;
+            strategy.once = false;
+        }
         match strategy.inner {
             HotReloadStrategyInner::Trigger {
                 ref mut triggered,
@@ -288,3 +311,32 @@ where
         format.import(path, source, options, true)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use amethyst_core::specs::{RunNow, World};
+
+    #[test]
+    fn reload_once_reloads_a_single_frame() {
+        let mut world = World::new();
+        world.add_resource(Time::default());
+        let mut strategy = HotReloadStrategy::when_triggered();
+        strategy.reload_once();
+        world.add_resource(strategy);
+        let mut system = HotReloadSystem::new(HotReloadStrategy::never());
+
+        system.run_now(&world.res);
+        world.write_resource::<Time>().increment_frame_number();
+        assert!(world.read_resource::<HotReloadStrategy>().needs_reload(1));
+
+        system.run_now(&world.res);
+        world.write_resource::<Time>().increment_frame_number();
+        assert!(!world.read_resource::<HotReloadStrategy>().needs_reload(2));
+
+        // The strategy is still the one it was created with.
+        world.write_resource::<HotReloadStrategy>().trigger();
+        system.run_now(&world.res);
+        assert!(world.read_resource::<HotReloadStrategy>().needs_reload(3));
+    }
+}
@@ -0,0 +1,2325 @@
+//! Configuration built from layers, each overriding the values of the previous ones: the
+//! defaults of the structure, files such as the defaults shipped with the game and the user's
+//! overrides, environment variables and command line arguments.
+//!
+//! Each layer is deserialized by serde over the values of the previous ones: when a layer reads
+//! a struct or a map, the fields and keys it doesn't give are taken from the previous layers.
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::env;
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{ErrorKind, Read};
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::slice;
+
+use ron;
+use serde::de::value::StrDeserializer;
+use serde::de::{
+    self, DeserializeSeed, Deserializer, EnumAccess, IntoDeserializer, MapAccess, SeqAccess,
+    VariantAccess, Visitor,
+};
+use serde::ser::{self, Serializer};
+use serde::{Deserialize, Serialize};
+
+use ConfigError;
+
+/// Field of the configuration files holding the version of their schema.
+pub const VERSION_FIELD: &str = "version";
+
+/// A value of a configuration, in the data model of serde.
+///
+/// Paths to nested values are made of the struct field names, map keys and sequence indices
+/// separated by dots, e.g. `dimensions`, `actions.jump` or `axes.horizontal`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ConfigValue {
+    /// `true` or `false`.
+    Bool(bool),
+    /// A signed integer.
+    I64(i64),
+    /// An unsigned integer.
+    U64(u64),
+    /// A floating point number.
+    F64(f64),
+    /// A character, e.g. `'a'`.
+    Char(char),
+    /// A string.
+    String(String),
+    /// `()` or a unit struct.
+    Unit,
+    /// `None` or `Some(value)`.
+    Option(Option<Box<ConfigValue>>),
+    /// A sequence, e.g. `[1, 2]`.
+    Seq(Vec<ConfigValue>),
+    /// A tuple or tuple struct, e.g. `(1, 2)`.
+    Tuple(Vec<ConfigValue>),
+    /// A map, e.g. `{"jump": 1}`.
+    Map(Vec<(ConfigValue, ConfigValue)>),
+    /// A struct, e.g. `(fps: 144)`.
+    Struct(Vec<(String, ConfigValue)>),
+    /// An enum variant with its content, `Unit` for a unit variant.
+    Variant(String, Box<ConfigValue>),
+}
+
+impl ConfigValue {
+    /// Gets the value at the path.
+    pub fn get(&self, path: &str) -> Option<&ConfigValue> {
+        segments(path)
+            .into_iter()
+            .fold(Some(self), |value, segment| value.and_then(|v| v.child(segment)))
+    }
+
+    /// Sets the value at the path, adding the missing map keys. Returns `false` if the path
+    /// doesn't exist.
+    pub fn set(&mut self, path: &str, new: ConfigValue) -> bool {
+        let mut value = Some(self);
+        for segment in segments(path) {
+            value = value.and_then(|v| v.child_mut(segment));
+        }
+        match value {
+            Some(value) => {
+                *value = new;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn child(&self, segment: &str) -> Option<&ConfigValue> {
+        match *self {
+            ConfigValue::Option(Some(ref value)) => value.child(segment),
+            ConfigValue::Struct(ref fields) => fields
+                .iter()
+                .find(|&&(ref name, _)| name == segment)
+                .map(|&(_, ref value)| value),
+            ConfigValue::Map(ref entries) => entries
+                .iter()
+                .find(|&&(ref key, _)| key_segment(key) == segment)
+                .map(|&(_, ref value)| value),
+            ConfigValue::Seq(ref items) | ConfigValue::Tuple(ref items) => {
+                segment.parse::<usize>().ok().and_then(|i| items.get(i))
+            }
+            _ => None,
+        }
+    }
+
+    fn child_mut(&mut self, segment: &str) -> Option<&mut ConfigValue> {
+        match *self {
+            ConfigValue::Option(Some(ref mut value)) => value.child_mut(segment),
+            ConfigValue::Struct(ref mut fields) => fields
+                .iter_mut()
+                .find(|&&mut (ref name, _)| name == segment)
+                .map(|&mut (_, ref mut value)| value),
+            ConfigValue::Map(ref mut entries) => {
+                let index = match entries
+                    .iter()
+                    .position(|&(ref key, _)| key_segment(key) == segment)
+                {
+                    Some(index) => index,
+                    None => {
+                        entries.push((ConfigValue::String(segment.to_string()), ConfigValue::Unit));
+                        entries.len() - 1
+                    }
+                };
+                Some(&mut entries[index].1)
+            }
+            ConfigValue::Seq(ref mut items) | ConfigValue::Tuple(ref mut items) => {
+                match segment.parse::<usize>() {
+                    Ok(index) => items.get_mut(index),
+                    Err(_) => None,
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Writes the value as RON.
+impl fmt::Display for ConfigValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ConfigValue::Bool(v) => write!(f, "{}", v),
+            ConfigValue::I64(v) => write!(f, "{}", v),
+            ConfigValue::U64(v) => write!(f, "{}", v),
+            ConfigValue::F64(v) => write!(f, "{:?}", v),
+            ConfigValue::Char(c) => write!(f, "'{}'", escape(&c.to_string())),
+            ConfigValue::String(ref s) => write!(f, "\"{}\"", escape(s)),
+            ConfigValue::Unit => write!(f, "()"),
+            ConfigValue::Option(None) => write!(f, "None"),
+            ConfigValue::Option(Some(ref value)) => write!(f, "Some({})", value),
+            ConfigValue::Seq(ref items) => write!(f, "[{}]", join(items.iter())),
+            ConfigValue::Tuple(ref items) if items.len() == 1 => write!(f, "({},)", items[0]),
+            ConfigValue::Tuple(ref items) => write!(f, "({})", join(items.iter())),
+            ConfigValue::Map(ref entries) => write!(
+                f,
+                "{{{}}}",
+                join(entries.iter().map(|&(ref k, ref v)| format!("{}: {}", k, v)))
+            ),
+            ConfigValue::Struct(ref fields) => write!(
+                f,
+                "({})",
+                join(fields.iter().map(|&(ref k, ref v)| format!("{}: {}", k, v)))
+            ),
+            ConfigValue::Variant(ref name, ref content) => match **content {
+                ConfigValue::Unit => write!(f, "{}", name),
+                ConfigValue::Tuple(ref items) => write!(f, "{}({})", name, join(items.iter())),
+                ConfigValue::Struct(_) => write!(f, "{}{}", name, content),
+                ref value => write!(f, "{}({})", name, value),
+            },
+        }
+    }
+}
+
+fn join<I, D>(items: I) -> String
+where
+    I: Iterator<Item = D>,
+    D: fmt::Display,
+{
+    items.map(|item| item.to_string()).collect::<Vec<_>>().join(", ")
+}
+
+fn escape(s: &str) -> String {
+    s.chars()
+        .map(|c| match c {
+            '\\' => "\\\\".to_string(),
+            '"' => "\\\"".to_string(),
+            '\'' => "\\'".to_string(),
+            '\n' => "\\n".to_string(),
+            '\r' => "\\r".to_string(),
+            '\t' => "\\t".to_string(),
+            c => c.to_string(),
+        }).collect()
+}
+
+fn segments(path: &str) -> Vec<&str> {
+    path.split('.').filter(|segment| !segment.is_empty()).collect()
+}
+
+fn key_segment(key: &ConfigValue) -> String {
+    match *key {
+        ConfigValue::String(ref s) => s.clone(),
+        ref key => key.to_string(),
+    }
+}
+
+fn child_path(path: &str, segment: &str) -> String {
+    if path.is_empty() {
+        segment.to_string()
+    } else {
+        format!("{}.{}", path, segment)
+    }
+}
+
+/// Error converting a value from or to a `ConfigValue`.
+#[derive(Debug)]
+struct ValueError(String);
+
+impl fmt::Display for ValueError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Error for ValueError {
+    fn description(&self) -> &str {
+        &self.0
+    }
+}
+
+impl ser::Error for ValueError {
+    fn custom<M: fmt::Display>(message: M) -> Self {
+        ValueError(message.to_string())
+    }
+}
+
+impl de::Error for ValueError {
+    fn custom<M: fmt::Display>(message: M) -> Self {
+        ValueError(message.to_string())
+    }
+}
+
+fn to_value<T: Serialize + ?Sized>(value: &T) -> Result<ConfigValue, ValueError> {
+    value.serialize(ValueSerializer)
+}
+
+/// Serializes a value to a `ConfigValue`.
+struct ValueSerializer;
+
+impl Serializer for ValueSerializer {
+    type Ok = ConfigValue;
+    type Error = ValueError;
+    type SerializeSeq = SeqSerializer;
+    type SerializeTuple = SeqSerializer;
+    type SerializeTupleStruct = SeqSerializer;
+    type SerializeTupleVariant = SeqSerializer;
+    type SerializeMap = MapSerializer;
+    type SerializeStruct = StructSerializer;
+    type SerializeStructVariant = StructSerializer;
+
+    fn serialize_bool(self, v: bool) -> Result<ConfigValue, ValueError> {
+        Ok(ConfigValue::Bool(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<ConfigValue, ValueError> {
+        Ok(ConfigValue::I64(v as i64))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<ConfigValue, ValueError> {
+        Ok(ConfigValue::I64(v as i64))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<ConfigValue, ValueError> {
+        Ok(ConfigValue::I64(v as i64))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<ConfigValue, ValueError> {
+        Ok(ConfigValue::I64(v))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<ConfigValue, ValueError> {
+        Ok(ConfigValue::U64(v as u64))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<ConfigValue, ValueError> {
+        Ok(ConfigValue::U64(v as u64))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<ConfigValue, ValueError> {
+        Ok(ConfigValue::U64(v as u64))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<ConfigValue, ValueError> {
+        Ok(ConfigValue::U64(v))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<ConfigValue, ValueError> {
+        Ok(ConfigValue::F64(v as f64))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<ConfigValue, ValueError> {
+        Ok(ConfigValue::F64(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<ConfigValue, ValueError> {
+        Ok(ConfigValue::Char(v))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<ConfigValue, ValueError> {
+        Ok(ConfigValue::String(v.to_string()))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<ConfigValue, ValueError> {
+        Ok(ConfigValue::Seq(
+            v.iter().map(|&b| ConfigValue::U64(b as u64)).collect(),
+        ))
+    }
+
+    fn serialize_none(self) -> Result<ConfigValue, ValueError> {
+        Ok(ConfigValue::Option(None))
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<ConfigValue, ValueError> {
+        Ok(ConfigValue::Option(Some(Box::new(to_value(value)?))))
+    }
+
+    fn serialize_unit(self) -> Result<ConfigValue, ValueError> {
+        Ok(ConfigValue::Unit)
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Result<ConfigValue, ValueError> {
+        Ok(ConfigValue::Unit)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+    ) -> Result<ConfigValue, ValueError> {
+        Ok(ConfigValue::Variant(
+            variant.to_string(),
+            Box::new(ConfigValue::Unit),
+        ))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<ConfigValue, ValueError> {
+        to_value(value)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<ConfigValue, ValueError> {
+        Ok(ConfigValue::Variant(
+            variant.to_string(),
+            Box::new(to_value(value)?),
+        ))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SeqSerializer, ValueError> {
+        Ok(SeqSerializer::new(None, false, len.unwrap_or(0)))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SeqSerializer, ValueError> {
+        Ok(SeqSerializer::new(None, true, len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _: &'static str,
+        len: usize,
+    ) -> Result<SeqSerializer, ValueError> {
+        Ok(SeqSerializer::new(None, true, len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SeqSerializer, ValueError> {
+        Ok(SeqSerializer::new(Some(variant), true, len))
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> Result<MapSerializer, ValueError> {
+        Ok(MapSerializer {
+            entries: Vec::new(),
+            key: None,
+        })
+    }
+
+    fn serialize_struct(self, _: &'static str, len: usize) -> Result<StructSerializer, ValueError> {
+        Ok(StructSerializer {
+            variant: None,
+            fields: Vec::with_capacity(len),
+        })
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<StructSerializer, ValueError> {
+        Ok(StructSerializer {
+            variant: Some(variant),
+            fields: Vec::with_capacity(len),
+        })
+    }
+}
+
+/// Wraps the content of an enum variant.
+fn variant(variant: Option<&'static str>, content: ConfigValue) -> ConfigValue {
+    match variant {
+        Some(name) => ConfigValue::Variant(name.to_string(), Box::new(content)),
+        None => content,
+    }
+}
+
+struct SeqSerializer {
+    variant: Option<&'static str>,
+    tuple: bool,
+    items: Vec<ConfigValue>,
+}
+
+impl SeqSerializer {
+    fn new(variant: Option<&'static str>, tuple: bool, len: usize) -> Self {
+        SeqSerializer {
+            variant,
+            tuple,
+            items: Vec::with_capacity(len),
+        }
+    }
+
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ValueError> {
+        self.items.push(to_value(value)?);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<ConfigValue, ValueError> {
+        let items = if self.tuple {
+            ConfigValue::Tuple(self.items)
+        } else {
+            ConfigValue::Seq(self.items)
+        };
+        Ok(variant(self.variant, items))
+    }
+}
+
+impl ser::SerializeSeq for SeqSerializer {
+    type Ok = ConfigValue;
+    type Error = ValueError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ValueError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<ConfigValue, ValueError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTuple for SeqSerializer {
+    type Ok = ConfigValue;
+    type Error = ValueError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ValueError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<ConfigValue, ValueError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleStruct for SeqSerializer {
+    type Ok = ConfigValue;
+    type Error = ValueError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ValueError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<ConfigValue, ValueError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleVariant for SeqSerializer {
+    type Ok = ConfigValue;
+    type Error = ValueError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ValueError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<ConfigValue, ValueError> {
+        self.finish()
+    }
+}
+
+struct MapSerializer {
+    entries: Vec<(ConfigValue, ConfigValue)>,
+    key: Option<ConfigValue>,
+}
+
+impl ser::SerializeMap for MapSerializer {
+    type Ok = ConfigValue;
+    type Error = ValueError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), ValueError> {
+        self.key = Some(to_value(key)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ValueError> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| ValueError("Map value without a key".to_string()))?;
+        self.entries.push((key, to_value(value)?));
+        Ok(())
+    }
+
+    fn end(self) -> Result<ConfigValue, ValueError> {
+        Ok(ConfigValue::Map(self.entries))
+    }
+}
+
+struct StructSerializer {
+    variant: Option<&'static str>,
+    fields: Vec<(String, ConfigValue)>,
+}
+
+impl StructSerializer {
+    fn push<T: Serialize + ?Sized>(&mut self, name: &str, value: &T) -> Result<(), ValueError> {
+        self.fields.push((name.to_string(), to_value(value)?));
+        Ok(())
+    }
+}
+
+impl ser::SerializeStruct for StructSerializer {
+    type Ok = ConfigValue;
+    type Error = ValueError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<(), ValueError> {
+        self.push(name, value)
+    }
+
+    fn end(self) -> Result<ConfigValue, ValueError> {
+        Ok(variant(self.variant, ConfigValue::Struct(self.fields)))
+    }
+}
+
+impl ser::SerializeStructVariant for StructSerializer {
+    type Ok = ConfigValue;
+    type Error = ValueError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<(), ValueError> {
+        self.push(name, value)
+    }
+
+    fn end(self) -> Result<ConfigValue, ValueError> {
+        Ok(variant(self.variant, ConfigValue::Struct(self.fields)))
+    }
+}
+
+/// Deserializes a value from a `ConfigValue`.
+#[derive(Clone, Copy)]
+struct ValueDeserializer<'de>(&'de ConfigValue);
+
+impl<'de> Deserializer<'de> for ValueDeserializer<'de> {
+    type Error = ValueError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ValueError> {
+        match *self.0 {
+            ConfigValue::Bool(v) => visitor.visit_bool(v),
+            ConfigValue::I64(v) => visitor.visit_i64(v),
+            ConfigValue::U64(v) => visitor.visit_u64(v),
+            ConfigValue::F64(v) => visitor.visit_f64(v),
+            ConfigValue::Char(v) => visitor.visit_char(v),
+            ConfigValue::String(ref v) => visitor.visit_borrowed_str(v),
+            ConfigValue::Unit => visitor.visit_unit(),
+            ConfigValue::Option(None) => visitor.visit_none(),
+            ConfigValue::Option(Some(ref value)) => visitor.visit_some(ValueDeserializer(value)),
+            ConfigValue::Seq(ref items) | ConfigValue::Tuple(ref items) => {
+                visitor.visit_seq(ValueSeq(items.iter()))
+            }
+            ConfigValue::Map(ref entries) => visitor.visit_map(ValueMap {
+                entries: entries.iter(),
+                value: None,
+            }),
+            ConfigValue::Struct(ref fields) => visitor.visit_map(ValueStruct {
+                fields: fields.iter(),
+                value: None,
+            }),
+            ConfigValue::Variant(..) => visitor.visit_enum(self),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ValueError> {
+        match *self.0 {
+            ConfigValue::Option(None) => visitor.visit_none(),
+            ConfigValue::Option(Some(ref value)) => visitor.visit_some(ValueDeserializer(value)),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, ValueError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        _: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, ValueError> {
+        match *self.0 {
+            ConfigValue::String(ref name) => {
+                let name: StrDeserializer<ValueError> = name.as_str().into_deserializer();
+                visitor.visit_enum(name)
+            }
+            _ => visitor.visit_enum(self),
+        }
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ValueError> {
+        visitor.visit_unit()
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string bytes byte_buf unit
+        unit_struct seq tuple tuple_struct map struct identifier
+    }
+}
+
+impl<'de> EnumAccess<'de> for ValueDeserializer<'de> {
+    type Error = ValueError;
+    type Variant = ValueDeserializer<'de>;
+
+    fn variant_seed<S: DeserializeSeed<'de>>(
+        self,
+        seed: S,
+    ) -> Result<(S::Value, ValueDeserializer<'de>), ValueError> {
+        match *self.0 {
+            ConfigValue::Variant(ref name, ref content) => {
+                let name: StrDeserializer<ValueError> = name.as_str().into_deserializer();
+                Ok((seed.deserialize(name)?, ValueDeserializer(content)))
+            }
+            ref other => Err(de::Error::custom(format!(
+                "Expected an enum variant, found {}",
+                other
+            ))),
+        }
+    }
+}
+
+impl<'de> VariantAccess<'de> for ValueDeserializer<'de> {
+    type Error = ValueError;
+
+    fn unit_variant(self) -> Result<(), ValueError> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<S: DeserializeSeed<'de>>(
+        self,
+        seed: S,
+    ) -> Result<S::Value, ValueError> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _: usize, visitor: V) -> Result<V::Value, ValueError> {
+        self.deserialize_any(visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, ValueError> {
+        self.deserialize_any(visitor)
+    }
+}
+
+struct ValueSeq<'de>(slice::Iter<'de, ConfigValue>);
+
+impl<'de> SeqAccess<'de> for ValueSeq<'de> {
+    type Error = ValueError;
+
+    fn next_element_seed<S: DeserializeSeed<'de>>(
+        &mut self,
+        seed: S,
+    ) -> Result<Option<S::Value>, ValueError> {
+        match self.0.next() {
+            Some(value) => seed.deserialize(ValueDeserializer(value)).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+struct ValueMap<'de> {
+    entries: slice::Iter<'de, (ConfigValue, ConfigValue)>,
+    value: Option<&'de ConfigValue>,
+}
+
+impl<'de> MapAccess<'de> for ValueMap<'de> {
+    type Error = ValueError;
+
+    fn next_key_seed<S: DeserializeSeed<'de>>(
+        &mut self,
+        seed: S,
+    ) -> Result<Option<S::Value>, ValueError> {
+        match self.entries.next() {
+            Some(&(ref key, ref value)) => {
+                self.value = Some(value);
+                seed.deserialize(ValueDeserializer(key)).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<S: DeserializeSeed<'de>>(
+        &mut self,
+        seed: S,
+    ) -> Result<S::Value, ValueError> {
+        match self.value.take() {
+            Some(value) => seed.deserialize(ValueDeserializer(value)),
+            None => Err(de::Error::custom("Map value without a key")),
+        }
+    }
+}
+
+struct ValueStruct<'de> {
+    fields: slice::Iter<'de, (String, ConfigValue)>,
+    value: Option<&'de ConfigValue>,
+}
+
+impl<'de> MapAccess<'de> for ValueStruct<'de> {
+    type Error = ValueError;
+
+    fn next_key_seed<S: DeserializeSeed<'de>>(
+        &mut self,
+        seed: S,
+    ) -> Result<Option<S::Value>, ValueError> {
+        match self.fields.next() {
+            Some(&(ref name, ref value)) => {
+                self.value = Some(value);
+                let name: StrDeserializer<ValueError> = name.as_str().into_deserializer();
+                seed.deserialize(name).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<S: DeserializeSeed<'de>>(
+        &mut self,
+        seed: S,
+    ) -> Result<S::Value, ValueError> {
+        match self.value.take() {
+            Some(value) => seed.deserialize(ValueDeserializer(value)),
+            None => Err(de::Error::custom("Struct field without a name")),
+        }
+    }
+}
+
+/// What was found while reading a layer.
+#[derive(Default)]
+struct Trace {
+    /// Paths of the values given by the layer.
+    paths: Vec<String>,
+    /// Path of the innermost value which couldn't be read.
+    error: Option<String>,
+    /// The version field of a file.
+    version: Option<ConfigValue>,
+}
+
+/// Implements the methods of `Deserializer` by calling them on the inner deserializer, with
+/// the visitor returned by `self.wrap(visitor)`.
+macro_rules! forward_wrapped {
+    ($($method:ident($($arg:ident: $ty:ty),*);)*) => {
+        $(
+            fn $method<V>(self, $($arg: $ty,)* visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: Visitor<'de>,
+            {
+                let (inner, visitor) = self.wrap(visitor);
+                inner.$method($($arg,)* visitor)
+            }
+        )*
+    };
+}
+
+macro_rules! deserializer_methods {
+    ($macro:ident) => {
+        $macro! {
+            deserialize_any();
+            deserialize_bool();
+            deserialize_i8();
+            deserialize_i16();
+            deserialize_i32();
+            deserialize_i64();
+            deserialize_u8();
+            deserialize_u16();
+            deserialize_u32();
+            deserialize_u64();
+            deserialize_f32();
+            deserialize_f64();
+            deserialize_char();
+            deserialize_str();
+            deserialize_string();
+            deserialize_bytes();
+            deserialize_byte_buf();
+            deserialize_option();
+            deserialize_unit();
+            deserialize_unit_struct(name: &'static str);
+            deserialize_newtype_struct(name: &'static str);
+            deserialize_seq();
+            deserialize_tuple(len: usize);
+            deserialize_tuple_struct(name: &'static str, len: usize);
+            deserialize_map();
+            deserialize_struct(name: &'static str, fields: &'static [&'static str]);
+            deserialize_enum(name: &'static str, variants: &'static [&'static str]);
+            deserialize_identifier();
+            deserialize_ignored_any();
+        }
+    };
+}
+
+/// Reads a layer from `inner`, taking the struct fields and map keys it doesn't give from
+/// `base`, the value of the previous layers.
+struct Overlay<'t, 'de, D> {
+    inner: D,
+    base: Option<&'de ConfigValue>,
+    path: String,
+    trace: &'t RefCell<Trace>,
+}
+
+impl<'t, 'de, D> Overlay<'t, 'de, D> {
+    fn wrap<V>(self, visitor: V) -> (D, OverlayVisitor<'t, 'de, V>) {
+        let visitor = OverlayVisitor {
+            visitor,
+            base: self.base,
+            path: self.path,
+            trace: self.trace,
+        };
+        (self.inner, visitor)
+    }
+}
+
+impl<'t, 'de, D: Deserializer<'de>> Deserializer<'de> for Overlay<'t, 'de, D> {
+    type Error = D::Error;
+
+    deserializer_methods!(forward_wrapped);
+
+    fn is_human_readable(&self) -> bool {
+        self.inner.is_human_readable()
+    }
+}
+
+struct OverlaySeed<'t, 'de, S> {
+    seed: S,
+    base: Option<&'de ConfigValue>,
+    path: String,
+    trace: &'t RefCell<Trace>,
+}
+
+impl<'t, 'de, S: DeserializeSeed<'de>> DeserializeSeed<'de> for OverlaySeed<'t, 'de, S> {
+    type Value = S::Value;
+
+    fn deserialize<D: Deserializer<'de>>(self, inner: D) -> Result<S::Value, D::Error> {
+        self.seed.deserialize(Overlay {
+            inner,
+            base: self.base,
+            path: self.path,
+            trace: self.trace,
+        })
+    }
+}
+
+/// Implements visit methods recording that the layer gives the value.
+macro_rules! visit_given {
+    ($($method:ident($ty:ty);)*) => {
+        $(
+            fn $method<E: de::Error>(self, v: $ty) -> Result<V::Value, E> {
+                self.given();
+                self.visitor.$method(v)
+            }
+        )*
+    };
+}
+
+struct OverlayVisitor<'t, 'de, V> {
+    visitor: V,
+    base: Option<&'de ConfigValue>,
+    path: String,
+    trace: &'t RefCell<Trace>,
+}
+
+impl<'t, 'de, V> OverlayVisitor<'t, 'de, V> {
+    fn given(&self) {
+        self.trace.borrow_mut().paths.push(self.path.clone());
+    }
+}
+
+impl<'t, 'de, V: Visitor<'de>> Visitor<'de> for OverlayVisitor<'t, 'de, V> {
+    type Value = V::Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.visitor.expecting(f)
+    }
+
+    visit_given! {
+        visit_bool(bool);
+        visit_i8(i8);
+        visit_i16(i16);
+        visit_i32(i32);
+        visit_i64(i64);
+        visit_u8(u8);
+        visit_u16(u16);
+        visit_u32(u32);
+        visit_u64(u64);
+        visit_f32(f32);
+        visit_f64(f64);
+        visit_char(char);
+        visit_str(&str);
+        visit_borrowed_str(&'de str);
+        visit_string(String);
+        visit_bytes(&[u8]);
+        visit_borrowed_bytes(&'de [u8]);
+        visit_byte_buf(Vec<u8>);
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<V::Value, E> {
+        self.given();
+        self.visitor.visit_none()
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<V::Value, E> {
+        self.given();
+        self.visitor.visit_unit()
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, inner: D) -> Result<V::Value, D::Error> {
+        let base = self.base.and_then(|base| match *base {
+            ConfigValue::Option(Some(ref value)) => Some(&**value),
+            _ => None,
+        });
+        self.visitor.visit_some(Overlay {
+            inner,
+            base,
+            path: self.path,
+            trace: self.trace,
+        })
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(self, inner: D) -> Result<V::Value, D::Error> {
+        self.visitor.visit_newtype_struct(Overlay {
+            inner,
+            base: self.base,
+            path: self.path,
+            trace: self.trace,
+        })
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<V::Value, A::Error> {
+        self.given();
+        self.visitor.visit_seq(seq)
+    }
+
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<V::Value, A::Error> {
+        self.given();
+        self.visitor.visit_enum(data)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<V::Value, A::Error> {
+        self.visitor.visit_map(OverlayMap {
+            inner: map,
+            base: self.base,
+            path: self.path,
+            trace: self.trace,
+            given: Vec::new(),
+            layer_done: false,
+            replaced: false,
+            next: None,
+            index: 0,
+        })
+    }
+}
+
+/// The value read after a key of an `OverlayMap`.
+enum Next<'de> {
+    /// Given by the layer, at the path, over the base value.
+    Layer(String, Option<&'de ConfigValue>),
+    /// The version of a file.
+    Version,
+    /// Only in the base.
+    Base(&'de ConfigValue),
+}
+
+/// Reads the entries of a struct or map given by the layer, then those of the base which the
+/// layer doesn't give.
+struct OverlayMap<'t, 'de, A> {
+    inner: A,
+    base: Option<&'de ConfigValue>,
+    path: String,
+    trace: &'t RefCell<Trace>,
+    /// Keys given by the layer.
+    given: Vec<ConfigValue>,
+    /// Whether all the entries of the layer were read.
+    layer_done: bool,
+    /// Whether the layer gives a key which can't be compared with the base, so that the base
+    /// entries are dropped rather than risking duplicates.
+    replaced: bool,
+    next: Option<Next<'de>>,
+    /// Index of the next base entry.
+    index: usize,
+}
+
+impl<'t, 'de, A> OverlayMap<'t, 'de, A> {
+    fn base_value(&self, key: &ConfigValue) -> Option<&'de ConfigValue> {
+        self.base.and_then(|base| match (base, key) {
+            (&ConfigValue::Struct(ref fields), &ConfigValue::String(ref name)) => fields
+                .iter()
+                .find(|&&(ref field, _)| field == name)
+                .map(|&(_, ref value)| value),
+            (&ConfigValue::Map(ref entries), key) => entries
+                .iter()
+                .find(|&&(ref k, _)| k == key)
+                .map(|&(_, ref value)| value),
+            _ => None,
+        })
+    }
+
+    fn is_given(&self, name: &str) -> bool {
+        self.given.iter().any(|key| match *key {
+            ConfigValue::String(ref given) => given == name,
+            _ => false,
+        })
+    }
+}
+
+impl<'t, 'de, A: MapAccess<'de>> MapAccess<'de> for OverlayMap<'t, 'de, A> {
+    type Error = A::Error;
+
+    fn next_key_seed<S: DeserializeSeed<'de>>(
+        &mut self,
+        seed: S,
+    ) -> Result<Option<S::Value>, A::Error> {
+        let mut seed = Some(seed);
+        if !self.layer_done {
+            let mut captured = None;
+            let key = self.inner.next_key_seed(CaptureSeed {
+                seed: &mut seed,
+                captured: &mut captured,
+            })?;
+            if let Some(key) = key {
+                let next = match captured {
+                    Some(ConfigValue::String(ref name))
+                        if self.path.is_empty() && name == VERSION_FIELD =>
+                    {
+                        Next::Version
+                    }
+                    Some(ref captured) => Next::Layer(
+                        child_path(&self.path, &key_segment(captured)),
+                        self.base_value(captured),
+                    ),
+                    None => {
+                        self.replaced = true;
+                        Next::Layer(self.path.clone(), None)
+                    }
+                };
+                self.next = Some(next);
+                self.given.extend(captured);
+                return Ok(Some(key));
+            }
+            self.layer_done = true;
+        }
+
+        let seed = match seed {
+            Some(seed) => seed,
+            None => return Ok(None),
+        };
+        let base = self.base;
+        match base {
+            Some(&ConfigValue::Struct(ref fields)) if !self.replaced => {
+                while let Some(&(ref name, ref value)) = fields.get(self.index) {
+                    self.index += 1;
+                    if !self.is_given(name) {
+                        self.next = Some(Next::Base(value));
+                        let name: StrDeserializer<A::Error> = name.as_str().into_deserializer();
+                        return seed.deserialize(name).map(Some);
+                    }
+                }
+            }
+            Some(&ConfigValue::Map(ref entries)) if !self.replaced => {
+                while let Some(&(ref key, ref value)) = entries.get(self.index) {
+                    self.index += 1;
+                    if !self.given.contains(key) {
+                        self.next = Some(Next::Base(value));
+                        return seed
+                            .deserialize(ValueDeserializer(key))
+                            .map(Some)
+                            .map_err(de::Error::custom);
+                    }
+                }
+            }
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn next_value_seed<S: DeserializeSeed<'de>>(&mut self, seed: S) -> Result<S::Value, A::Error> {
+        match self.next.take() {
+            Some(Next::Layer(path, base)) => {
+                let trace = self.trace;
+                let seed = OverlaySeed {
+                    seed,
+                    base,
+                    path: path.clone(),
+                    trace,
+                };
+                self.inner.next_value_seed(seed).map_err(|e| {
+                    let mut trace = trace.borrow_mut();
+                    if trace.error.is_none() {
+                        trace.error = Some(path);
+                    }
+                    e
+                })
+            }
+            Some(Next::Version) => {
+                let mut seed = Some(seed);
+                let mut captured = None;
+                let value = self.inner.next_value_seed(CaptureSeed {
+                    seed: &mut seed,
+                    captured: &mut captured,
+                })?;
+                self.trace.borrow_mut().version = captured;
+                Ok(value)
+            }
+            Some(Next::Base(value)) => seed
+                .deserialize(ValueDeserializer(value))
+                .map_err(de::Error::custom),
+            None => Err(de::Error::custom("Map value without a key")),
+        }
+    }
+}
+
+/// Reads a value with a seed while keeping a copy of it if it is a scalar, e.g. a map key.
+///
+/// The seed is only taken when a value is read, so that it can still be used after the end
+/// of a map.
+struct CaptureSeed<'a, S: 'a> {
+    seed: &'a mut Option<S>,
+    captured: &'a mut Option<ConfigValue>,
+}
+
+impl<'a, 'de, S: DeserializeSeed<'de>> DeserializeSeed<'de> for CaptureSeed<'a, S> {
+    type Value = S::Value;
+
+    fn deserialize<D: Deserializer<'de>>(self, inner: D) -> Result<S::Value, D::Error> {
+        let seed = self.seed.take().expect("A seed is only used once");
+        seed.deserialize(Capture {
+            inner,
+            captured: self.captured,
+        })
+    }
+}
+
+struct Capture<'a, D> {
+    inner: D,
+    captured: &'a mut Option<ConfigValue>,
+}
+
+impl<'a, D> Capture<'a, D> {
+    fn wrap<V>(self, visitor: V) -> (D, CaptureVisitor<'a, V>) {
+        let visitor = CaptureVisitor {
+            visitor,
+            captured: self.captured,
+        };
+        (self.inner, visitor)
+    }
+}
+
+impl<'a, 'de, D: Deserializer<'de>> Deserializer<'de> for Capture<'a, D> {
+    type Error = D::Error;
+
+    deserializer_methods!(forward_wrapped);
+
+    fn is_human_readable(&self) -> bool {
+        self.inner.is_human_readable()
+    }
+}
+
+/// Implements visit methods keeping a copy of the value.
+macro_rules! visit_captured {
+    ($($method:ident($ty:ty) => $variant:ident;)*) => {
+        $(
+            fn $method<E: de::Error>(self, v: $ty) -> Result<V::Value, E> {
+                *self.captured = Some(ConfigValue::$variant(v.into()));
+                self.visitor.$method(v)
+            }
+        )*
+    };
+}
+
+struct CaptureVisitor<'a, V> {
+    visitor: V,
+    captured: &'a mut Option<ConfigValue>,
+}
+
+impl<'a, 'de, V: Visitor<'de>> Visitor<'de> for CaptureVisitor<'a, V> {
+    type Value = V::Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.visitor.expecting(f)
+    }
+
+    visit_captured! {
+        visit_bool(bool) => Bool;
+        visit_i8(i8) => I64;
+        visit_i16(i16) => I64;
+        visit_i32(i32) => I64;
+        visit_i64(i64) => I64;
+        visit_u8(u8) => U64;
+        visit_u16(u16) => U64;
+        visit_u32(u32) => U64;
+        visit_u64(u64) => U64;
+        visit_f32(f32) => F64;
+        visit_f64(f64) => F64;
+        visit_char(char) => Char;
+        visit_str(&str) => String;
+        visit_borrowed_str(&'de str) => String;
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> Result<V::Value, E> {
+        *self.captured = Some(ConfigValue::String(v.clone()));
+        self.visitor.visit_string(v)
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<V::Value, E> {
+        *self.captured = Some(ConfigValue::Unit);
+        self.visitor.visit_unit()
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<V::Value, E> {
+        self.visitor.visit_bytes(v)
+    }
+
+    fn visit_borrowed_bytes<E: de::Error>(self, v: &'de [u8]) -> Result<V::Value, E> {
+        self.visitor.visit_borrowed_bytes(v)
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<V::Value, E> {
+        self.visitor.visit_byte_buf(v)
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<V::Value, E> {
+        self.visitor.visit_none()
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, inner: D) -> Result<V::Value, D::Error> {
+        self.visitor.visit_some(inner)
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(self, inner: D) -> Result<V::Value, D::Error> {
+        self.visitor.visit_newtype_struct(inner)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<V::Value, A::Error> {
+        self.visitor.visit_seq(seq)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<V::Value, A::Error> {
+        self.visitor.visit_map(map)
+    }
+
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<V::Value, A::Error> {
+        self.visitor.visit_enum(CaptureEnum {
+            inner: data,
+            captured: self.captured,
+        })
+    }
+}
+
+/// Keeps a copy of unit variants, e.g. map keys.
+struct CaptureEnum<'a, A> {
+    inner: A,
+    captured: &'a mut Option<ConfigValue>,
+}
+
+impl<'a, 'de, A: EnumAccess<'de>> EnumAccess<'de> for CaptureEnum<'a, A> {
+    type Error = A::Error;
+    type Variant = CaptureVariant<'a, A::Variant>;
+
+    fn variant_seed<S: DeserializeSeed<'de>>(
+        self,
+        seed: S,
+    ) -> Result<(S::Value, Self::Variant), A::Error> {
+        let mut seed = Some(seed);
+        let mut name = None;
+        let (value, variant) = self.inner.variant_seed(CaptureSeed {
+            seed: &mut seed,
+            captured: &mut name,
+        })?;
+        let variant = CaptureVariant {
+            inner: variant,
+            name,
+            captured: self.captured,
+        };
+        Ok((value, variant))
+    }
+}
+
+struct CaptureVariant<'a, A> {
+    inner: A,
+    name: Option<ConfigValue>,
+    captured: &'a mut Option<ConfigValue>,
+}
+
+impl<'a, 'de, A: VariantAccess<'de>> VariantAccess<'de> for CaptureVariant<'a, A> {
+    type Error = A::Error;
+
+    fn unit_variant(self) -> Result<(), A::Error> {
+        if let Some(ConfigValue::String(name)) = self.name {
+            *self.captured = Some(ConfigValue::Variant(name, Box::new(ConfigValue::Unit)));
+        }
+        self.inner.unit_variant()
+    }
+
+    fn newtype_variant_seed<S: DeserializeSeed<'de>>(self, seed: S) -> Result<S::Value, A::Error> {
+        self.inner.newtype_variant_seed(seed)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, A::Error> {
+        self.inner.tuple_variant(len, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, A::Error> {
+        self.inner.struct_variant(fields, visitor)
+    }
+}
+
+/// Reads `T` from RON text over `base`.
+fn read_text<'de, T>(
+    text: &'de str,
+    base: &'de ConfigValue,
+    trace: &RefCell<Trace>,
+) -> Result<T, ron::de::Error>
+where
+    T: Deserialize<'de>,
+{
+    let mut deserializer = ron::de::Deserializer::from_str(text);
+    let value = T::deserialize(Overlay {
+        inner: &mut deserializer,
+        base: Some(base),
+        path: String::new(),
+        trace,
+    })?;
+    deserializer.end()?;
+    Ok(value)
+}
+
+/// Writes a layer setting the value at the path to the RON text `raw`, e.g.
+/// `(axes: {"horizontal": <raw>})`. Struct fields and map keys are looked up in `base`
+/// ignoring their case if there is no exact match. Returns the path to the value with the
+/// names found in `base`, after `resolved`.
+fn assignment(
+    base: Option<&ConfigValue>,
+    path: &[&str],
+    raw: &str,
+    resolved: String,
+) -> Option<(String, String)> {
+    let (segment, rest) = match path.split_first() {
+        Some(split) => split,
+        None => return Some((resolved, leaf(base, raw))),
+    };
+    match base {
+        Some(&ConfigValue::Option(Some(ref value))) => assignment(Some(value), path, raw, resolved)
+            .map(|(resolved, layer)| (resolved, format!("Some({})", layer))),
+        Some(&ConfigValue::Struct(ref fields)) => {
+            let &(ref name, ref value) = fields
+                .iter()
+                .find(|&&(ref name, _)| name.as_str() == *segment)
+                .or_else(|| {
+                    fields
+                        .iter()
+                        .find(|&&(ref name, _)| name.eq_ignore_ascii_case(segment))
+                })?;
+            let resolved = child_path(&resolved, name);
+            let (resolved, layer) = assignment(Some(value), rest, raw, resolved)?;
+            Some((resolved, format!("({}: {})", name, layer)))
+        }
+        Some(&ConfigValue::Map(ref entries)) => {
+            let entry = entries
+                .iter()
+                .find(|&&(ref key, _)| key_segment(key) == *segment)
+                .or_else(|| {
+                    entries
+                        .iter()
+                        .find(|&&(ref key, _)| key_segment(key).eq_ignore_ascii_case(segment))
+                });
+            let (key, segment, value) = match (entry, entries.first()) {
+                (Some(&(ref key, ref value)), _) => (key.to_string(), key_segment(key), value),
+                // A new key is written like the others, and its value is shaped like theirs.
+                (None, Some(&(ConfigValue::String(_), ref other))) => (
+                    ConfigValue::String(segment.to_string()).to_string(),
+                    segment.to_string(),
+                    other,
+                ),
+                (None, Some(&(_, ref other))) => (segment.to_string(), segment.to_string(), other),
+                (None, None) => {
+                    let key = ConfigValue::String(segment.to_string()).to_string();
+                    let (resolved, layer) =
+                        assignment(None, rest, raw, child_path(&resolved, segment))?;
+                    return Some((resolved, format!("{{{}: {}}}", key, layer)));
+                }
+            };
+            let resolved = child_path(&resolved, &segment);
+            let (resolved, layer) = assignment(Some(value), rest, raw, resolved)?;
+            Some((resolved, format!("{{{}: {}}}", key, layer)))
+        }
+        _ => None,
+    }
+}
+
+/// Quotes strings and wraps options in `Some` if the text doesn't already.
+fn leaf(base: Option<&ConfigValue>, raw: &str) -> String {
+    match base {
+        Some(&ConfigValue::String(_)) if !raw.starts_with('"') => {
+            ConfigValue::String(raw.to_string()).to_string()
+        }
+        Some(&ConfigValue::Option(ref value)) if raw != "None" && !raw.starts_with("Some(") => {
+            format!("Some({})", leaf(value.as_ref().map(|value| &**value), raw))
+        }
+        _ => raw.to_string(),
+    }
+}
+
+/// Adds the paths of the values of `new` which differ from `old`.
+fn changed_paths(
+    old: Option<&ConfigValue>,
+    new: &ConfigValue,
+    path: &str,
+    paths: &mut Vec<String>,
+) {
+    match (old, new) {
+        (Some(&ConfigValue::Struct(ref old_fields)), &ConfigValue::Struct(ref fields)) => {
+            for &(ref name, ref value) in fields {
+                let old = old_fields
+                    .iter()
+                    .find(|&&(ref old_name, _)| old_name == name)
+                    .map(|&(_, ref old)| old);
+                changed_paths(old, value, &child_path(path, name), paths);
+            }
+        }
+        (Some(&ConfigValue::Map(ref old_entries)), &ConfigValue::Map(ref entries)) => {
+            for &(ref key, ref value) in entries {
+                let old = old_entries
+                    .iter()
+                    .find(|&&(ref old_key, _)| old_key == key)
+                    .map(|&(_, ref old)| old);
+                changed_paths(old, value, &child_path(path, &key_segment(key)), paths);
+            }
+        }
+        (Some(old), new) if old == new => {}
+        _ => paths.push(path.to_string()),
+    }
+}
+
+/// The version of a file, from its version field.
+fn version(value: &ConfigValue) -> Option<u32> {
+    let max = u32::max_value();
+    match *value {
+        ConfigValue::U64(v) if v <= u64::from(max) => Some(v as u32),
+        ConfigValue::I64(v) if v >= 0 && v <= i64::from(max) => Some(v as u32),
+        ConfigValue::F64(v) if v >= 0.0 && v.fract() == 0.0 && v <= f64::from(max) => {
+            Some(v as u32)
+        }
+        _ => None,
+    }
+}
+
+/// Where each value set by a layer comes from, by path.
+type Provenance = BTreeMap<String, String>;
+
+fn record(provenance: &mut Provenance, path: &str, source: &str) {
+    let prefix = format!("{}.", path);
+    let overridden = provenance
+        .keys()
+        .filter(|key| path.is_empty() || key.starts_with(&prefix))
+        .cloned()
+        .collect::<Vec<_>>();
+    for key in overridden {
+        provenance.remove(&key);
+    }
+    provenance.insert(path.to_string(), source.to_string());
+}
+
+fn source_of<'p>(provenance: &'p Provenance, path: &str) -> Option<&'p str> {
+    let mut path = path;
+    loop {
+        if let Some(source) = provenance.get(path) {
+            return Some(source);
+        }
+        match path.rfind('.') {
+            Some(index) => path = &path[..index],
+            None if !path.is_empty() => path = "",
+            None => return None,
+        }
+    }
+}
+
+/// A field which couldn't be read or has an invalid value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FieldError {
+    /// Path of the field, e.g. `dimensions` or `axes.horizontal`.
+    pub path: String,
+    /// What is wrong with the value.
+    pub message: String,
+    /// Where the value comes from, e.g. a file or an environment variable. `None` if it is
+    /// the default value.
+    pub source: Option<String>,
+}
+
+impl FieldError {
+    /// Creates an error about the field at the path, for `LayeredConfig::with_validation`.
+    pub fn new<P, M>(path: P, message: M) -> Self
+    where
+        P: Into<String>,
+        M: Into<String>,
+    {
+        FieldError {
+            path: path.into(),
+            message: message.into(),
+            source: None,
+        }
+    }
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)?;
+        if let Some(ref source) = self.source {
+            write!(f, " (set by {})", source)?;
+        }
+        Ok(())
+    }
+}
+
+fn invalid<P, M>(path: P, message: M, source: &str) -> ConfigError
+where
+    P: Into<String>,
+    M: fmt::Display,
+{
+    ConfigError::Invalid(vec![FieldError {
+        path: path.into(),
+        message: message.to_string(),
+        source: Some(source.to_string()),
+    }])
+}
+
+/// The error of a layer which couldn't be read, on the field it failed at if there is one.
+fn layer_error<E: fmt::Display>(error: E, trace: &RefCell<Trace>, source: &str) -> ConfigError {
+    match trace.borrow().error {
+        Some(ref path) => invalid(path.as_str(), error, source),
+        None => ConfigError::Syntax(source.to_string(), error.to_string()),
+    }
+}
+
+enum Layer {
+    File(PathBuf, bool),
+    Value(String, String, ConfigValue),
+    Assignments(Vec<(String, String, String)>),
+}
+
+/// A file being migrated: its text, to read over the previous layers, or the value migrated
+/// from an older version.
+enum Document<'a> {
+    Text(&'a str, &'a ConfigValue),
+    Value(&'a ConfigValue),
+}
+
+type Migration = Box<Fn(Document) -> Result<ConfigValue, String>>;
+
+fn migration<V, W, F>(migration: F) -> Migration
+where
+    V: for<'a> Deserialize<'a> + 'static,
+    W: Serialize + 'static,
+    F: Fn(V) -> Result<W, String> + 'static,
+{
+    Box::new(move |document: Document| {
+        let old = match document {
+            Document::Text(text, base) => read_text::<V>(text, base, &RefCell::default())
+                .map_err(|e| e.to_string())?,
+            Document::Value(value) => {
+                V::deserialize(ValueDeserializer(value)).map_err(|e| e.to_string())?
+            }
+        };
+        to_value(&migration(old)?).map_err(|e| e.to_string())
+    })
+}
+
+/// Loads a configuration from layers, each overriding the values of the previous ones.
+///
+/// The first layer is the `Default` of the configuration, then the layers are applied in the
+/// order they were added:
+///
+/// - files, e.g. the config shipped with the game then the user's overrides, which only need
+///   the fields they change. Structs are merged field by field and maps key by key.
+/// - environment variables, `<PREFIX>_<FIELD>` with `__` between nested fields, e.g.
+///   `GAME_DISPLAY_FULLSCREEN=true` or `GAME_INPUT_AXES__HORIZONTAL=...`. Fields and map keys
+///   are matched ignoring their case.
+/// - command line arguments, `--<prefix>.<path>=<value>`, e.g. `--display.dimensions=(800, 600)`
+///   or `--display.fullscreen` for `true`.
+/// - single values set by the game, e.g. from a settings menu.
+///
+/// Values given as text are read as RON, except for strings which don't need quotes.
+///
+/// If the config has a version, files give theirs in a `version` field, `0` if they have none,
+/// and are migrated to the current version before being merged.
+///
+/// ```rust,no_run
+/// # extern crate amethyst_config;
+/// # #[macro_use] extern crate serde_derive;
+/// use amethyst_config::{FieldError, LayeredConfig};
+///
+/// #[derive(Default, Deserialize, Serialize)]
+/// struct Audio {
+///     volume: f32,
+/// }
+///
+/// # fn main() {
+/// let config = LayeredConfig::<Audio>::new()
+///     .with_file("resources/audio.ron")
+///     .with_optional_file("user/audio.ron")
+///     .with_env("GAME_AUDIO")
+///     .with_args("audio", std::env::args())
+///     .with_validation(|audio| {
+///         if audio.volume < 0.0 || audio.volume > 1.0 {
+///             vec![FieldError::new("volume", "must be between 0 and 1")]
+///         } else {
+///             vec![]
+///         }
+///     })
+///     .load()
+///     .unwrap();
+/// println!("The volume comes from {:?}", config.source("volume"));
+/// # }
+/// ```
+pub struct LayeredConfig<T> {
+    layers: Vec<Layer>,
+    version: Option<u32>,
+    migrations: BTreeMap<u32, Migration>,
+    validations: Vec<Box<Fn(&T) -> Vec<FieldError>>>,
+    marker: PhantomData<T>,
+}
+
+impl<T> LayeredConfig<T>
+where
+    T: for<'a> Deserialize<'a> + Serialize + Default,
+{
+    /// Creates a config with only the default values.
+    pub fn new() -> Self {
+        LayeredConfig {
+            layers: Vec::new(),
+            version: None,
+            migrations: BTreeMap::new(),
+            validations: Vec::new(),
+            marker: PhantomData,
+        }
+    }
+
+    /// Adds a file layer, which must exist.
+    pub fn with_file<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.layers.push(Layer::File(path.as_ref().to_owned(), true));
+        self
+    }
+
+    /// Adds a file layer which is skipped if the file doesn't exist, e.g. the user's overrides.
+    pub fn with_optional_file<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.layers.push(Layer::File(path.as_ref().to_owned(), false));
+        self
+    }
+
+    /// Adds the environment variables starting with `<prefix>_` as a layer.
+    pub fn with_env(self, prefix: &str) -> Self {
+        let vars = env::vars_os().filter_map(|(name, value)| {
+            Some((name.into_string().ok()?, value.into_string().ok()?))
+        });
+        self.with_env_vars(prefix, vars)
+    }
+
+    /// Adds the given variables starting with `<prefix>_` as a layer, like `with_env` does
+    /// with the environment.
+    pub fn with_env_vars<I, K, V>(mut self, prefix: &str, vars: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        let prefix = format!("{}_", prefix);
+        let mut assignments = vars
+            .into_iter()
+            .map(|(name, value)| (name.into(), value.into()))
+            .filter(|&(ref name, _): &(String, String)| {
+                name.starts_with(&prefix) && name.len() > prefix.len()
+            }).map(|(name, value)| {
+                let path = name[prefix.len()..].replace("__", ".");
+                (name, path, value)
+            }).collect::<Vec<_>>();
+        assignments.sort();
+        self.layers.push(Layer::Assignments(assignments));
+        self
+    }
+
+    /// Adds the command line arguments starting with `--<prefix>.` as a layer, the others are
+    /// ignored.
+    pub fn with_args<I, S>(mut self, prefix: &str, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let prefix = format!("--{}.", prefix);
+        let assignments = args
+            .into_iter()
+            .map(|arg| -> String { arg.into() })
+            .filter(|arg| arg.starts_with(&prefix))
+            .map(|arg| {
+                let (path, value) = match arg.find('=') {
+                    Some(index) => (&arg[prefix.len()..index], &arg[index + 1..]),
+                    None => (&arg[prefix.len()..], "true"),
+                };
+                let name = format!("--{}", &arg[2..arg.find('=').unwrap_or_else(|| arg.len())]);
+                (name, path.to_string(), value.to_string())
+            }).collect();
+        self.layers.push(Layer::Assignments(assignments));
+        self
+    }
+
+    /// Adds a layer setting the value at the path, coming from `source`.
+    pub fn with_value<S>(mut self, source: S, path: &str, value: ConfigValue) -> Self
+    where
+        S: Into<String>,
+    {
+        self.layers
+            .push(Layer::Value(source.into(), path.to_string(), value));
+        self
+    }
+
+    /// Sets the current version of the config.
+    pub fn with_version(mut self, version: u32) -> Self {
+        self.version = Some(version);
+        self
+    }
+
+    /// Sets the function migrating files from the version `from`, read as `V`, to the next
+    /// version `W`.
+    pub fn with_migration<V, W, F>(mut self, from: u32, migration: F) -> Self
+    where
+        V: for<'a> Deserialize<'a> + 'static,
+        W: Serialize + 'static,
+        F: Fn(V) -> Result<W, String> + 'static,
+    {
+        self.migrations
+            .insert(from, self::migration::<V, W, F>(migration));
+        self
+    }
+
+    /// Adds a check of the loaded config, returning the invalid fields.
+    pub fn with_validation<F>(mut self, validation: F) -> Self
+    where
+        F: Fn(&T) -> Vec<FieldError> + 'static,
+    {
+        self.validations.push(Box::new(validation));
+        self
+    }
+
+    /// Loads the config from its layers.
+    pub fn load(&self) -> Result<LoadedConfig<T>, ConfigError> {
+        let mut config = T::default();
+        let mut value = to_value(&config).map_err(|e| invalid("", e, "default"))?;
+        let mut provenance = Provenance::new();
+        for layer in &self.layers {
+            let (source, paths) = match *layer {
+                Layer::File(ref path, required) => {
+                    let source = path.display().to_string();
+                    let content = match read_file(path) {
+                        Ok(content) => content,
+                        Err(ConfigError::File(ref e))
+                            if !required && e.kind() == ErrorKind::NotFound =>
+                        {
+                            continue
+                        }
+                        Err(e) => return Err(e),
+                    };
+                    let (read, paths) = self.read_layer(&content, &value, &source)?;
+                    config = read;
+                    (source, paths)
+                }
+                Layer::Value(ref source, ref path, ref new) => {
+                    let mut layer = value.clone();
+                    if !layer.set(path, new.clone()) {
+                        return Err(invalid(path.as_str(), "No such field", source));
+                    }
+                    config = T::deserialize(ValueDeserializer(&layer))
+                        .map_err(|e| invalid(path.as_str(), e, source))?;
+                    (source.clone(), vec![path.clone()])
+                }
+                Layer::Assignments(ref assignments) => {
+                    for &(ref source, ref path, ref raw) in assignments {
+                        let (path, text) =
+                            assignment(Some(&value), &segments(path), raw, String::new())
+                                .ok_or_else(|| invalid(path.as_str(), "No such field", source))?;
+                        config = read_text(&text, &value, &RefCell::default())
+                            .map_err(|e| invalid(path.as_str(), e, source))?;
+                        value = to_value(&config).map_err(|e| invalid(path.as_str(), e, source))?;
+                        record(&mut provenance, &path, source);
+                    }
+                    continue;
+                }
+            };
+            value = to_value(&config).map_err(|e| invalid("", e, &source))?;
+            for path in paths {
+                record(&mut provenance, &path, &source);
+            }
+        }
+
+        let mut errors = self
+            .validations
+            .iter()
+            .flat_map(|validation| validation(&config))
+            .collect::<Vec<_>>();
+        if !errors.is_empty() {
+            for error in &mut errors {
+                error.source = source_of(&provenance, &error.path).map(str::to_string);
+            }
+            return Err(ConfigError::Invalid(errors));
+        }
+        Ok(LoadedConfig {
+            value: config,
+            provenance,
+        })
+    }
+
+    /// Reads a file over the previous layers, migrating it if it's older than the config.
+    /// Returns the config with the paths of the values given by the file.
+    fn read_layer(
+        &self,
+        content: &str,
+        base: &ConfigValue,
+        source: &str,
+    ) -> Result<(T, Vec<String>), ConfigError> {
+        let trace = RefCell::new(Trace::default());
+        let read = read_text::<T>(content, base, &trace);
+        let current = match self.version {
+            Some(current) => current,
+            None => {
+                let config = read.map_err(|e| layer_error(e, &trace, source))?;
+                return Ok((config, trace.into_inner().paths));
+            }
+        };
+
+        let migration_error = |message: String| ConfigError::Migration(source.to_string(), message);
+        let version = match trace.borrow().version {
+            Some(ref value) => version(value)
+                .ok_or_else(|| migration_error(format!("Invalid version {}", value)))?,
+            None => 0,
+        };
+        if version > current {
+            return Err(migration_error(format!(
+                "Version {} is newer than the supported version {}",
+                version, current
+            )));
+        }
+        if version == current {
+            let config = read.map_err(|e| layer_error(e, &trace, source))?;
+            return Ok((config, trace.into_inner().paths));
+        }
+
+        let mut migrated = None;
+        for from in version..current {
+            let migration = self
+                .migrations
+                .get(&from)
+                .ok_or_else(|| migration_error(format!("No migration from version {}", from)))?;
+            let document = match migrated {
+                Some(ref value) => Document::Value(value),
+                None => Document::Text(content, base),
+            };
+            let next = migration(document).map_err(|e| {
+                migration_error(format!("Migration from version {} failed: {}", from, e))
+            })?;
+            migrated = Some(next);
+        }
+        let migrated = migrated.expect("The file was migrated at least once");
+        let config =
+            T::deserialize(ValueDeserializer(&migrated)).map_err(|e| invalid("", e, source))?;
+        let mut paths = Vec::new();
+        let value = to_value(&config).map_err(|e| invalid("", e, source))?;
+        changed_paths(Some(base), &value, "", &mut paths);
+        Ok((config, paths))
+    }
+}
+
+fn read_file(path: &Path) -> Result<String, ConfigError> {
+    if path.extension().and_then(|e| e.to_str()) != Some("ron") {
+        return Err(ConfigError::Extension(path.to_path_buf()));
+    }
+    let mut content = String::new();
+    File::open(path)?.read_to_string(&mut content)?;
+    Ok(content)
+}
+
+/// A config loaded by `LayeredConfig`, with where its values come from.
+#[derive(Clone, Debug)]
+pub struct LoadedConfig<T> {
+    /// The loaded config.
+    pub value: T,
+    provenance: Provenance,
+}
+
+impl<T> LoadedConfig<T> {
+    /// Gets where the value at the path comes from, e.g. a file or an environment variable.
+    /// `None` if it is the default value.
+    pub fn source(&self, path: &str) -> Option<&str> {
+        source_of(&self.provenance, path)
+    }
+
+    /// Gets the paths of the values which aren't the defaults, with where they come from.
+    pub fn sources(&self) -> &BTreeMap<String, String> {
+        &self.provenance
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+    enum Key {
+        Escape,
+        Char(char),
+    }
+
+    #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+    enum Mode {
+        Windowed,
+        Fullscreen { monitor: u32 },
+    }
+
+    impl Default for Mode {
+        fn default() -> Self {
+            Mode::Windowed
+        }
+    }
+
+    #[derive(Debug, Default, Deserialize, PartialEq, Serialize)]
+    struct Screen {
+        mode: Mode,
+        scale: f32,
+    }
+
+    #[derive(Debug, Default, Deserialize, PartialEq, Serialize)]
+    struct Window {
+        title: String,
+        dimensions: Option<(u32, u32)>,
+        vsync: bool,
+        keys: BTreeMap<String, Key>,
+        screen: Screen,
+    }
+
+    /// Writes a file in a directory of its own for each test, as they run in parallel.
+    fn write(test: &str, name: &str, content: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("amethyst_layered_config_{}", test));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    fn source(path: &Path) -> String {
+        path.display().to_string()
+    }
+
+    #[test]
+    fn values_round_trip() {
+        let mut keys = BTreeMap::new();
+        keys.insert("Quit".to_string(), Key::Escape);
+        keys.insert("jump".to_string(), Key::Char(' '));
+        let window = Window {
+            title: "Say \"hi\"".to_string(),
+            dimensions: Some((800, 600)),
+            vsync: true,
+            keys,
+            screen: Screen {
+                mode: Mode::Fullscreen { monitor: 1 },
+                scale: 1.5,
+            },
+        };
+        let value = to_value(&window).unwrap();
+
+        assert_eq!(
+            value.get("keys.jump"),
+            Some(&ConfigValue::Variant(
+                "Char".to_string(),
+                Box::new(ConfigValue::Char(' '))
+            ))
+        );
+        assert_eq!(value.get("dimensions.1"), Some(&ConfigValue::U64(600)));
+        assert_eq!(
+            value.get("screen").unwrap().to_string(),
+            "(mode: Fullscreen(monitor: 1), scale: 1.5)"
+        );
+        assert_eq!(Window::deserialize(ValueDeserializer(&value)).unwrap(), window);
+        assert_eq!(ron::de::from_str::<Window>(&value.to_string()).unwrap(), window);
+    }
+
+    #[test]
+    fn later_files_override_fields() {
+        let pack = write(
+            "files",
+            "pack.ron",
+            r#"(
+                title: "Game",
+                dimensions: Some((800, 600)),
+                keys: {"Quit": Escape, "jump": Char(' ')},
+                screen: (mode: Fullscreen(monitor: 1), scale: 2.0),
+            )"#,
+        );
+        let user = write(
+            "files",
+            "user.ron",
+            r#"(dimensions: None, keys: {"jump": Char('w')}, screen: (mode: Windowed))"#,
+        );
+
+        let config = LayeredConfig::<Window>::new()
+            .with_file(&pack)
+            .with_optional_file(&user)
+            .with_optional_file("missing.ron")
+            .load()
+            .unwrap();
+
+        assert_eq!(config.value.title, "Game");
+        assert_eq!(config.value.dimensions, None);
+        assert_eq!(config.value.keys["Quit"], Key::Escape);
+        assert_eq!(config.value.keys["jump"], Key::Char('w'));
+        assert_eq!(
+            config.value.screen,
+            Screen {
+                mode: Mode::Windowed,
+                scale: 2.0,
+            }
+        );
+    }
+
+    #[test]
+    fn env_vars_override_fields() {
+        let pack = write("env", "pack.ron", r#"(keys: {"Quit": Escape}, vsync: true)"#);
+        let vars = vec![
+            ("LAYERED_TEST_VSYNC", "false"),
+            ("LAYERED_TEST_TITLE", "Unquoted title"),
+            ("LAYERED_TEST_DIMENSIONS", "(640, 480)"),
+            ("LAYERED_TEST_KEYS__QUIT", "Char('q')"),
+            ("LAYERED_TEST_SCREEN__MODE", "Fullscreen(monitor: 2)"),
+            ("OTHER_VSYNC", "true"),
+        ];
+
+        let config = LayeredConfig::<Window>::new()
+            .with_file(&pack)
+            .with_env_vars("LAYERED_TEST", vars)
+            .load()
+            .unwrap();
+
+        assert!(!config.value.vsync);
+        assert_eq!(config.value.title, "Unquoted title");
+        assert_eq!(config.value.dimensions, Some((640, 480)));
+        assert_eq!(config.value.keys["Quit"], Key::Char('q'));
+        assert_eq!(config.value.keys.len(), 1);
+        assert_eq!(config.value.screen.mode, Mode::Fullscreen { monitor: 2 });
+
+        let result = LayeredConfig::<Window>::new()
+            .with_env_vars("LAYERED_TEST", vec![("LAYERED_TEST_NOPE", "1")])
+            .load();
+        match result {
+            Err(ConfigError::Invalid(errors)) => assert_eq!(
+                errors,
+                vec![FieldError {
+                    path: "NOPE".to_string(),
+                    message: "No such field".to_string(),
+                    source: Some("LAYERED_TEST_NOPE".to_string()),
+                }]
+            ),
+            other => panic!("Expected invalid fields, got {:?}", other.map(|c| c.value)),
+        }
+    }
+
+    #[test]
+    fn args_override_fields() {
+        let config = LayeredConfig::<Window>::new()
+            .with_env_vars("LAYERED_TEST", vec![("LAYERED_TEST_TITLE", "From env")])
+            .with_args(
+                "window",
+                vec![
+                    "game",
+                    "--other.title=Ignored",
+                    "--window.title=My game",
+                    "--window.vsync",
+                    "--window.keys.jump=Char('w')",
+                    "--window.screen.scale=0.5",
+                ],
+            ).load()
+            .unwrap();
+
+        assert_eq!(config.value.title, "My game");
+        assert!(config.value.vsync);
+        assert_eq!(config.value.keys["jump"], Key::Char('w'));
+        assert_eq!(
+            config.value.screen,
+            Screen {
+                mode: Mode::Windowed,
+                scale: 0.5,
+            }
+        );
+
+        let result = LayeredConfig::<Window>::new()
+            .with_args("window", vec!["--window.screen.scale=big"])
+            .load();
+        match result {
+            Err(ConfigError::Invalid(errors)) => {
+                assert_eq!(errors.len(), 1);
+                assert_eq!(errors[0].path, "screen.scale");
+                assert_eq!(errors[0].source, Some("--window.screen.scale".to_string()));
+            }
+            other => panic!("Expected invalid fields, got {:?}", other.map(|c| c.value)),
+        }
+    }
+
+    #[test]
+    fn sources_follow_the_layers() {
+        let pack = write(
+            "sources",
+            "pack.ron",
+            r#"(title: "Game", keys: {"Quit": Escape, "jump": Char(' ')}, screen: (scale: 2.0))"#,
+        );
+        let user = write("sources", "user.ron", r#"(keys: {"jump": Char('w')})"#);
+
+        let config = LayeredConfig::<Window>::new()
+            .with_file(&pack)
+            .with_file(&user)
+            .with_env_vars("LAYERED_TEST", vec![("LAYERED_TEST_VSYNC", "true")])
+            .with_args("window", vec!["--window.dimensions=(640, 480)"])
+            .with_value("settings", "screen.mode", to_value(&Mode::Windowed).unwrap())
+            .load()
+            .unwrap();
+
+        assert_eq!(config.source("title"), Some(&*source(&pack)));
+        assert_eq!(config.source("keys.Quit"), Some(&*source(&pack)));
+        assert_eq!(config.source("keys.jump"), Some(&*source(&user)));
+        assert_eq!(config.source("screen.scale"), Some(&*source(&pack)));
+        assert_eq!(config.source("vsync"), Some("LAYERED_TEST_VSYNC"));
+        assert_eq!(config.source("dimensions.0"), Some("--window.dimensions"));
+        assert_eq!(config.source("screen.mode"), Some("settings"));
+        assert_eq!(config.source("screen"), None);
+        assert!(!config.sources().contains_key("screen"));
+    }
+
+    #[derive(Deserialize)]
+    struct WindowV0 {
+        name: String,
+        vsync: bool,
+    }
+
+    #[test]
+    fn files_are_migrated() {
+        let pack = write("migrated", "pack.ron", r#"(version: 1, title: "Game", vsync: true)"#);
+        let old = write("migrated", "old.ron", r#"(name: "Old game")"#);
+
+        let config = LayeredConfig::<Window>::new()
+            .with_file(&pack)
+            .with_file(&old)
+            .with_version(1)
+            .with_migration(0, |old: WindowV0| {
+                Ok(Window {
+                    title: old.name,
+                    vsync: old.vsync,
+                    ..Window::default()
+                })
+            }).load()
+            .unwrap();
+
+        assert_eq!(config.value.title, "Old game");
+        assert!(config.value.vsync);
+        assert_eq!(config.source("title"), Some(&*source(&old)));
+        assert_eq!(config.source("vsync"), Some(&*source(&pack)));
+    }
+
+    #[test]
+    fn failed_migrations_are_errors() {
+        let old = write("failed_migration", "old.ron", r#"(name: "")"#);
+        let result = LayeredConfig::<Window>::new()
+            .with_file(&old)
+            .with_version(1)
+            .with_migration(0, |old: WindowV0| -> Result<Window, String> {
+                if old.name.is_empty() {
+                    Err("The name is empty".to_string())
+                } else {
+                    Ok(Window::default())
+                }
+            }).load();
+        match result {
+            Err(ConfigError::Migration(ref file, ref message)) => {
+                assert_eq!(*file, source(&old));
+                assert_eq!(message, "Migration from version 0 failed: The name is empty");
+            }
+            other => panic!("Expected a migration error, got {:?}", other.map(|c| c.value)),
+        }
+
+        let result = LayeredConfig::<Window>::new()
+            .with_file(&old)
+            .with_version(2)
+            .with_migration(0, |_: WindowV0| Ok(Window::default()))
+            .load();
+        match result {
+            Err(ConfigError::Migration(_, ref message)) => {
+                assert_eq!(message, "No migration from version 1")
+            }
+            other => panic!("Expected a migration error, got {:?}", other.map(|c| c.value)),
+        }
+    }
+
+    #[test]
+    fn newer_versions_are_errors() {
+        let new = write("newer", "new.ron", r#"(version: 2, title: "New game")"#);
+        match LayeredConfig::<Window>::new().with_file(&new).with_version(1).load() {
+            Err(ConfigError::Migration(ref file, ref message)) => {
+                assert_eq!(*file, source(&new));
+                assert_eq!(message, "Version 2 is newer than the supported version 1");
+            }
+            other => panic!("Expected a migration error, got {:?}", other.map(|c| c.value)),
+        }
+    }
+
+    #[test]
+    fn errors_point_at_fields() {
+        let invalid = write("errors", "invalid.ron", r#"(title: "Game", screen: (scale: "big"))"#);
+        match LayeredConfig::<Window>::new().with_file(&invalid).load() {
+            Err(ConfigError::Invalid(errors)) => {
+                assert_eq!(errors.len(), 1);
+                assert_eq!(errors[0].path, "screen.scale");
+                assert_eq!(errors[0].source, Some(source(&invalid)));
+            }
+            other => panic!("Expected invalid fields, got {:?}", other.map(|c| c.value)),
+        }
+    }
+
+    #[test]
+    fn validations_point_at_fields() {
+        let pack = write("validations", "pack.ron", r#"(screen: (scale: -1.0))"#);
+        let dimensions = ConfigValue::Option(Some(Box::new(ConfigValue::Tuple(vec![
+            ConfigValue::U64(0),
+            ConfigValue::U64(0),
+        ]))));
+
+        let result = LayeredConfig::<Window>::new()
+            .with_file(&pack)
+            .with_value("settings", "dimensions", dimensions)
+            .with_validation(|window| match window.dimensions {
+                Some((0, _)) | Some((_, 0)) => vec![FieldError::new("dimensions.0", "is empty")],
+                _ => vec![],
+            }).with_validation(|window| {
+                if window.screen.scale <= 0.0 {
+                    vec![FieldError::new("screen.scale", "must be positive")]
+                } else {
+                    vec![]
+                }
+            }).load();
+        match result {
+            Err(ConfigError::Invalid(errors)) => assert_eq!(
+                errors,
+                vec![
+                    FieldError {
+                        path: "dimensions.0".to_string(),
+                        message: "is empty".to_string(),
+                        source: Some("settings".to_string()),
+                    },
+                    FieldError {
+                        path: "screen.scale".to_string(),
+                        message: "must be positive".to_string(),
+                        source: Some(source(&pack)),
+                    },
+                ]
+            ),
+            other => panic!("Expected invalid fields, got {:?}", other.map(|c| c.value)),
+        }
+    }
+}
@@ -1,13 +1,19 @@
 //! Loads RON files into a structure for easy / statically typed usage.
 //!
+//! `Config` loads a single file, while `LayeredConfig` merges the defaults, files, environment
+//! variables and command line arguments, and can migrate and validate them.
 
 #![crate_name = "amethyst_config"]
 #![doc(html_logo_url = "http://tinyurl.com/hgsb45k")]
 
 extern crate ron;
+#[macro_use]
 extern crate serde;
 #[macro_use]
 extern crate log;
+#[cfg(test)]
+#[macro_use]
+extern crate serde_derive;
 
 #[cfg(feature = "profiler")]
 extern crate thread_profiler;
@@ -21,6 +27,10 @@ use ron::de::Error as DeError;
 use ron::ser::Error as SerError;
 use serde::{Deserialize, Serialize};
 
+pub use layered::{ConfigValue, FieldError, LayeredConfig, LoadedConfig, VERSION_FIELD};
+
+mod layered;
+
 /// Error related to anything that manages/creates configurations as well as
 /// "workspace"-related things.
 #[derive(Debug)]
@@ -33,6 +43,12 @@ pub enum ConfigError {
     Serializer(SerError),
     /// Related to the path of the file.
     Extension(PathBuf),
+    /// A layer of a `LayeredConfig` isn't valid RON: where the layer comes from and the error.
+    Syntax(String, String),
+    /// A file of a `LayeredConfig` couldn't be migrated: the file and the error.
+    Migration(String, String),
+    /// Fields of a `LayeredConfig` couldn't be deserialized or failed its validation.
+    Invalid(Vec<FieldError>),
 }
 
 impl fmt::Display for ConfigError {
@@ -54,6 +70,12 @@ impl fmt::Display for ConfigError {
                     found,
                 )
             }
+            ConfigError::Syntax(ref source, ref msg) => write!(f, "{}: {}", source, msg),
+            ConfigError::Migration(ref source, ref msg) => write!(f, "{}: {}", source, msg),
+            ConfigError::Invalid(ref errors) => {
+                let errors = errors.iter().map(ToString::to_string).collect::<Vec<_>>();
+                write!(f, "Invalid fields: {}", errors.join(", "))
+            }
         }
     }
 }
@@ -83,6 +105,9 @@ impl Error for ConfigError {
             ConfigError::Parser(_) => "Project parser error",
             ConfigError::Serializer(_) => "Project serializer error",
             ConfigError::Extension(_) => "Invalid extension or directory for a file",
+            ConfigError::Syntax(_, _) => "Config layer syntax error",
+            ConfigError::Migration(_, _) => "Config migration error",
+            ConfigError::Invalid(_) => "Invalid config fields",
         }
     }
 
@@ -0,0 +1,265 @@
+//! Named clocks, each with its own pause, scale and absolute time.
+
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::ops::Deref;
+use std::time::Duration;
+
+use shred::{Read, ResourceId, Resources, SystemData};
+
+use timing::{duration_to_secs, duration_to_secs_f64, secs_to_duration};
+
+/// Name of the clock of the game world, paused when the game is paused.
+pub const GAME_CLOCK: &str = "game";
+/// Name of the clock of the user interface, which keeps running when the game is paused.
+pub const UI_CLOCK: &str = "ui";
+/// Name of the clock following the real time.
+pub const REAL_CLOCK: &str = "real";
+
+/// A clock advanced every frame by the engine, which can be paused and scaled without
+/// affecting the other clocks.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Clock {
+    delta_time: Duration,
+    delta_seconds: f32,
+    absolute_time: Duration,
+    time_scale: f32,
+    paused: bool,
+}
+
+impl Clock {
+    /// Gets the time elapsed on this clock during the last frame.
+    pub fn delta_time(&self) -> Duration {
+        self.delta_time
+    }
+
+    /// Gets the time elapsed on this clock during the last frame, in seconds.
+    pub fn delta_seconds(&self) -> f32 {
+        self.delta_seconds
+    }
+
+    /// Gets the time elapsed on this clock since it was created.
+    pub fn absolute_time(&self) -> Duration {
+        self.absolute_time
+    }
+
+    /// Gets the time elapsed on this clock since it was created, in seconds.
+    pub fn absolute_time_seconds(&self) -> f64 {
+        duration_to_secs_f64(self.absolute_time)
+    }
+
+    /// Gets the speed of this clock compared to the real time.
+    pub fn time_scale(&self) -> f32 {
+        self.time_scale
+    }
+
+    /// Sets the speed of this clock compared to the real time.
+    ///
+    /// # Panics
+    ///
+    /// This will panic if the multiplier is negative.
+    pub fn set_time_scale(&mut self, multiplier: f32) {
+        use std::f32::INFINITY;
+        assert!(multiplier >= 0.0);
+        assert!(multiplier != INFINITY);
+        self.time_scale = multiplier;
+    }
+
+    /// Whether the clock is paused.
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Stops the clock until it is resumed.
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    /// Restarts the clock after a pause.
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    /// Advances the clock by a frame lasting `real_delta`.
+    ///
+    /// This should only be called by the engine.  Bad things might happen if you call this in
+    /// your game.
+    pub fn advance(&mut self, real_delta: Duration) {
+        if self.paused {
+            self.delta_seconds = 0.0;
+            self.delta_time = Duration::from_secs(0);
+        } else {
+            self.delta_seconds = duration_to_secs(real_delta) * self.time_scale;
+            self.delta_time = secs_to_duration(self.delta_seconds);
+        }
+        self.absolute_time += self.delta_time;
+    }
+}
+
+impl Default for Clock {
+    fn default() -> Clock {
+        Clock {
+            delta_time: Duration::from_secs(0),
+            delta_seconds: 0.0,
+            absolute_time: Duration::from_secs(0),
+            time_scale: 1.0,
+            paused: false,
+        }
+    }
+}
+
+/// The clocks of the game by name, advanced together every frame.
+///
+/// The `game`, `ui` and `real` clocks always exist; games can add their own with `add`.
+#[derive(Clone, Debug)]
+pub struct Clocks {
+    clocks: HashMap<String, Clock>,
+}
+
+impl Clocks {
+    /// Gets the clock with the given name.
+    pub fn get(&self, name: &str) -> Option<&Clock> {
+        self.clocks.get(name)
+    }
+
+    /// Gets the clock with the given name mutably, to pause or scale it.
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut Clock> {
+        self.clocks.get_mut(name)
+    }
+
+    /// Adds a clock with the given name, or gets it if it already exists.
+    pub fn add<N: Into<String>>(&mut self, name: N) -> &mut Clock {
+        self.clocks.entry(name.into()).or_insert_with(Clock::default)
+    }
+
+    /// Removes the clock with the given name.
+    pub fn remove(&mut self, name: &str) -> Option<Clock> {
+        self.clocks.remove(name)
+    }
+
+    /// Gets the names of the clocks.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.clocks.keys().map(|name| name.as_str())
+    }
+
+    /// Advances all the clocks by a frame lasting `real_delta`.
+    ///
+    /// This should only be called by the engine.  Bad things might happen if you call this in
+    /// your game.
+    pub fn advance(&mut self, real_delta: Duration) {
+        for clock in self.clocks.values_mut() {
+            clock.advance(real_delta);
+        }
+    }
+}
+
+impl Default for Clocks {
+    fn default() -> Clocks {
+        let mut clocks = Clocks {
+            clocks: HashMap::new(),
+        };
+        clocks.add(GAME_CLOCK);
+        clocks.add(UI_CLOCK);
+        clocks.add(REAL_CLOCK);
+        clocks
+    }
+}
+
+/// Names a clock of `Clocks`, to fetch it with `ReadClock`.
+pub trait ClockName: 'static {
+    /// The name of the clock.
+    const NAME: &'static str;
+}
+
+/// Marker for the `game` clock.
+pub struct GameClock;
+
+impl ClockName for GameClock {
+    const NAME: &'static str = GAME_CLOCK;
+}
+
+/// Marker for the `ui` clock.
+pub struct UiClock;
+
+impl ClockName for UiClock {
+    const NAME: &'static str = UI_CLOCK;
+}
+
+/// Marker for the `real` clock.
+pub struct RealClock;
+
+impl ClockName for RealClock {
+    const NAME: &'static str = REAL_CLOCK;
+}
+
+/// System data reading the clock named by `N`, e.g. `ReadClock<'a, UiClock>`.
+///
+/// # Panics
+///
+/// Dereferencing panics if the clock was removed from `Clocks`.
+pub struct ReadClock<'a, N> {
+    clocks: Read<'a, Clocks>,
+    marker: PhantomData<N>,
+}
+
+impl<'a, N: ClockName> Deref for ReadClock<'a, N> {
+    type Target = Clock;
+
+    fn deref(&self) -> &Clock {
+        self.clocks
+            .get(N::NAME)
+            .unwrap_or_else(|| panic!("No clock named \"{}\"", N::NAME))
+    }
+}
+
+impl<'a, N: ClockName> SystemData<'a> for ReadClock<'a, N> {
+    fn setup(res: &mut Resources) {
+        <Read<'a, Clocks> as SystemData<'a>>::setup(res);
+    }
+
+    fn fetch(res: &'a Resources) -> Self {
+        ReadClock {
+            clocks: <Read<'a, Clocks> as SystemData<'a>>::fetch(res),
+            marker: PhantomData,
+        }
+    }
+
+    fn reads() -> Vec<ResourceId> {
+        <Read<'a, Clocks> as SystemData<'a>>::reads()
+    }
+
+    fn writes() -> Vec<ResourceId> {
+        vec![]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paused_clock_stops() {
+        let mut clocks = Clocks::default();
+        clocks.get_mut(GAME_CLOCK).unwrap().pause();
+        clocks.get_mut(UI_CLOCK).unwrap().set_time_scale(2.0);
+        clocks.advance(Duration::from_millis(500));
+
+        assert_eq!(clocks.get(GAME_CLOCK).unwrap().absolute_time_seconds(), 0.0);
+        assert!((clocks.get(UI_CLOCK).unwrap().absolute_time_seconds() - 1.0).abs() < 1e-4);
+        assert!((clocks.get(REAL_CLOCK).unwrap().absolute_time_seconds() - 0.5).abs() < 1e-4);
+
+        clocks.get_mut(GAME_CLOCK).unwrap().resume();
+        clocks.advance(Duration::from_millis(500));
+        assert!((clocks.get(GAME_CLOCK).unwrap().absolute_time_seconds() - 0.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn read_clock() {
+        let mut res = Resources::new();
+        <ReadClock<UiClock> as SystemData>::setup(&mut res);
+        res.fetch_mut::<Clocks>().advance(Duration::from_secs(1));
+
+        let clock = <ReadClock<UiClock> as SystemData>::fetch(&res);
+        assert!((clock.absolute_time_seconds() - 1.0).abs() < 1e-4);
+    }
+}
@@ -134,6 +134,18 @@ impl Default for FrameRateLimitConfig {
     }
 }
 
+impl FrameRateLimitConfig {
+    /// Gets the frame rate limiting strategy.
+    pub fn strategy(&self) -> &FrameRateLimitStrategy {
+        &self.strategy
+    }
+
+    /// Gets the maximum frames per second, `0` for no limit.
+    pub fn fps(&self) -> u32 {
+        self.fps
+    }
+}
+
 /// Frame limiter resource.
 ///
 /// `FrameLimiter` is used internally by amethyst to limit the frame rate to the
@@ -22,6 +22,7 @@ extern crate thread_profiler;
 compile_error!("the cfg flag \"no_threading\" is required when building for emscripten");
 
 pub use bundle::{Error, ErrorKind, Result, SystemBundle};
+pub use clock::*;
 pub use orientation::Orientation;
 pub use timing::*;
 pub use transform::*;
@@ -29,6 +30,7 @@ pub use transform::*;
 use std::sync::Arc;
 
 pub mod bundle;
+pub mod clock;
 pub mod frame_limiter;
 mod orientation;
 pub mod timing;
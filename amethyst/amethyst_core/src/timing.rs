@@ -19,6 +19,12 @@ pub struct Time {
     fixed_time: Duration,
     /// Time at which `State::fixed_update` was last called.
     pub last_fixed_update: Instant,
+    /// Time elapsed that is not yet simulated by fixed updates.
+    fixed_accumulator: Duration,
+    /// Maximum number of fixed updates run in one frame to catch up.
+    max_fixed_updates: u32,
+    /// Fraction of a fixed step elapsed since the last fixed update.
+    interpolation_alpha: f32,
     /// The total number of frames that have been played in this session.
     frame_number: u64,
     ///Time elapsed since game start, ignoring the speed multipler.
@@ -60,6 +66,18 @@ impl Time {
         self.fixed_time
     }
 
+    /// Gets the maximum number of fixed updates run in one frame.
+    pub fn max_fixed_updates(&self) -> u32 {
+        self.max_fixed_updates
+    }
+
+    /// Gets how far the game is between the last fixed update and the next one, from 0 to 1.
+    ///
+    /// Rendering can interpolate between the last two fixed states with this factor.
+    pub fn interpolation_alpha(&self) -> f32 {
+        self.interpolation_alpha
+    }
+
     /// Gets the current frame number.  This increments by 1 every frame.  There is no frame 0.
     pub fn frame_number(&self) -> u64 {
         self.frame_number
@@ -136,6 +154,14 @@ impl Time {
         self.fixed_time = time;
     }
 
+    /// Sets the maximum number of fixed updates run in one frame.
+    ///
+    /// When a frame takes longer than this many fixed steps, the time left over is dropped
+    /// instead of slowing down the next frames.
+    pub fn set_max_fixed_updates(&mut self, max: u32) {
+        self.max_fixed_updates = max;
+    }
+
     /// Increments the current frame number by 1.
     ///
     /// This should only be called by the engine.  Bad things might happen if you call this in
@@ -156,6 +182,27 @@ impl Time {
         self.time_scale = multiplier;
     }
 
+    /// Adds the time of the last frame to the time to simulate with fixed updates.
+    ///
+    /// This should only be called by the engine.  Bad things might happen if you call this in
+    /// your game.
+    pub fn accumulate_fixed_time(&mut self) {
+        self.fixed_accumulator += self.delta_time;
+    }
+
+    /// Takes a fixed step from the accumulated time, returning whether a fixed update is due.
+    ///
+    /// This should only be called by the engine.  Bad things might happen if you call this in
+    /// your game.
+    pub fn step_fixed_update(&mut self) -> bool {
+        if self.fixed_time > Duration::from_secs(0) && self.fixed_accumulator >= self.fixed_time {
+            self.fixed_accumulator -= self.fixed_time;
+            true
+        } else {
+            false
+        }
+    }
+
     /// Indicates a fixed update just finished.
     ///
     /// This should only be called by the engine.  Bad things might happen if you call this in
@@ -163,6 +210,26 @@ impl Time {
     pub fn finish_fixed_update(&mut self) {
         self.last_fixed_update += self.fixed_time
     }
+
+    /// Indicates the fixed updates of the frame are done, and computes the interpolation alpha.
+    /// Whole steps left after `max_fixed_updates` are dropped.
+    ///
+    /// This should only be called by the engine.  Bad things might happen if you call this in
+    /// your game.
+    pub fn finish_fixed_updates(&mut self) {
+        if self.fixed_time == Duration::from_secs(0) {
+            self.interpolation_alpha = 0.0;
+            return;
+        }
+        if self.fixed_accumulator >= self.fixed_time {
+            let fixed = duration_to_nanos(self.fixed_time);
+            self.fixed_accumulator =
+                nanos_to_duration(duration_to_nanos(self.fixed_accumulator) % fixed);
+            self.last_fixed_update = Instant::now() - self.fixed_accumulator;
+        }
+        self.interpolation_alpha =
+            duration_to_secs(self.fixed_accumulator) / duration_to_secs(self.fixed_time);
+    }
 }
 
 impl Default for Time {
@@ -175,6 +242,9 @@ impl Default for Time {
             fixed_seconds: duration_to_secs(Duration::new(0, 16666666)),
             fixed_time: Duration::new(0, 16666666),
             last_fixed_update: Instant::now(),
+            fixed_accumulator: Duration::from_secs(0),
+            max_fixed_updates: 5,
+            interpolation_alpha: 0.0,
             frame_number: 0,
             absolute_real_time: Duration::default(),
             absolute_time: Duration::default(),
@@ -254,10 +324,49 @@ impl Stopwatch {
 // Unit tests
 #[cfg(test)]
 mod tests {
-    use super::Stopwatch;
+    use super::{Stopwatch, Time};
     use std::thread;
     use std::time::Duration;
 
+    /// Runs the fixed updates of a frame like the `Application` does, returning their number.
+    fn fixed_updates(time: &mut Time, frame: Duration) -> u32 {
+        time.set_delta_time(frame);
+        time.accumulate_fixed_time();
+        let mut updates = 0;
+        while updates < time.max_fixed_updates() && time.step_fixed_update() {
+            time.finish_fixed_update();
+            updates += 1;
+        }
+        time.finish_fixed_updates();
+        updates
+    }
+
+    #[test]
+    fn fixed_update_catch_up() {
+        let mut time = Time::default();
+        time.set_fixed_time(Duration::from_millis(10));
+
+        assert_eq!(fixed_updates(&mut time, Duration::from_millis(5)), 0);
+        assert!((time.interpolation_alpha() - 0.5).abs() < 1e-4);
+        // A slow frame runs several fixed updates, and keeps the remainder.
+        assert_eq!(fixed_updates(&mut time, Duration::from_millis(32)), 3);
+        assert!((time.interpolation_alpha() - 0.7).abs() < 1e-4);
+        assert_eq!(fixed_updates(&mut time, Duration::from_millis(3)), 1);
+        assert!(time.interpolation_alpha().abs() < 1e-4);
+    }
+
+    #[test]
+    fn fixed_update_cap() {
+        let mut time = Time::default();
+        time.set_fixed_time(Duration::from_millis(10));
+        time.set_max_fixed_updates(2);
+
+        // The steps over the cap are dropped instead of piling up.
+        assert_eq!(fixed_updates(&mut time, Duration::from_millis(55)), 2);
+        assert!((time.interpolation_alpha() - 0.5).abs() < 1e-4);
+        assert_eq!(fixed_updates(&mut time, Duration::from_millis(1)), 0);
+    }
+
     #[test]
     fn elapsed() {
         const DURATION: u64 = 1; // in seconds.
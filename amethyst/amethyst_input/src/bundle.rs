@@ -7,7 +7,7 @@ use std::result::Result as StdResult;
 #[cfg(feature = "sdl_controller")]
 use sdl_events_system::ControllerMappings;
 
-use amethyst_config::{Config, ConfigError};
+use amethyst_config::{Config, ConfigError, LayeredConfig};
 use amethyst_core::bundle::{Result, SystemBundle};
 use amethyst_core::specs::prelude::DispatcherBuilder;
 use serde::de::DeserializeOwned;
@@ -68,6 +68,19 @@ where
         Ok(self.with_bindings(Bindings::load_no_fallback(file)?))
     }
 
+    /// Load bindings from layers, e.g. the bindings of the game overridden by the user's
+    /// bindings file. Fails if a layer can't be read or the layers fail to merge.
+    pub fn with_layered_bindings(
+        self,
+        config: &LayeredConfig<Bindings<AX, AC>>,
+    ) -> StdResult<Self, ConfigError>
+    where
+        AX: DeserializeOwned + Serialize,
+        AC: DeserializeOwned + Serialize,
+    {
+        Ok(self.with_bindings(config.load()?.value))
+    }
+
     /// Load SDL controller mappings from file
     #[cfg(feature = "sdl_controller")]
     pub fn with_sdl_controller_mappings(mut self, mappings: String) -> Self {
@@ -1,10 +1,13 @@
 //! Color value types.
 
+use amethyst_core::specs::prelude::{Component, DenseVecStorage};
 use gfx::shade::{Formatted, ToUniform};
 use gfx_core::shade::{BaseType, ContainerType, UniformValue};
 use glsl_layout::{vec3, vec4};
 
 /// An RGBA color value.
+///
+/// As a component, it tints the sprite of its entity in `DrawSprite`.
 #[derive(Clone, Copy, Debug, Deserialize, PartialEq, PartialOrd, Serialize)]
 pub struct Rgba(pub f32, pub f32, pub f32, pub f32);
 
@@ -40,6 +43,10 @@ impl Rgba {
     }
 }
 
+impl Component for Rgba {
+    type Storage = DenseVecStorage<Self>;
+}
+
 impl Default for Rgba {
     fn default() -> Rgba {
         Rgba::black()
@@ -0,0 +1,13 @@
+use amethyst_core::specs::prelude::Component;
+use amethyst_core::specs::storage::NullStorage;
+
+/// Hidden mesh component
+///
+/// Entities with this component are skipped by the draw passes, but keep their meshes, materials
+/// and sprites loaded so they can be shown again by removing it.
+#[derive(Clone, Debug, Default)]
+pub struct Hidden;
+
+impl Component for Hidden {
+    type Storage = NullStorage<Self>;
+}
@@ -91,6 +91,7 @@ pub use formats::{
     GraphicsPrefab, ImageData, ImageError, JpgFormat, MaterialPrefab, MeshCreator, MeshData,
     ObjFormat, PngFormat, TextureData, TextureFormat, TextureMetadata, TexturePrefab,
 };
+pub use hidden::Hidden;
 pub use input::{ElementState, Event, KeyboardInput, MouseButton, VirtualKeyCode, WindowEvent};
 pub use light::{DirectionalLight, Light, LightPrefab, PointLight, SpotLight, SunLight};
 pub use mesh::{vertex_data, Mesh, MeshBuilder, MeshHandle, VertexBuffer};
@@ -139,6 +140,7 @@ mod cam;
 mod color;
 mod config;
 mod formats;
+mod hidden;
 mod input;
 mod light;
 mod mesh;
@@ -12,6 +12,7 @@ use glsl_layout::Uniform;
 use super::*;
 use cam::{ActiveCamera, Camera};
 use error::Result;
+use hidden::Hidden;
 use mesh::{Mesh, MeshHandle};
 use mtl::{Material, MaterialDefaults};
 use pass::util::{draw_mesh, get_camera, setup_textures, VertexArgs};
@@ -73,6 +74,7 @@ where
         ReadStorage<'a, MeshHandle>,
         ReadStorage<'a, Material>,
         ReadStorage<'a, GlobalTransform>,
+        ReadStorage<'a, Hidden>,
     );
 }
 
@@ -113,12 +115,15 @@ where
             mesh,
             material,
             global,
+            hidden,
         ): <Self as PassData<'a>>::Data,
     ) {
         let camera = get_camera(active, &camera, &global);
 
         match visibility {
-            None => for (mesh, material, global) in (&mesh, &material, &global).join() {
+            None => for (mesh, material, global, _) in
+                (&mesh, &material, &global, !&hidden).join()
+            {
                 draw_mesh(
                     encoder,
                     effect,
@@ -10,6 +10,7 @@ use glsl_layout::Uniform;
 use super::*;
 use cam::{ActiveCamera, Camera};
 use error::Result;
+use hidden::Hidden;
 use mesh::{Mesh, MeshHandle};
 use mtl::{Material, MaterialDefaults};
 use pass::skinning::{create_skinning_effect, setup_skinning_buffers};
@@ -82,6 +83,7 @@ impl<'a> PassData<'a> for DrawFlatSeparate {
         ReadStorage<'a, Material>,
         ReadStorage<'a, GlobalTransform>,
         ReadStorage<'a, JointTransforms>,
+        ReadStorage<'a, Hidden>,
     );
 }
 
@@ -137,13 +139,14 @@ impl Pass for DrawFlatSeparate {
             material,
             global,
             joints,
+            hidden,
         ): <Self as PassData<'a>>::Data,
     ) {
         let camera = get_camera(active, &camera, &global);
 
         match visibility {
-            None => for (entity, mesh, material, global) in
-                (&*entities, &mesh, &material, &global).join()
+            None => for (entity, mesh, material, global, _) in
+                (&*entities, &mesh, &material, &global, !&hidden).join()
             {
                 draw_mesh(
                     encoder,
@@ -11,6 +11,7 @@ use gfx_core::state::{Blend, ColorMask};
 use super::*;
 use cam::{ActiveCamera, Camera};
 use error::Result;
+use hidden::Hidden;
 use light::Light;
 use mesh::{Mesh, MeshHandle};
 use mtl::{Material, MaterialDefaults};
@@ -76,6 +77,7 @@ where
         ReadStorage<'a, Material>,
         ReadStorage<'a, GlobalTransform>,
         ReadStorage<'a, Light>,
+        ReadStorage<'a, Hidden>,
     );
 }
 
@@ -113,6 +115,7 @@ where
             material,
             global,
             light,
+            hidden,
         ): <Self as PassData<'a>>::Data,
     ) {
         let camera = get_camera(active, &camera, &global);
@@ -120,7 +123,9 @@ where
         set_light_args(effect, encoder, &light, &global, &ambient, camera);
 
         match visibility {
-            None => for (mesh, material, global) in (&mesh, &material, &global).join() {
+            None => for (mesh, material, global, _) in
+                (&mesh, &material, &global, !&hidden).join()
+            {
                 draw_mesh(
                     encoder,
                     effect,
@@ -9,6 +9,7 @@ use gfx_core::state::{Blend, ColorMask};
 use super::*;
 use cam::{ActiveCamera, Camera};
 use error::Result;
+use hidden::Hidden;
 use light::Light;
 use mesh::{Mesh, MeshHandle};
 use mtl::{Material, MaterialDefaults};
@@ -80,6 +81,7 @@ impl<'a> PassData<'a> for DrawPbmSeparate {
         ReadStorage<'a, GlobalTransform>,
         ReadStorage<'a, Light>,
         ReadStorage<'a, JointTransforms>,
+        ReadStorage<'a, Hidden>,
     );
 }
 
@@ -143,6 +145,7 @@ impl Pass for DrawPbmSeparate {
             global,
             light,
             joints,
+            hidden,
         ): <Self as PassData<'a>>::Data,
     ) {
         let camera = get_camera(active, &camera, &global);
@@ -150,8 +153,8 @@ impl Pass for DrawPbmSeparate {
         set_light_args(effect, encoder, &light, &global, &ambient, camera);
 
         match visibility {
-            None => for (entity, mesh, material, global) in
-                (&*entities, &mesh, &material, &global).join()
+            None => for (entity, mesh, material, global, _) in
+                (&*entities, &mesh, &material, &global, !&hidden).join()
             {
                 draw_mesh(
                     encoder,
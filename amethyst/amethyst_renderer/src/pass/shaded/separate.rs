@@ -9,6 +9,7 @@ use gfx_core::state::{Blend, ColorMask};
 use super::*;
 use cam::{ActiveCamera, Camera};
 use error::Result;
+use hidden::Hidden;
 use light::Light;
 use mesh::{Mesh, MeshHandle};
 use mtl::{Material, MaterialDefaults};
@@ -79,6 +80,7 @@ impl<'a> PassData<'a> for DrawShadedSeparate {
         ReadStorage<'a, GlobalTransform>,
         ReadStorage<'a, Light>,
         ReadStorage<'a, JointTransforms>,
+        ReadStorage<'a, Hidden>,
     );
 }
 
@@ -139,6 +141,7 @@ impl Pass for DrawShadedSeparate {
             global,
             light,
             joints,
+            hidden,
         ): <Self as PassData<'a>>::Data,
     ) {
         trace!("Drawing shaded pass");
@@ -147,8 +150,8 @@ impl Pass for DrawShadedSeparate {
         set_light_args(effect, encoder, &light, &global, &ambient, camera);
 
         match visibility {
-            None => for (entity, mesh, material, global) in
-                (&*entities, &mesh, &material, &global).join()
+            None => for (entity, mesh, material, global, _) in
+                (&*entities, &mesh, &material, &global, !&hidden).join()
             {
                 draw_mesh(
                     encoder,
@@ -4,10 +4,15 @@
 
 uniform sampler2D albedo;
 
+layout (std140) uniform TintArgs {
+    // Color multiplied with the texture, white when the entity has no tint.
+    uniform vec4 tint;
+};
+
 in vec2 tex_uv;
 
 out vec4 color;
 
 void main() {
-    color = texture(albedo, tex_uv);
+    color = texture(albedo, tex_uv) * tint;
 }
@@ -1,16 +1,18 @@
 //! Flat forward drawing pass that mimics a blit.
 
 use amethyst_assets::AssetStorage;
-use amethyst_core::specs::prelude::{Join, Read, ReadStorage};
+use amethyst_core::specs::prelude::{Entities, Join, Read, ReadStorage};
 use amethyst_core::transform::GlobalTransform;
 use gfx_core::state::{Blend, ColorMask};
 use glsl_layout::Uniform;
 
 use super::*;
 use cam::{ActiveCamera, Camera};
+use color::Rgba;
 use error::Result;
+use hidden::Hidden;
 use mtl::MaterialTextureSet;
-use pass::util::{draw_sprite, get_camera, setup_textures, SpriteArgs, VertexArgs};
+use pass::util::{draw_sprite, get_camera, setup_textures, SpriteArgs, TintArgs, VertexArgs};
 use pipe::pass::{Pass, PassData};
 use pipe::{DepthMode, Effect, NewEffect};
 use sprite::{SpriteRender, SpriteSheet};
@@ -18,7 +20,7 @@ use tex::Texture;
 use types::{Encoder, Factory};
 use visibility::Visibility;
 
-/// Draws sprites on a 2D quad.
+/// Draws sprites on a 2D quad, tinted by the `Rgba` component of their entity if they have one.
 #[derive(Derivative, Clone, Debug, PartialEq)]
 #[derivative(Default(bound = "Self: Pass"))]
 pub struct DrawSprite {
@@ -48,6 +50,7 @@ where
 
 impl<'a> PassData<'a> for DrawSprite {
     type Data = (
+        Entities<'a>,
         Option<Read<'a, ActiveCamera>>,
         ReadStorage<'a, Camera>,
         Read<'a, AssetStorage<SpriteSheet>>,
@@ -55,7 +58,9 @@ impl<'a> PassData<'a> for DrawSprite {
         Read<'a, MaterialTextureSet>,
         Option<Read<'a, Visibility>>,
         ReadStorage<'a, SpriteRender>,
+        ReadStorage<'a, Rgba>,
         ReadStorage<'a, GlobalTransform>,
+        ReadStorage<'a, Hidden>,
     );
 }
 
@@ -73,6 +78,11 @@ impl Pass for DrawSprite {
                 "SpriteArgs",
                 mem::size_of::<<SpriteArgs as Uniform>::Std140>(),
                 1,
+            )
+            .with_raw_constant_buffer(
+                "TintArgs",
+                mem::size_of::<<TintArgs as Uniform>::Std140>(),
+                1,
             );
         setup_textures(&mut builder, &TEXTURES);
         match self.transparency {
@@ -88,6 +98,7 @@ impl Pass for DrawSprite {
         effect: &mut Effect,
         _factory: Factory,
         (
+            entities,
             active,
             camera,
             sprite_sheet_storage,
@@ -95,17 +106,22 @@ impl Pass for DrawSprite {
             material_texture_set,
             visibility,
             sprite_render,
+            tint,
             global,
+            hidden,
         ): <Self as PassData<'a>>::Data,
     ) {
         let camera = get_camera(active, &camera, &global);
 
         match visibility {
-            None => for (sprite_render, global) in (&sprite_render, &global).join() {
+            None => for (entity, sprite_render, global, _) in
+                (&*entities, &sprite_render, &global, !&hidden).join()
+            {
                 draw_sprite(
                     encoder,
                     effect,
                     sprite_render,
+                    tint.get(entity),
                     &sprite_sheet_storage,
                     &tex_storage,
                     &material_texture_set,
@@ -114,13 +130,18 @@ impl Pass for DrawSprite {
                 );
             },
             Some(ref visibility) => {
-                for (sprite_render, global, _) in
-                    (&sprite_render, &global, &visibility.visible_unordered).join()
+                for (entity, sprite_render, global, _) in (
+                    &*entities,
+                    &sprite_render,
+                    &global,
+                    &visibility.visible_unordered,
+                ).join()
                 {
                     draw_sprite(
                         encoder,
                         effect,
                         sprite_render,
+                        tint.get(entity),
                         &sprite_sheet_storage,
                         &tex_storage,
                         &material_texture_set,
@@ -135,6 +156,7 @@ impl Pass for DrawSprite {
                             encoder,
                             effect,
                             sprite_render,
+                            tint.get(*entity),
                             &sprite_sheet_storage,
                             &tex_storage,
                             &material_texture_set,
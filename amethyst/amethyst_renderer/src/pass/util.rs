@@ -8,6 +8,7 @@ use amethyst_core::GlobalTransform;
 use glsl_layout::*;
 
 use cam::{ActiveCamera, Camera};
+use color::Rgba;
 use mesh::Mesh;
 use mtl::{Material, MaterialDefaults, MaterialTextureSet, TextureOffset};
 use pass::set_skinning_buffers;
@@ -45,6 +46,12 @@ pub(crate) struct SpriteArgs {
     flip_vertical: boolean,
 }
 
+#[repr(C, align(16))]
+#[derive(Clone, Copy, Debug, Uniform)]
+pub(crate) struct TintArgs {
+    tint: vec4,
+}
+
 #[repr(C, align(16))]
 #[derive(Clone, Copy, Debug, Uniform)]
 pub(crate) struct TextureOffsetPod {
@@ -275,6 +282,13 @@ pub(crate) fn set_sprite_args(
     effect.update_constant_buffer("SpriteArgs", &geometry_args.std140(), encoder);
 }
 
+pub(crate) fn set_tint_args(effect: &mut Effect, encoder: &mut Encoder, tint: Option<&Rgba>) {
+    let tint_args = TintArgs {
+        tint: tint.cloned().unwrap_or_else(Rgba::white).into(),
+    };
+    effect.update_constant_buffer("TintArgs", &tint_args.std140(), encoder);
+}
+
 pub(crate) fn draw_mesh(
     encoder: &mut Encoder,
     effect: &mut Effect,
@@ -329,6 +343,7 @@ pub(crate) fn draw_sprite(
     encoder: &mut Encoder,
     effect: &mut Effect,
     sprite_render: &SpriteRender,
+    tint: Option<&Rgba>,
     sprite_sheet_storage: &AssetStorage<SpriteSheet>,
     tex_storage: &AssetStorage<Texture>,
     material_texture_set: &MaterialTextureSet,
@@ -372,6 +387,7 @@ pub(crate) fn draw_sprite(
     // Sprite vertex shader
     set_vertex_args(effect, encoder, camera, global.unwrap());
     set_sprite_args(effect, encoder, sprite, sprite_render);
+    set_tint_args(effect, encoder, tint);
 
     add_texture(effect, texture.unwrap());
 
@@ -6,6 +6,7 @@ use amethyst_core::GlobalTransform;
 use hibitset::BitSet;
 
 use cam::{ActiveCamera, Camera};
+use hidden::Hidden;
 use transparent::Transparent;
 
 /// Resource for controlling what entities should be rendered, and whether to draw them ordered or
@@ -19,7 +20,8 @@ pub struct Visibility {
 }
 
 /// Determine what entities are visible to the camera, and which are not. Will also sort transparent
-/// entities back to front based on distance from camera.
+/// entities back to front based on distance from camera. Entities with a `Hidden` component are
+/// never visible.
 ///
 /// Note that this should run after `GlobalTransform` has been updated for the current frame, and
 /// before rendering occurs.
@@ -55,11 +57,12 @@ impl<'a> System<'a> for VisibilitySortingSystem {
         ReadStorage<'a, Camera>,
         ReadStorage<'a, Transparent>,
         ReadStorage<'a, GlobalTransform>,
+        ReadStorage<'a, Hidden>,
     );
 
     fn run(
         &mut self,
-        (entities, mut visibility, active, camera, transparent, global): Self::SystemData,
+        (entities, mut visibility, active, camera, transparent, global, hidden): Self::SystemData,
     ) {
         let origin = Point3::origin();
 
@@ -75,9 +78,9 @@ impl<'a> System<'a> for VisibilitySortingSystem {
 
         self.centroids.clear();
         self.centroids.extend(
-            (&*entities, &global)
+            (&*entities, &global, !&hidden)
                 .join()
-                .map(|(entity, global)| (entity, global.0.transform_point(origin)))
+                .map(|(entity, global, _)| (entity, global.0.transform_point(origin)))
                 .map(|(entity, centroid)| Internals {
                     entity,
                     transparent: transparent.contains(entity),
@@ -14,4 +14,5 @@ extern crate thread_profiler;
 pub mod circular_buffer;
 pub mod fps_counter;
 pub mod scene;
+pub mod state_scoped;
 pub mod tag;
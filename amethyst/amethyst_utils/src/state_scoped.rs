@@ -0,0 +1,81 @@
+//! Entities tied to the lifetime of the state that created them.
+
+use amethyst_assets::{PrefabData, PrefabError};
+use amethyst_core::specs::prelude::{Component, DenseVecStorage, Entity, WriteStorage};
+
+/// What happens to a `StateScoped` entity while its state is paused by another one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OnPause {
+    /// The entity stays as it is.
+    Keep,
+    /// The entity gets the renderer's `Hidden` component until its state resumes.
+    Hide,
+}
+
+impl Default for OnPause {
+    fn default() -> Self {
+        OnPause::Keep
+    }
+}
+
+/// Tags an entity with the state that created it. The `StateMachine` deletes the entity when
+/// that state stops.
+///
+/// The entity is claimed by the active state at the end of the `on_start`, `on_resume`,
+/// `handle_event`, `fixed_update` or `update` call that created it, including the frame's
+/// systems when the state dispatches them from its `update`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StateScoped {
+    /// What happens to the entity while its state is paused.
+    pub on_pause: OnPause,
+    #[serde(skip)]
+    depth: Option<usize>,
+}
+
+impl StateScoped {
+    /// Creates a tag keeping the entity as it is while its state is paused.
+    pub fn new() -> Self {
+        StateScoped::default()
+    }
+
+    /// Creates a tag hiding the entity while its state is paused.
+    pub fn hidden_on_pause() -> Self {
+        StateScoped {
+            on_pause: OnPause::Hide,
+            depth: None,
+        }
+    }
+
+    /// Gets the depth in the state stack of the state owning the entity, if it was claimed yet.
+    pub fn depth(&self) -> Option<usize> {
+        self.depth
+    }
+
+    /// Gives the entity to the state at `depth` in the stack, if it has no state yet.
+    ///
+    /// This should only be called by the engine.
+    pub fn claim(&mut self, depth: usize) {
+        if self.depth.is_none() {
+            self.depth = Some(depth);
+        }
+    }
+}
+
+impl Component for StateScoped {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl<'a> PrefabData<'a> for StateScoped {
+    type SystemData = WriteStorage<'a, StateScoped>;
+    type Result = ();
+
+    fn load_prefab(
+        &self,
+        entity: Entity,
+        storage: &mut Self::SystemData,
+        _: &[Entity],
+    ) -> Result<(), PrefabError> {
+        storage.insert(entity, self.clone()).map(|_| ())
+    }
+}
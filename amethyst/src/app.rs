@@ -15,11 +15,14 @@ use winit::{Event, WindowEvent};
 
 use assets::{Loader, Source};
 use core::frame_limiter::{FrameLimiter, FrameRateLimitConfig, FrameRateLimitStrategy};
+use core::clock::Clocks;
 use core::timing::{Stopwatch, Time};
 use ecs::common::Errors;
 use ecs::prelude::{Component, World};
 use error::{Error, Result};
+use error_policy::ErrorPolicy;
 use game_data::DataInit;
+use renderer::ScreenDimensions;
 use state::{State, StateData, StateMachine};
 use vergen;
 
@@ -101,6 +104,9 @@ pub struct Application<'a, T> {
     events_reader_id: ReaderId<Event>,
     states: StateMachine<'a, T>,
     ignore_window_close: bool,
+    initialized: bool,
+    #[derivative(Debug = "ignore")]
+    error_policy: ErrorPolicy,
     data: T,
 }
 
@@ -179,23 +185,29 @@ impl<'a, T> Application<'a, T> {
     /// `Trans::Pop` on the last state in from the stack. See full
     /// documentation on this in [State](trait.State.html) documentation.
     ///
+    /// The game data must be `'static`, since the `TransQueue<T>` is a resource of the world.
+    ///
     /// # Examples
     ///
     /// See the example supplied in the
     /// [`new`](struct.Application.html#examples) method.
-    pub fn run(&mut self) {
+    pub fn run(&mut self)
+    where
+        T: 'static,
+    {
         self.initialize();
         self.world.write_resource::<Stopwatch>().start();
         while self.states.is_running() {
             self.advance_frame();
 
             self.world.write_resource::<FrameLimiter>().wait();
+            let elapsed = self.world.read_resource::<Stopwatch>().elapsed();
             {
-                let elapsed = self.world.read_resource::<Stopwatch>().elapsed();
                 let mut time = self.world.write_resource::<Time>();
                 time.increment_frame_number();
                 time.set_delta_time(elapsed);
             }
+            self.world.write_resource::<Clocks>().advance(elapsed);
             let mut stopwatch = self.world.write_resource::<Stopwatch>();
             stopwatch.stop();
             stopwatch.restart();
@@ -204,16 +216,109 @@ impl<'a, T> Application<'a, T> {
         self.shutdown();
     }
 
+    /// Advances the game by a single frame lasting `delta`, instead of measuring the time with
+    /// the `Stopwatch` like `run` does. The states are started on the first call, and the
+    /// application shuts down when they stop running.
+    ///
+    /// This is meant for tests, with an application built by
+    /// [`ApplicationBuilder::headless`](struct.ApplicationBuilder.html#method.headless): events
+    /// can be sent with `send_event` and the world inspected with `world` between the steps.
+    ///
+    /// Like `run`, this needs `'static` game data.
+    ///
+    /// # Returns
+    ///
+    /// Whether the game is still running after the frame.
+    ///
+    /// # Examples
+    ///
+    /// ~~~no_run
+    /// use std::time::Duration;
+    ///
+    /// use amethyst::prelude::*;
+    ///
+    /// struct NullState;
+    /// impl State<()> for NullState {}
+    ///
+    /// let mut game = Application::build("assets/", NullState)
+    ///     .expect("Failed to initialize")
+    ///     .headless(800, 600)
+    ///     .build(())
+    ///     .expect("Failed to create Application");
+    ///
+    /// while game.step(Duration::from_millis(16)) {
+    ///     // inspect `game.world()` here
+    /// }
+    /// ~~~
+    pub fn step(&mut self, delta: Duration) -> bool
+    where
+        T: 'static,
+    {
+        if !self.initialized {
+            self.initialize();
+        }
+        if !self.states.is_running() {
+            return false;
+        }
+        {
+            let mut time = self.world.write_resource::<Time>();
+            time.increment_frame_number();
+            time.set_delta_time(delta);
+        }
+        self.world.write_resource::<Clocks>().advance(delta);
+        self.advance_frame();
+        if !self.states.is_running() {
+            self.shutdown();
+            return false;
+        }
+        true
+    }
+
+    /// Whether the states are still running, see `step`.
+    pub fn is_running(&self) -> bool {
+        self.states.is_running()
+    }
+
+    /// Pushes an event in the `EventChannel<Event>`, to be handled by the states and the systems
+    /// during the next frame.
+    pub fn send_event(&mut self, event: Event) {
+        self.world
+            .write_resource::<EventChannel<Event>>()
+            .single_write(event);
+    }
+
+    /// Gets the world, to inspect it between frames.
+    pub fn world(&self) -> &World {
+        &self.world
+    }
+
+    /// Gets the world mutably, to change it between frames.
+    pub fn world_mut(&mut self) -> &mut World {
+        &mut self.world
+    }
+
+    /// Gets the game data.
+    pub fn data(&self) -> &T {
+        &self.data
+    }
+
     /// Sets up the application.
-    fn initialize(&mut self) {
+    fn initialize(&mut self)
+    where
+        T: 'static,
+    {
         #[cfg(feature = "profiler")]
         profile_scope!("initialize");
+        self.initialized = true;
         self.states
             .start(StateData::new(&mut self.world, &mut self.data));
     }
 
     /// Advances the game world by one tick.
-    fn advance_frame(&mut self) {
+    fn advance_frame(&mut self)
+    where
+        T: 'static,
+    {
         trace!("Advancing frame (`Application::advance_frame`)");
 
         {
@@ -252,17 +357,24 @@ impl<'a, T> Application<'a, T> {
             }
         }
         {
-            let do_fixed = {
-                let time = self.world.write_resource::<Time>();
-                time.last_fixed_update().elapsed() >= time.fixed_time()
+            let max_fixed_updates = {
+                let mut time = self.world.write_resource::<Time>();
+                time.accumulate_fixed_time();
+                time.max_fixed_updates()
             };
             #[cfg(feature = "profiler")]
             profile_scope!("fixed_update");
-            if do_fixed {
+            // Catches up with the time elapsed since the last frame, up to the cap.
+            let mut fixed_updates = 0;
+            while fixed_updates < max_fixed_updates
+                && self.world.write_resource::<Time>().step_fixed_update()
+            {
                 self.states
                     .fixed_update(StateData::new(&mut self.world, &mut self.data));
                 self.world.write_resource::<Time>().finish_fixed_update();
+                fixed_updates += 1;
             }
+            self.world.write_resource::<Time>().finish_fixed_updates();
 
             #[cfg(feature = "profiler")]
             profile_scope!("update");
@@ -274,10 +386,20 @@ impl<'a, T> Application<'a, T> {
         profile_scope!("maintain");
         self.world.maintain();
 
-        // TODO: replace this with a more customizable method.
-        // TODO: effectively, the user should have more control over error handling here
-        // TODO: because right now the app will just exit in case of an error.
-        self.world.write_resource::<Errors>().print_and_exit();
+        let errors = self.world.write_resource::<Errors>().collect();
+        for error in errors {
+            let severity = self.error_policy.report(&*error);
+            self.states.handle_error(
+                StateData::new(&mut self.world, &mut self.data),
+                &*error,
+                severity,
+            );
+            if self.error_policy.stops(severity) && self.states.is_running() {
+                error!("Stopping the application after a {:?} error", severity);
+                self.states
+                    .stop(StateData::new(&mut self.world, &mut self.data));
+            }
+        }
     }
 
     /// Cleans up after the quit signal is received.
@@ -308,6 +430,7 @@ pub struct ApplicationBuilder<S> {
     /// Used by bundles to access the world directly
     pub world: World,
     ignore_window_close: bool,
+    error_policy: ErrorPolicy,
 }
 
 impl<S> ApplicationBuilder<S> {
@@ -412,11 +535,13 @@ impl<S> ApplicationBuilder<S> {
         world.add_resource(FrameLimiter::default());
         world.add_resource(Stopwatch::default());
         world.add_resource(Time::default());
+        world.add_resource(Clocks::default());
 
         Ok(ApplicationBuilder {
             initial_state,
             world,
             ignore_window_close: false,
+            error_policy: ErrorPolicy::default(),
         })
     }
 
@@ -592,6 +717,27 @@ impl<S> ApplicationBuilder<S> {
         self
     }
 
+    /// Replaces the default asset source of the `Loader`, which is otherwise a
+    /// [`Directory`](../amethyst_assets/struct.Directory.html) at the path given to `build`.
+    ///
+    /// # Parameters
+    ///
+    /// - `store`: The asset store used when no source is given.
+    ///
+    /// # Returns
+    ///
+    /// This function returns ApplicationBuilder after it has modified it.
+    pub fn with_default_source<O>(self, store: O) -> Self
+    where
+        O: Source,
+    {
+        {
+            let mut loader = self.world.write_resource::<Loader>();
+            loader.set_default_source(store);
+        }
+        self
+    }
+
     /// Sets the maximum frames per second of this game.
     ///
     /// # Parameters
@@ -636,6 +782,45 @@ impl<S> ApplicationBuilder<S> {
         self
     }
 
+    /// Sets the maximum number of fixed updates run in one frame to catch up with slow frames,
+    /// defaults to 5. The time left over after the last one is dropped.
+    ///
+    /// # Parameters
+    ///
+    /// `max`: The maximum number of fixed updates per frame.
+    ///
+    /// # Returns
+    ///
+    /// This function returns the ApplicationBuilder after modifying it.
+    pub fn with_max_fixed_updates(self, max: u32) -> Self {
+        self.world.write_resource::<Time>().set_max_fixed_updates(max);
+        self
+    }
+
+    /// Sets the application up to run without a window or a renderer, for example in tests
+    /// driven by [`Application::step`](struct.Application.html#method.step) on a machine
+    /// without a GPU.
+    ///
+    /// The frame rate is not limited, and `ScreenDimensions` are added with the given size so the
+    /// UI systems work without a window. The game data must then be built without the render
+    /// bundle.
+    ///
+    /// # Parameters
+    ///
+    /// - `width`: The width of the pretended screen, in pixels.
+    /// - `height`: The height of the pretended screen, in pixels.
+    ///
+    /// # Returns
+    ///
+    /// This function returns the ApplicationBuilder after modifying it.
+    pub fn headless(mut self, width: u32, height: u32) -> Self {
+        self.world
+            .add_resource(FrameLimiter::new(FrameRateLimitStrategy::Unlimited, 0));
+        self.world
+            .add_resource(ScreenDimensions::new(width, height, 1.0));
+        self
+    }
+
     /// Tells the resulting application window to ignore close events if ignore is true.
     /// This will make your game window unresponsive to operating system close commands.
     /// Use with caution.
@@ -652,6 +837,49 @@ impl<S> ApplicationBuilder<S> {
         self
     }
 
+    /// Sets what the application does with the errors reported through the `Errors` resource.
+    /// By default any error stops it.
+    ///
+    /// # Parameters
+    ///
+    /// `policy`: The severity of the errors, their handler and whether recoverable errors stop
+    /// the application.
+    ///
+    /// # Returns
+    ///
+    /// This function returns the ApplicationBuilder after modifying it.
+    ///
+    /// # Examples
+    ///
+    /// ~~~no_run
+    /// use amethyst::prelude::*;
+    /// use amethyst::{ErrorPolicy, Severity};
+    ///
+    /// struct NullState;
+    /// impl State<()> for NullState {}
+    ///
+    /// let mut game = Application::build("assets/", NullState)
+    ///     .expect("Failed to initialize")
+    ///     .with_error_policy(
+    ///         ErrorPolicy::default()
+    ///             .with_severity(|error| {
+    ///                 if error.to_string().contains("audio") {
+    ///                     Severity::Warning
+    ///                 } else {
+    ///                     Severity::Recoverable
+    ///                 }
+    ///             })
+    ///             .continue_on_recoverable(true),
+    ///     )
+    ///     .build(())
+    ///     .expect("Failed to build");
+    /// game.run();
+    /// ~~~
+    pub fn with_error_policy(mut self, policy: ErrorPolicy) -> Self {
+        self.error_policy = policy;
+        self
+    }
+
     /// Build an `Application` object using the `ApplicationBuilder` as configured.
     ///
     /// # Returns
@@ -695,7 +923,107 @@ impl<S> ApplicationBuilder<S> {
             states: StateMachine::new(self.initial_state),
             events_reader_id: reader_id,
             ignore_window_close: self.ignore_window_close,
+            initialized: false,
+            error_policy: self.error_policy,
             data,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::io;
+    use std::rc::Rc;
+
+    use super::*;
+    use error_policy::{ReportedError, Severity};
+    use state::Trans;
+
+    struct CountState(u32);
+
+    impl State<()> for CountState {
+        fn update(&mut self, _: StateData<()>) -> Trans<()> {
+            self.0 += 1;
+            if self.0 == 3 {
+                Trans::Quit
+            } else {
+                Trans::None
+            }
+        }
+    }
+
+    #[test]
+    fn headless_step() {
+        let mut game = Application::build("assets/", CountState(0))
+            .unwrap()
+            .headless(800, 600)
+            .build(())
+            .unwrap();
+
+        assert!(game.step(Duration::from_millis(10)));
+        assert!(game.step(Duration::from_millis(20)));
+        {
+            let time = game.world().read_resource::<Time>();
+            assert_eq!(time.frame_number(), 2);
+            assert_eq!(time.delta_real_time(), Duration::from_millis(20));
+            assert_eq!(time.absolute_real_time(), Duration::from_millis(30));
+        }
+        assert!(!game.step(Duration::from_millis(10)));
+        assert!(!game.is_running());
+    }
+
+    struct FailingState(Rc<Cell<u32>>);
+
+    impl State<()> for FailingState {
+        fn update(&mut self, data: StateData<()>) -> Trans<()> {
+            data.world
+                .read_resource::<Errors>()
+                .add(Box::new(io::Error::new(io::ErrorKind::Other, "asset failed")));
+            Trans::None
+        }
+
+        fn handle_error(
+            &mut self,
+            _: StateData<()>,
+            _: &ReportedError,
+            severity: Severity,
+        ) -> Trans<()> {
+            assert_eq!(severity, Severity::Recoverable);
+            self.0.set(self.0.get() + 1);
+            Trans::None
+        }
+    }
+
+    #[test]
+    fn error_policy() {
+        let handled = Rc::new(Cell::new(0));
+        let reported = Rc::new(Cell::new(0));
+        let counter = reported.clone();
+        let mut game = Application::build("assets/", FailingState(handled.clone()))
+            .unwrap()
+            .headless(800, 600)
+            .with_error_policy(
+                ErrorPolicy::default()
+                    .with_handler(move |_, _| counter.set(counter.get() + 1))
+                    .continue_on_recoverable(true),
+            )
+            .build(())
+            .unwrap();
+
+        assert!(game.step(Duration::from_millis(10)));
+        assert!(game.step(Duration::from_millis(10)));
+        assert_eq!(handled.get(), 2);
+        assert_eq!(reported.get(), 2);
+
+        let handled = Rc::new(Cell::new(0));
+        let mut game = Application::build("assets/", FailingState(handled.clone()))
+            .unwrap()
+            .headless(800, 600)
+            .build(())
+            .unwrap();
+
+        assert!(!game.step(Duration::from_millis(10)));
+        assert_eq!(handled.get(), 1);
+    }
+}
@@ -0,0 +1,89 @@
+//! Handling of the errors reported by systems through the `Errors` resource.
+
+use std::error::Error as StdError;
+
+/// An error reported through the `Errors` resource.
+pub type ReportedError = StdError + Send + Sync + 'static;
+
+/// How serious a reported error is, which decides whether the application keeps running.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// The error is logged and the application keeps running.
+    Warning,
+    /// The application keeps running if the policy continues after recoverable errors,
+    /// otherwise it stops.
+    Recoverable,
+    /// The application stops.
+    Fatal,
+}
+
+/// Decides what the `Application` does with the errors reported during a frame.
+///
+/// Every error is given a `Severity`, logged, passed to the handler if there is one and then to
+/// the active state through `State::handle_error`. The states are stopped afterwards if the
+/// error is fatal, or recoverable while the policy doesn't continue after recoverable errors.
+///
+/// By default every error is recoverable and stops the application.
+pub struct ErrorPolicy {
+    severity: Box<Fn(&ReportedError) -> Severity>,
+    handler: Option<Box<FnMut(&ReportedError, Severity)>>,
+    continue_on_recoverable: bool,
+}
+
+impl ErrorPolicy {
+    /// Sets the function giving the severity of each error.
+    pub fn with_severity<F>(mut self, severity: F) -> Self
+    where
+        F: Fn(&ReportedError) -> Severity + 'static,
+    {
+        self.severity = Box::new(severity);
+        self
+    }
+
+    /// Sets the callback called with every error and its severity, after it was logged.
+    pub fn with_handler<F>(mut self, handler: F) -> Self
+    where
+        F: FnMut(&ReportedError, Severity) + 'static,
+    {
+        self.handler = Some(Box::new(handler));
+        self
+    }
+
+    /// Sets whether the application keeps running after recoverable errors.
+    pub fn continue_on_recoverable(mut self, continue_on_recoverable: bool) -> Self {
+        self.continue_on_recoverable = continue_on_recoverable;
+        self
+    }
+
+    /// Gets the severity of the error, then logs it and passes it to the handler.
+    pub fn report(&mut self, error: &ReportedError) -> Severity {
+        let severity = (self.severity)(error);
+        match severity {
+            Severity::Warning => warn!("{}", error),
+            Severity::Recoverable | Severity::Fatal => error!("{}", error),
+        }
+        if let Some(ref mut handler) = self.handler {
+            handler(error, severity);
+        }
+        severity
+    }
+
+    /// Whether an error of the given severity stops the application.
+    pub fn stops(&self, severity: Severity) -> bool {
+        match severity {
+            Severity::Warning => false,
+            Severity::Recoverable => !self.continue_on_recoverable,
+            Severity::Fatal => true,
+        }
+    }
+}
+
+impl Default for ErrorPolicy {
+    fn default() -> Self {
+        ErrorPolicy {
+            severity: Box::new(|_| Severity::Recoverable),
+            handler: None,
+            continue_on_recoverable: false,
+        }
+    }
+}
@@ -13,26 +13,107 @@ pub trait DataInit<T> {
     fn build(self, world: &mut World) -> T;
 }
 
+/// A named set of systems that states enable while they need it
+struct SystemGroup<'a, 'b> {
+    name: String,
+    dispatcher: Dispatcher<'a, 'b>,
+    enabled: bool,
+}
+
 /// Default game data
 pub struct GameData<'a, 'b> {
     dispatcher: Dispatcher<'a, 'b>,
+    fixed_dispatcher: Option<Dispatcher<'a, 'b>>,
+    groups: Vec<SystemGroup<'a, 'b>>,
 }
 
 impl<'a, 'b> GameData<'a, 'b> {
     /// Create new game data
     pub fn new(dispatcher: Dispatcher<'a, 'b>) -> Self {
-        GameData { dispatcher }
+        GameData {
+            dispatcher,
+            fixed_dispatcher: None,
+            groups: Vec::new(),
+        }
     }
 
-    /// Update game data
+    /// Create new game data, with a dispatcher run at the fixed update rate
+    pub fn with_fixed(
+        dispatcher: Dispatcher<'a, 'b>,
+        fixed_dispatcher: Dispatcher<'a, 'b>,
+    ) -> Self {
+        GameData {
+            dispatcher,
+            fixed_dispatcher: Some(fixed_dispatcher),
+            groups: Vec::new(),
+        }
+    }
+
+    /// Update game data, running the enabled system groups after the other systems
     pub fn update(&mut self, world: &World) {
         self.dispatcher.dispatch(&world.res);
+        for group in self.groups.iter_mut().filter(|group| group.enabled) {
+            group.dispatcher.dispatch(&world.res);
+        }
+    }
+
+    /// Enables or disables the system group with the given name, see
+    /// `GameDataBuilder::with_group`. Does nothing but log an error if there is no such group.
+    pub fn set_group_enabled(&mut self, name: &str, enabled: bool) {
+        match self.groups.iter_mut().find(|group| group.name == name) {
+            Some(group) => group.enabled = enabled,
+            None => error!("No system group named \"{}\"", name),
+        }
+    }
+
+    /// Enables the system group with the given name.
+    pub fn enable_group(&mut self, name: &str) {
+        self.set_group_enabled(name, true);
+    }
+
+    /// Disables the system group with the given name.
+    pub fn disable_group(&mut self, name: &str) {
+        self.set_group_enabled(name, false);
     }
+
+    /// Whether the system group with the given name exists and is enabled.
+    pub fn is_group_enabled(&self, name: &str) -> bool {
+        self.groups
+            .iter()
+            .any(|group| group.name == name && group.enabled)
+    }
+
+    /// Run the fixed rate systems, if any. Call this from `State::fixed_update`.
+    pub fn fixed_update(&mut self, world: &World) {
+        if let Some(ref mut dispatcher) = self.fixed_dispatcher {
+            dispatcher.dispatch(&world.res);
+        }
+    }
+}
+
+/// Builder for a system group
+struct SystemGroupBuilder<'a, 'b> {
+    name: String,
+    disp_builder: DispatcherBuilder<'a, 'b>,
+    systems: Vec<String>,
+    enabled: bool,
+}
+
+/// Runs after the given systems of the main dispatcher, which panics if one of them wasn't added
+struct DependencyCheck;
+
+impl<'a> System<'a> for DependencyCheck {
+    type SystemData = ();
+
+    fn run(&mut self, _: Self::SystemData) {}
 }
 
 /// Builder for default game data
 pub struct GameDataBuilder<'a, 'b> {
     disp_builder: DispatcherBuilder<'a, 'b>,
+    systems: Vec<String>,
+    fixed_disp_builder: Option<DispatcherBuilder<'a, 'b>>,
+    groups: Vec<SystemGroupBuilder<'a, 'b>>,
 }
 
 impl<'a, 'b> Default for GameDataBuilder<'a, 'b> {
@@ -46,6 +127,9 @@ impl<'a, 'b> GameDataBuilder<'a, 'b> {
     pub fn new() -> Self {
         GameDataBuilder {
             disp_builder: DispatcherBuilder::new(),
+            systems: Vec::new(),
+            fixed_disp_builder: None,
+            groups: Vec::new(),
         }
     }
 
@@ -142,6 +226,182 @@ impl<'a, 'b> GameDataBuilder<'a, 'b> {
         for<'c> S: System<'c> + Send + 'a,
     {
         self.disp_builder.add(system, name, dependencies);
+        if !name.is_empty() {
+            self.systems.push(name.to_owned());
+        }
+        self
+    }
+
+    /// Adds a given system to the systems run at the fixed update rate, see
+    /// `ApplicationBuilder::with_fixed_step_length`.
+    ///
+    /// The fixed rate systems only run when the states call `GameData::fixed_update` from
+    /// `State::fixed_update`. They have their own dependency graph: dependencies must name
+    /// other fixed rate systems.
+    ///
+    /// # Parameters
+    ///
+    /// - `system`: The system that is to be added to the fixed rate loop.
+    /// - `name`: A unique string to identify the system by.
+    /// - `dependencies`: A list of named fixed rate systems that _must_ have completed running
+    ///                 before this system is permitted to run.
+    ///
+    /// # Returns
+    ///
+    /// This function returns GameDataBuilder after it has modified it.
+    ///
+    /// # Panics
+    ///
+    /// Like `with`, if the name is used twice or if a dependency has not been added before.
+    ///
+    /// # Examples
+    ///
+    /// ~~~no_run
+    /// use amethyst::prelude::*;
+    /// use amethyst::ecs::prelude::System;
+    ///
+    /// struct NopSystem;
+    /// impl<'a> System<'a> for NopSystem {
+    ///     type SystemData = ();
+    ///     fn run(&mut self, _: Self::SystemData) {}
+    /// }
+    ///
+    /// GameDataBuilder::default()
+    ///     // The "physics" system runs once per fixed update.
+    ///     .with_fixed(NopSystem, "physics", &[]);
+    /// ~~~
+    pub fn with_fixed<S>(mut self, system: S, name: &str, dependencies: &[&str]) -> Self
+    where
+        for<'c> S: System<'c> + Send + 'a,
+    {
+        self.fixed_disp_builder
+            .get_or_insert_with(DispatcherBuilder::new)
+            .add(system, name, dependencies);
+        self
+    }
+
+    /// Declares a named group of systems, which states enable and disable with
+    /// `GameData::set_group_enabled`, e.g. from `on_start` and `on_stop`.
+    ///
+    /// The enabled groups run in the order they were declared, after the other systems. They
+    /// are set up in the same order when the game data is built.
+    ///
+    /// # Parameters
+    ///
+    /// - `name`: A unique string to identify the group by.
+    /// - `enabled`: Whether the group runs before a state enables it.
+    ///
+    /// # Returns
+    ///
+    /// This function returns GameDataBuilder after it has modified it.
+    ///
+    /// # Panics
+    ///
+    /// If two groups are declared with the same name.
+    ///
+    /// # Examples
+    ///
+    /// ~~~no_run
+    /// use amethyst::prelude::*;
+    /// use amethyst::ecs::prelude::System;
+    ///
+    /// struct NopSystem;
+    /// impl<'a> System<'a> for NopSystem {
+    ///     type SystemData = ();
+    ///     fn run(&mut self, _: Self::SystemData) {}
+    /// }
+    ///
+    /// GameDataBuilder::default()
+    ///     .with(NopSystem, "input", &[])
+    ///     // The "gameplay" group only runs while a state enables it.
+    ///     .with_group("gameplay", false)
+    ///     .with_in_group("gameplay", NopSystem, "movement", &["input"])
+    ///     .with_in_group("gameplay", NopSystem, "camera", &["movement"]);
+    /// ~~~
+    pub fn with_group(mut self, name: &str, enabled: bool) -> Self {
+        if self.groups.iter().any(|group| group.name == name) {
+            panic!("The system group \"{}\" is declared twice", name);
+        }
+        self.groups.push(SystemGroupBuilder {
+            name: name.to_owned(),
+            disp_builder: DispatcherBuilder::new(),
+            systems: Vec::new(),
+            enabled,
+        });
+        self
+    }
+
+    /// Adds a given system to a group declared with `with_group`.
+    ///
+    /// The dependencies can name systems of the same group, or systems which run before the group:
+    /// the systems added outside of any group, and the systems of the groups declared before it.
+    ///
+    /// # Parameters
+    ///
+    /// - `group`: The name of the group.
+    /// - `system`: The system that is to be added to the group.
+    /// - `name`: A unique string to identify the system by.
+    /// - `dependencies`: A list of named system that _must_ have completed running
+    ///                 before this system is permitted to run.
+    ///
+    /// # Returns
+    ///
+    /// This function returns GameDataBuilder after it has modified it.
+    ///
+    /// # Panics
+    ///
+    /// If the group was not declared, if a dependency is a system of a group declared after this
+    /// one or wasn't added at all, and in the cases `with` panics.
+    pub fn with_in_group<S>(
+        mut self,
+        group: &str,
+        system: S,
+        name: &str,
+        dependencies: &[&str],
+    ) -> Self
+    where
+        for<'c> S: System<'c> + Send + 'a,
+    {
+        let index = match self.groups.iter().position(|g| g.name == group) {
+            Some(index) => index,
+            None => panic!("No system group named \"{}\"", group),
+        };
+        let mut unknown = Vec::new();
+        for dependency in dependencies {
+            let in_group = |g: &SystemGroupBuilder| g.systems.iter().any(|s| s == *dependency);
+            if let Some(later) = self.groups[index + 1..]
+                .iter()
+                .find(|g| in_group(g))
+            {
+                panic!(
+                    "The system \"{}\" of group \"{}\" depends on \"{}\" of group \"{}\", \
+                     which runs after it",
+                    name, group, dependency, later.name
+                );
+            }
+            if !self.groups[..index + 1].iter().any(|g| in_group(g))
+                && !self.systems.iter().any(|s| s == *dependency)
+            {
+                unknown.push(*dependency);
+            }
+        }
+        if !unknown.is_empty() {
+            // The names of the systems added by bundles aren't known here, the main dispatcher
+            // panics if it doesn't have them either.
+            self.disp_builder.add(DependencyCheck, "", &unknown);
+            self.systems.extend(unknown.iter().map(|s| s.to_string()));
+        }
+        {
+            let group = &mut self.groups[index];
+            // The systems out of the group already ran when the group is dispatched.
+            let dependencies = dependencies
+                .iter()
+                .cloned()
+                .filter(|dependency| group.systems.iter().any(|s| s == *dependency))
+                .collect::<Vec<_>>();
+            group.disp_builder.add(system, name, &dependencies);
+            group.systems.push(name.to_owned());
+        }
         self
     }
 
@@ -257,17 +517,44 @@ impl<'a, 'b> GameDataBuilder<'a, 'b> {
     }
 }
 
+/// Builds a dispatcher using the thread pool of the world, and sets it up
+fn build_dispatcher<'a, 'b>(
+    disp_builder: DispatcherBuilder<'a, 'b>,
+    world: &mut World,
+) -> Dispatcher<'a, 'b> {
+    #[cfg(not(no_threading))]
+    let pool = world.read_resource::<Arc<ThreadPool>>().clone();
+
+    #[cfg(not(no_threading))]
+    let mut dispatcher = disp_builder.with_pool(pool).build();
+    #[cfg(no_threading)]
+    let mut dispatcher = disp_builder.build();
+    dispatcher.setup(&mut world.res);
+    dispatcher
+}
+
 impl<'a, 'b> DataInit<GameData<'a, 'b>> for GameDataBuilder<'a, 'b> {
     fn build(self, world: &mut World) -> GameData<'a, 'b> {
-        #[cfg(not(no_threading))]
-        let pool = world.read_resource::<Arc<ThreadPool>>().clone();
-
-        #[cfg(not(no_threading))]
-        let mut dispatcher = self.disp_builder.with_pool(pool).build();
-        #[cfg(no_threading)]
-        let mut dispatcher = self.disp_builder.build();
-        dispatcher.setup(&mut world.res);
-        GameData::new(dispatcher)
+        let dispatcher = build_dispatcher(self.disp_builder, world);
+        let groups = self
+            .groups
+            .into_iter()
+            .map(|group| SystemGroup {
+                name: group.name,
+                dispatcher: build_dispatcher(group.disp_builder, world),
+                enabled: group.enabled,
+            })
+            .collect();
+
+        let mut game_data = match self.fixed_disp_builder {
+            Some(fixed_disp_builder) => {
+                let fixed_dispatcher = build_dispatcher(fixed_disp_builder, world);
+                GameData::with_fixed(dispatcher, fixed_dispatcher)
+            }
+            None => GameData::new(dispatcher),
+        };
+        game_data.groups = groups;
+        game_data
     }
 }
 
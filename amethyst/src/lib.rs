@@ -76,12 +76,18 @@ extern crate fern;
 extern crate log;
 extern crate rayon;
 extern crate rustc_version_runtime;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
 
 pub use self::app::{Application, ApplicationBuilder};
 pub use self::error::{Error, Result};
+pub use self::error_policy::{ErrorPolicy, ReportedError, Severity};
 pub use self::game_data::{DataInit, GameData, GameDataBuilder};
-pub use self::logger::{start_logger, LevelFilter as LogLevelFilter, LoggerConfig};
-pub use self::state::{State, StateData, StateMachine, Trans};
+pub use self::logger::{
+    start_logger, LevelFilter as LogLevelFilter, LogBuffer, LogLine, LoggerConfig,
+};
+pub use self::state::{State, StateData, StateEvent, StateMachine, Trans, TransQueue};
 pub use core::shred;
 pub use core::shrev;
 pub use core::specs as ecs;
@@ -90,6 +96,7 @@ pub mod prelude;
 
 mod app;
 mod error;
+mod error_policy;
 mod game_data;
 mod logger;
 mod state;
@@ -1,15 +1,44 @@
-use std::io;
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 
 use fern;
+use log::{Level, Log, Metadata, Record};
 pub use log::LevelFilter;
 
 /// Logger configuration object.
-#[derive(Clone, Copy)]
+///
+/// It can be loaded from a RON file with `amethyst::config::Config`, e.g.
+///
+/// ```ron
+/// (
+///     level_filter: Info,
+///     module_levels: [("gfx_device_gl", Warn)],
+///     log_file: Some("game.log"),
+///     max_log_file_size: Some(1048576),
+/// )
+/// ```
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
 pub struct LoggerConfig {
     /// Whether to use color output when logging to the terminal or not.
     pub use_colors: bool,
     /// Sets the overarching level filter for the logger.
     pub level_filter: LevelFilter,
+    /// Level filters of modules and their submodules, overriding `level_filter`.
+    pub module_levels: Vec<(String, LevelFilter)>,
+    /// File the logs are written to as well as the terminal.
+    pub log_file: Option<PathBuf>,
+    /// Size in bytes above which the log file is rotated: `game.log` is renamed to `game.log.1`,
+    /// `game.log.1` to `game.log.2` and so on. The file grows forever if `None`.
+    pub max_log_file_size: Option<u64>,
+    /// Number of rotated log files kept besides the current one.
+    pub rotated_log_files: usize,
+    /// Number of lines kept by the `LogBuffer` returned by `start_logger`.
+    pub buffer_lines: usize,
 }
 
 impl Default for LoggerConfig {
@@ -17,39 +46,291 @@ impl Default for LoggerConfig {
         LoggerConfig {
             use_colors: true,
             level_filter: LevelFilter::Debug,
+            module_levels: Vec::new(),
+            log_file: None,
+            max_log_file_size: None,
+            rotated_log_files: 3,
+            buffer_lines: 256,
+        }
+    }
+}
+
+/// A line logged while the `LogBuffer` was recording.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LogLine {
+    /// Level of the line.
+    pub level: Level,
+    /// Module which logged the line.
+    pub target: String,
+    /// The logged message.
+    pub message: String,
+}
+
+/// The last lines logged, e.g. for an in-game console or a crash report.
+///
+/// `start_logger` returns the buffer of the engine logger, which can be added to the `World`
+/// with `ApplicationBuilder::with_resource`. Clones share the same lines.
+#[derive(Clone, Debug, Default)]
+pub struct LogBuffer {
+    lines: Arc<Mutex<VecDeque<LogLine>>>,
+    capacity: usize,
+}
+
+impl LogBuffer {
+    /// Creates a buffer keeping the last `capacity` lines.
+    pub fn new(capacity: usize) -> Self {
+        LogBuffer {
+            lines: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+            capacity,
+        }
+    }
+
+    /// Gets the maximum number of lines kept.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Gets the lines kept, from the oldest to the newest.
+    pub fn lines(&self) -> Vec<LogLine> {
+        self.lines
+            .lock()
+            .map(|lines| lines.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Removes all the lines kept.
+    pub fn clear(&self) {
+        if let Ok(mut lines) = self.lines.lock() {
+            lines.clear();
+        }
+    }
+
+    /// Adds a line, dropping the oldest one if the buffer is full.
+    pub fn push(&self, line: LogLine) {
+        if self.capacity == 0 {
+            return;
+        }
+        if let Ok(mut lines) = self.lines.lock() {
+            if lines.len() == self.capacity {
+                lines.pop_front();
+            }
+            lines.push_back(line);
+        }
+    }
+}
+
+impl Log for LogBuffer {
+    fn enabled(&self, _: &Metadata) -> bool {
+        self.capacity > 0
+    }
+
+    fn log(&self, record: &Record) {
+        self.push(LogLine {
+            level: record.level(),
+            target: record.target().to_string(),
+            message: record.args().to_string(),
+        });
+    }
+
+    fn flush(&self) {}
+}
+
+/// A log file moved to a numbered copy and emptied once it gets too big.
+///
+/// The writes are buffered until their line ends, so that a line is never split between two
+/// files.
+struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    line: Vec<u8>,
+    size: u64,
+    max_size: Option<u64>,
+    rotated_files: usize,
+}
+
+impl RotatingFile {
+    fn open(path: &Path, max_size: Option<u64>, rotated_files: usize) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let size = file.metadata()?.len();
+        Ok(RotatingFile {
+            path: path.to_owned(),
+            file,
+            line: Vec::new(),
+            size,
+            max_size,
+            rotated_files,
+        })
+    }
+
+    fn rotated_path(&self, index: usize) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{}", index));
+        path.into()
+    }
+
+    // The current file is copied and truncated rather than renamed, since it can't be renamed
+    // while it is open on some platforms.
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        if self.rotated_files > 0 {
+            for index in (1..self.rotated_files).rev() {
+                let from = self.rotated_path(index);
+                if from.exists() {
+                    fs::rename(&from, self.rotated_path(index + 1))?;
+                }
+            }
+            fs::copy(&self.path, self.rotated_path(1))?;
+        }
+        self.file.set_len(0)?;
+        self.size = 0;
+        Ok(())
+    }
+
+    /// Writes whole lines, rotating the file first if they don't fit in it.
+    fn write_lines(&mut self, lines: &[u8]) -> io::Result<()> {
+        if let Some(max_size) = self.max_size {
+            if self.size > 0 && self.size + lines.len() as u64 > max_size {
+                self.rotate()?;
+            }
+        }
+        self.file.write_all(lines)?;
+        self.size += lines.len() as u64;
+        Ok(())
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.line.extend_from_slice(buf);
+        if let Some(end) = self.line.iter().rposition(|&byte| byte == b'\n') {
+            let lines = self.line.drain(..end + 1).collect::<Vec<_>>();
+            self.write_lines(&lines)?;
         }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if !self.line.is_empty() {
+            let line = mem::replace(&mut self.line, Vec::new());
+            self.write_lines(&line)?;
+        }
+        self.file.flush()
     }
 }
 
-/// Starts a basic logger outputting to stdout with color on supported platforms.
+/// Starts a basic logger outputting to stdout with color on supported platforms, to the log
+/// file of the config if there is one, and to the returned `LogBuffer`.
+///
+/// If the log file can't be opened, the logger starts without it and logs the error.
 ///
 /// If you do not intend on using the logger builtin to Amethyst, it's highly recommended you
 /// initialise your own.
-pub fn start_logger(config: LoggerConfig) {
+pub fn start_logger(config: LoggerConfig) -> LogBuffer {
     let color_config = fern::colors::ColoredLevelConfig::new();
+    let use_colors = config.use_colors;
+    let buffer = LogBuffer::new(config.buffer_lines);
 
-    fern::Dispatch::new()
-        .format(move |out, message, record| {
-            out.finish(format_args!(
-                "{color}[{level}][{target}] {message}{color_reset}",
-                color = if config.use_colors {
-                    format!(
-                        "\x1B[{}m",
-                        color_config.get_color(&record.level()).to_fg_str()
-                    )
-                } else {
-                    String::from("")
-                },
-                level = record.level(),
-                target = record.target(),
-                message = message,
-                color_reset = if config.use_colors { "\x1B[0m" } else { "" }
-            ))
-        })
-        .level(config.level_filter)
-        .chain(io::stdout())
-        .apply()
-        .unwrap_or_else(|_| {
-            debug!("Global logger already set, default Amethyst logger will not be used")
-        });
+    let mut dispatch = fern::Dispatch::new().level(config.level_filter);
+    for (module, level) in config.module_levels {
+        dispatch = dispatch.level_for(module, level);
+    }
+    dispatch = dispatch.chain(
+        fern::Dispatch::new()
+            .format(move |out, message, record| {
+                out.finish(format_args!(
+                    "{color}[{level}][{target}] {message}{color_reset}",
+                    color = if use_colors {
+                        format!(
+                            "\x1B[{}m",
+                            color_config.get_color(&record.level()).to_fg_str()
+                        )
+                    } else {
+                        String::from("")
+                    },
+                    level = record.level(),
+                    target = record.target(),
+                    message = message,
+                    color_reset = if use_colors { "\x1B[0m" } else { "" }
+                ))
+            })
+            .chain(io::stdout()),
+    );
+    let mut file_error = None;
+    if let Some(ref path) = config.log_file {
+        match RotatingFile::open(path, config.max_log_file_size, config.rotated_log_files) {
+            Ok(file) => {
+                dispatch = dispatch.chain(
+                    fern::Dispatch::new()
+                        .format(|out, message, record| {
+                            out.finish(format_args!(
+                                "[{}][{}] {}",
+                                record.level(),
+                                record.target(),
+                                message
+                            ))
+                        })
+                        .chain(Box::new(file) as Box<Write + Send>),
+                );
+            }
+            Err(e) => file_error = Some((path, e)),
+        }
+    }
+    if buffer.capacity() > 0 {
+        dispatch = dispatch.chain(Box::new(buffer.clone()) as Box<Log>);
+    }
+
+    dispatch.apply().unwrap_or_else(|_| {
+        debug!("Global logger already set, default Amethyst logger will not be used")
+    });
+    if let Some((path, e)) = file_error {
+        error!("Failed to open the log file {:?}, logging to stdout only: {}", path, e);
+    }
+    buffer
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(message: &str) -> LogLine {
+        LogLine {
+            level: Level::Info,
+            target: "test".to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn buffer_keeps_last_lines() {
+        let buffer = LogBuffer::new(2);
+        let shared = buffer.clone();
+        buffer.push(line("a"));
+        buffer.push(line("b"));
+        buffer.push(line("c"));
+
+        assert_eq!(shared.lines(), vec![line("b"), line("c")]);
+        shared.clear();
+        assert!(buffer.lines().is_empty());
+    }
+
+    #[test]
+    fn file_rotates() {
+        let dir = ::std::env::temp_dir().join("amethyst_logger_rotation");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("test.log");
+
+        let mut file = RotatingFile::open(&path, Some(8), 1).unwrap();
+        file.write_all(b"first\n").unwrap();
+        file.write_all(b"second\n").unwrap();
+        // A line written in parts goes to a single file.
+        file.write_all(b"th").unwrap();
+        file.write_all(b"ird\n").unwrap();
+        file.flush().unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "third\n");
+        assert_eq!(fs::read_to_string(dir.join("test.log.1")).unwrap(), "second\n");
+        assert!(!dir.join("test.log.2").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
 }
@@ -6,4 +6,4 @@ pub use ecs::prelude::{Builder, World};
 pub use game_data::{DataInit, GameData, GameDataBuilder};
 //pub use renderer::input::*;
 
-pub use state::{State, StateData, Trans};
+pub use state::{State, StateData, StateEvent, Trans, TransQueue};
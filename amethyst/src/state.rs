@@ -1,7 +1,10 @@
 //! Utilities for game state management.
 
-use ecs::prelude::World;
-use renderer::Event;
+use core::shrev::EventChannel;
+use ecs::prelude::{Entity, Join, World};
+use error_policy::{ReportedError, Severity};
+use renderer::{Event, Hidden};
+use utils::state_scoped::{OnPause, StateScoped};
 
 /// State data encapsulates the data sent to all state functions from the application main loop.
 pub struct StateData<'a, T>
@@ -37,6 +40,55 @@ pub enum Trans<T> {
     Switch(Box<State<T>>),
     /// Stop and remove all states and shut down the engine.
     Quit,
+    /// Perform the transitions one after the other.
+    Sequence(Vec<Trans<T>>),
+    /// Stop and remove all states, then start the given one.
+    Replace(Box<State<T>>),
+    /// Remove states until the predicate is true for the active one, and resume it. All the
+    /// states are removed, which shuts down the engine, if it is true for none of them.
+    PopTo(Box<Fn(&State<T>) -> bool>),
+}
+
+/// Change of the state stack, published by the `StateMachine` in an `EventChannel<StateEvent>`.
+/// Each change gives the number of states in the stack after it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StateEvent {
+    /// A state was started on top of the stack, pausing the previous one if any.
+    Push(usize),
+    /// The active state was stopped and removed.
+    Pop(usize),
+    /// The active state was stopped and replaced by a new one.
+    Switch(usize),
+}
+
+/// Transitions requested from outside of the states, e.g. by systems. The `StateMachine`
+/// performs them after the `update` of the active state, in the order they were pushed.
+///
+/// The transitions are created when they are performed, since states can't be shared between
+/// threads.
+pub struct TransQueue<T> {
+    queue: Vec<Box<Fn() -> Trans<T> + Send + Sync>>,
+}
+
+impl<T> TransQueue<T> {
+    /// Requests the transition returned by `trans`.
+    pub fn push<F>(&mut self, trans: F)
+    where
+        F: Fn() -> Trans<T> + Send + Sync + 'static,
+    {
+        self.queue.push(Box::new(trans));
+    }
+
+    /// Creates the requested transitions, emptying the queue.
+    pub fn drain(&mut self) -> Vec<Trans<T>> {
+        self.queue.drain(..).map(|trans| trans()).collect()
+    }
+}
+
+impl<T> Default for TransQueue<T> {
+    fn default() -> Self {
+        TransQueue { queue: Vec::new() }
+    }
 }
 
 /// A trait which defines game states that can be used by the state machine.
@@ -58,6 +110,17 @@ pub trait State<T> {
         Trans::None
     }
 
+    /// Executed at the end of the frame for every error reported through the `Errors` resource,
+    /// before the `ErrorPolicy` of the application stops it on fatal errors.
+    fn handle_error(
+        &mut self,
+        _data: StateData<T>,
+        _error: &ReportedError,
+        _severity: Severity,
+    ) -> Trans<T> {
+        Trans::None
+    }
+
     /// Executed repeatedly at stable, predictable intervals (1/60th of a second
     /// by default).
     fn fixed_update(&mut self, _data: StateData<T>) -> Trans<T> {
@@ -68,9 +131,76 @@ pub trait State<T> {
     fn update(&mut self, _data: StateData<T>) -> Trans<T> {
         Trans::None
     }
+
+    /// Name of the state, e.g. for the predicates of `Trans::PopTo`.
+    fn name(&self) -> &str {
+        ""
+    }
+}
+
+/// Gives the `StateScoped` entities created since the last call to the state at `depth`.
+fn claim_scoped(world: &World, depth: usize) {
+    for scoped in (&mut world.write_storage::<StateScoped>()).join() {
+        scoped.claim(depth);
+    }
+}
+
+/// Collects the entities owned by the state at `depth`.
+fn scoped_entities(world: &World, depth: usize) -> Vec<Entity> {
+    (&*world.entities(), &world.read_storage::<StateScoped>())
+        .join()
+        .filter(|(_, scoped)| scoped.depth() == Some(depth))
+        .map(|(entity, _)| entity)
+        .collect()
+}
+
+/// Publishes a change of the state stack.
+fn send_event(world: &World, event: StateEvent) {
+    world
+        .write_resource::<EventChannel<StateEvent>>()
+        .single_write(event);
+}
+
+/// Deletes the entities owned by the stopped state at `depth`.
+fn delete_scoped(world: &mut World, depth: usize) {
+    claim_scoped(world, depth);
+    let entities = scoped_entities(world, depth);
+    if let Err(err) = world.delete_entities(&entities) {
+        error!("Failed to delete the entities of a stopped state: {:?}", err);
+    }
+}
+
+/// Collects the entities owned by the state at `depth` which are hidden while it is paused.
+fn hidden_on_pause(world: &World, depth: usize) -> Vec<Entity> {
+    (&*world.entities(), &world.read_storage::<StateScoped>())
+        .join()
+        .filter(|(_, scoped)| scoped.depth() == Some(depth) && scoped.on_pause == OnPause::Hide)
+        .map(|(entity, _)| entity)
+        .collect()
+}
+
+/// Hides the entities owned by the paused state at `depth` which ask for it.
+fn hide_scoped(world: &World, depth: usize) {
+    let mut hidden = world.write_storage::<Hidden>();
+    for entity in hidden_on_pause(world, depth) {
+        if let Err(err) = hidden.insert(entity, Hidden) {
+            error!("Failed to hide the entity of a paused state: {:?}", err);
+        }
+    }
+}
+
+/// Shows again the entities hidden by `hide_scoped` when the state at `depth` resumes.
+fn show_scoped(world: &World, depth: usize) {
+    let mut hidden = world.write_storage::<Hidden>();
+    for entity in hidden_on_pause(world, depth) {
+        hidden.remove(entity);
+    }
 }
 
 /// A simple stack-based state machine (pushdown automaton).
+///
+/// Entities with the `StateScoped` component belong to the state that created them: they are
+/// deleted when it stops, and hidden while it is paused if they ask for it.
 #[derive(Derivative)]
 #[derivative(Debug)]
 pub struct StateMachine<'a, T> {
@@ -93,18 +223,39 @@ impl<'a, T> StateMachine<'a, T> {
         self.running
     }
 
-    /// Initializes the state machine.
+    /// Initializes the state machine, adding the `TransQueue<T>` resource if it's missing. Since
+    /// it is a resource, the game data must be `'static`.
     ///
     /// # Panics
     /// Panics if no states are present in the stack.
-    pub fn start(&mut self, data: StateData<T>) {
+    pub fn start(&mut self, data: StateData<T>)
+    where
+        T: 'static,
+    {
         if !self.running {
+            let StateData { world, data } = data;
+            world.register::<StateScoped>();
+            world.register::<Hidden>();
+            if !world.res.has_value::<EventChannel<StateEvent>>() {
+                world.add_resource(EventChannel::<StateEvent>::new());
+            }
+            if !world.res.has_value::<TransQueue<T>>() {
+                world.add_resource(TransQueue::<T>::default());
+            }
+            let depth = self.depth();
             let state = self.state_stack.last_mut().unwrap();
-            state.on_start(data);
+            state.on_start(StateData { world, data });
+            claim_scoped(world, depth);
+            send_event(world, StateEvent::Push(depth + 1));
             self.running = true;
         }
     }
 
+    /// Depth in the stack of the active state.
+    fn depth(&self) -> usize {
+        self.state_stack.len().saturating_sub(1)
+    }
+
     /// Passes a single event to the active state to handle.
     pub fn handle_event(&mut self, data: StateData<T>, event: Event) {
         let StateData { world, data } = data;
@@ -113,6 +264,21 @@ impl<'a, T> StateMachine<'a, T> {
                 Some(state) => state.handle_event(StateData { world, data }, event),
                 None => Trans::None,
             };
+            claim_scoped(world, self.depth());
+
+            self.transition(trans, StateData { world, data });
+        }
+    }
+
+    /// Passes a reported error to the active state to handle.
+    pub fn handle_error(&mut self, data: StateData<T>, error: &ReportedError, severity: Severity) {
+        let StateData { world, data } = data;
+        if self.running {
+            let trans = match self.state_stack.last_mut() {
+                Some(state) => state.handle_error(StateData { world, data }, error, severity),
+                None => Trans::None,
+            };
+            claim_scoped(world, self.depth());
 
             self.transition(trans, StateData { world, data });
         }
@@ -126,21 +292,32 @@ impl<'a, T> StateMachine<'a, T> {
                 Some(state) => state.fixed_update(StateData { world, data }),
                 None => Trans::None,
             };
+            claim_scoped(world, self.depth());
 
             self.transition(trans, StateData { world, data });
         }
     }
 
-    /// Updates the currently active state immediately.
-    pub fn update(&mut self, data: StateData<T>) {
+    /// Updates the currently active state immediately, then performs the transitions
+    /// requested through the `TransQueue`, which needs `'static` game data.
+    pub fn update(&mut self, data: StateData<T>)
+    where
+        T: 'static,
+    {
         let StateData { world, data } = data;
         if self.running {
             let trans = match self.state_stack.last_mut() {
                 Some(state) => state.update(StateData { world, data }),
                 None => Trans::None,
             };
+            claim_scoped(world, self.depth());
 
             self.transition(trans, StateData { world, data });
+
+            let queued = world.write_resource::<TransQueue<T>>().drain();
+            for trans in queued {
+                self.transition(trans, StateData { world, data });
+            }
         }
     }
 
@@ -154,6 +331,14 @@ impl<'a, T> StateMachine<'a, T> {
                 Trans::Push(state) => self.push(state, data),
                 Trans::Switch(state) => self.switch(state, data),
                 Trans::Quit => self.stop(data),
+                Trans::Sequence(requests) => {
+                    let StateData { world, data } = data;
+                    for request in requests {
+                        self.transition(request, StateData { world, data });
+                    }
+                }
+                Trans::Replace(state) => self.replace(state, data),
+                Trans::PopTo(predicate) => self.pop_to(&*predicate, data),
             }
         }
     }
@@ -162,13 +347,17 @@ impl<'a, T> StateMachine<'a, T> {
     fn switch(&mut self, state: Box<State<T>>, data: StateData<T>) {
         if self.running {
             let StateData { world, data } = data;
+            let depth = self.depth();
             if let Some(mut state) = self.state_stack.pop() {
                 state.on_stop(StateData { world, data });
+                delete_scoped(world, depth);
             }
 
             self.state_stack.push(state);
             let state = self.state_stack.last_mut().unwrap();
             state.on_start(StateData { world, data });
+            claim_scoped(world, depth);
+            send_event(world, StateEvent::Switch(depth + 1));
         }
     }
 
@@ -176,13 +365,43 @@ impl<'a, T> StateMachine<'a, T> {
     fn push(&mut self, state: Box<State<T>>, data: StateData<T>) {
         if self.running {
             let StateData { world, data } = data;
+            let depth = self.depth();
             if let Some(state) = self.state_stack.last_mut() {
                 state.on_pause(StateData { world, data });
+                claim_scoped(world, depth);
+                hide_scoped(world, depth);
             }
 
             self.state_stack.push(state);
+            let depth = self.depth();
             let state = self.state_stack.last_mut().unwrap();
             state.on_start(StateData { world, data });
+            claim_scoped(world, depth);
+            send_event(world, StateEvent::Push(depth + 1));
+        }
+    }
+
+    /// Stops and removes the active state, without resuming the next one.
+    fn stop_active(&mut self, data: StateData<T>) {
+        let StateData { world, data } = data;
+        if let Some(mut state) = self.state_stack.pop() {
+            let depth = self.state_stack.len();
+            state.on_stop(StateData { world, data });
+            delete_scoped(world, depth);
+            send_event(world, StateEvent::Pop(depth));
+        }
+    }
+
+    /// Un-pauses the active state, or stops the state machine if there is none.
+    fn resume_active(&mut self, data: StateData<T>) {
+        let StateData { world, data } = data;
+        let depth = self.depth();
+        if let Some(state) = self.state_stack.last_mut() {
+            show_scoped(world, depth);
+            state.on_resume(StateData { world, data });
+            claim_scoped(world, depth);
+        } else {
+            self.running = false;
         }
     }
 
@@ -191,15 +410,41 @@ impl<'a, T> StateMachine<'a, T> {
     fn pop(&mut self, data: StateData<T>) {
         if self.running {
             let StateData { world, data } = data;
-            if let Some(mut state) = self.state_stack.pop() {
-                state.on_stop(StateData { world, data });
+            self.stop_active(StateData { world, data });
+            self.resume_active(StateData { world, data });
+        }
+    }
+
+    /// Stops and removes states until the predicate is true for the active one, then un-pauses
+    /// it. The state machine stops if the predicate is true for none of them.
+    fn pop_to(&mut self, predicate: &Fn(&State<T>) -> bool, data: StateData<T>) {
+        if self.running {
+            let StateData { world, data } = data;
+            while self
+                .state_stack
+                .last()
+                .map(|state| !predicate(&**state))
+                .unwrap_or(false)
+            {
+                self.stop_active(StateData { world, data });
             }
+            self.resume_active(StateData { world, data });
+        }
+    }
 
-            if let Some(state) = self.state_stack.last_mut() {
-                state.on_resume(StateData { world, data });
-            } else {
-                self.running = false;
+    /// Stops and removes all the states, then starts the given one.
+    fn replace(&mut self, state: Box<State<T>>, data: StateData<T>) {
+        if self.running {
+            let StateData { world, data } = data;
+            while !self.state_stack.is_empty() {
+                self.stop_active(StateData { world, data });
             }
+
+            self.state_stack.push(state);
+            let state = self.state_stack.last_mut().unwrap();
+            state.on_start(StateData { world, data });
+            claim_scoped(world, 0);
+            send_event(world, StateEvent::Push(1));
         }
     }
 
@@ -207,8 +452,8 @@ impl<'a, T> StateMachine<'a, T> {
     pub(crate) fn stop(&mut self, data: StateData<T>) {
         if self.running {
             let StateData { world, data } = data;
-            while let Some(mut state) = self.state_stack.pop() {
-                state.on_stop(StateData { world, data });
+            while !self.state_stack.is_empty() {
+                self.stop_active(StateData { world, data });
             }
 
             self.running = false;
@@ -219,6 +464,8 @@ impl<'a, T> StateMachine<'a, T> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use core::shrev::ReaderId;
+    use ecs::prelude::Builder;
 
     struct State1(u8);
     struct State2;
@@ -257,4 +504,162 @@ mod tests {
         sm.update(StateData::new(&mut world, &mut ()));
         assert!(!sm.is_running());
     }
+
+    struct Menu;
+    struct Popup;
+
+    impl State<()> for Menu {
+        fn on_start(&mut self, data: StateData<()>) {
+            data.world
+                .create_entity()
+                .with(StateScoped::new())
+                .build();
+        }
+
+        fn update(&mut self, _: StateData<()>) -> Trans<()> {
+            Trans::Push(Box::new(Popup))
+        }
+    }
+
+    impl State<()> for Popup {
+        fn on_start(&mut self, data: StateData<()>) {
+            data.world.create_entity().with(StateScoped::new()).build();
+        }
+
+        fn update(&mut self, _: StateData<()>) -> Trans<()> {
+            Trans::Pop
+        }
+    }
+
+    /// A menu whose entity is hidden while the popup is over it.
+    struct HiddenMenu;
+
+    impl State<()> for HiddenMenu {
+        fn on_start(&mut self, data: StateData<()>) {
+            data.world
+                .create_entity()
+                .with(StateScoped::hidden_on_pause())
+                .build();
+        }
+
+        fn update(&mut self, _: StateData<()>) -> Trans<()> {
+            Trans::Push(Box::new(Popup))
+        }
+    }
+
+    /// A state known by its name.
+    struct Named(&'static str);
+
+    impl State<()> for Named {
+        fn name(&self) -> &str {
+            self.0
+        }
+    }
+
+    fn events(world: &World, reader: &mut ReaderId<StateEvent>) -> Vec<StateEvent> {
+        world
+            .read_resource::<EventChannel<StateEvent>>()
+            .read(reader)
+            .cloned()
+            .collect()
+    }
+
+    #[test]
+    fn sequence_pop_to_replace() {
+        let mut world = World::new();
+        world.add_resource(EventChannel::<StateEvent>::new());
+        let mut reader = world
+            .write_resource::<EventChannel<StateEvent>>()
+            .register_reader();
+
+        let mut sm = StateMachine::new(Named("menu"));
+        sm.start(StateData::new(&mut world, &mut ()));
+        sm.transition(
+            Trans::Sequence(vec![
+                Trans::Push(Box::new(Named("game"))),
+                Trans::Push(Box::new(Named("pause"))),
+            ]),
+            StateData::new(&mut world, &mut ()),
+        );
+        assert_eq!(sm.state_stack.len(), 3);
+
+        sm.transition(
+            Trans::PopTo(Box::new(|state| state.name() == "menu")),
+            StateData::new(&mut world, &mut ()),
+        );
+        assert_eq!(sm.state_stack.len(), 1);
+        assert_eq!(
+            events(&world, &mut reader),
+            vec![
+                StateEvent::Push(1),
+                StateEvent::Push(2),
+                StateEvent::Push(3),
+                StateEvent::Pop(2),
+                StateEvent::Pop(1),
+            ]
+        );
+
+        // Systems request transitions through the queue.
+        world
+            .write_resource::<TransQueue<()>>()
+            .push(|| Trans::Replace(Box::new(Named("credits"))));
+        sm.update(StateData::new(&mut world, &mut ()));
+        assert_eq!(sm.state_stack.len(), 1);
+        assert_eq!(sm.state_stack[0].name(), "credits");
+        assert_eq!(
+            events(&world, &mut reader),
+            vec![StateEvent::Pop(0), StateEvent::Push(1)]
+        );
+        assert!(sm.is_running());
+    }
+
+    fn scoped_count(world: &World) -> usize {
+        (&world.read_storage::<StateScoped>()).join().count()
+    }
+
+    #[test]
+    fn state_scoped_entities() {
+        let mut world = World::new();
+
+        let mut sm = StateMachine::new(Menu);
+        sm.start(StateData::new(&mut world, &mut ()));
+        assert_eq!(scoped_count(&world), 1);
+
+        // The popup is pushed over the menu, which keeps its entity.
+        sm.update(StateData::new(&mut world, &mut ()));
+        assert_eq!(scoped_count(&world), 2);
+
+        // Popping the popup deletes its entity only.
+        sm.update(StateData::new(&mut world, &mut ()));
+        world.maintain();
+        assert_eq!(scoped_count(&world), 1);
+
+        sm.stop(StateData::new(&mut world, &mut ()));
+        world.maintain();
+        assert_eq!(scoped_count(&world), 0);
+    }
+
+    fn hidden_count(world: &World) -> usize {
+        (&world.read_storage::<Hidden>()).join().count()
+    }
+
+    #[test]
+    fn paused_entities_are_hidden() {
+        let mut world = World::new();
+
+        let mut sm = StateMachine::new(HiddenMenu);
+        sm.start(StateData::new(&mut world, &mut ()));
+        assert_eq!(hidden_count(&world), 0);
+
+        // The popup is pushed over the menu, whose entity is hidden.
+        sm.update(StateData::new(&mut world, &mut ()));
+        assert_eq!(scoped_count(&world), 2);
+        assert_eq!(hidden_count(&world), 1);
+
+        // Popping the popup shows the menu's entity again.
+        sm.update(StateData::new(&mut world, &mut ()));
+        world.maintain();
+        assert_eq!(scoped_count(&world), 1);
+        assert_eq!(hidden_count(&world), 0);
+    }
 }
@@ -0,0 +1,58 @@
+language-name = English
+
+score-title-completed = Congratulations!
+score-title-failed = Oh no!
+score-label = Score
+achievement-unlocked = Achievement unlocked
+grade-comment-s = Awesome!
+grade-comment-a = Not bad!
+grade-comment-b = Okay!
+grade-comment-c = That's a start
+
+controls-left = Left
+controls-right = Right
+controls-up = Up
+controls-down = Down
+
+mods-title = Asset packs (later ones override earlier ones)
+mods-status-default = default
+mods-status-on = on
+mods-status-off = off
+mods-by = by
+mods-up = Up
+mods-restart = Restart the game to apply the display, controls and settings of the packs
+
+settings-title = Settings
+settings-music-volume = Music volume
+settings-sfx-volume = Sound effects volume
+settings-fullscreen = Fullscreen
+settings-vsync = VSync (needs restart)
+settings-scroll-speed = Scroll speed
+settings-note-offset = Note offset
+settings-ghost = Ghost of the best run
+settings-on = On
+settings-off = Off
+
+modifier-mirror = Mirror
+modifier-random = Random
+modifier-hidden = Hidden
+modifier-sudden-death = Sudden death
+modifier-speed = Speed
+modifier-versus = Versus
+
+versus-player = Player
+versus-wins = wins!
+versus-draw = Draw!
+versus-max-combo = max combo
+
+courses-title = Courses
+course-song = Song
+course-health = Health
+course-score = Score
+course-total = Total
+course-completed = Course completed!
+course-failed = Course failed
+course-high-scores = High scores
+course-continue = Press Enter to continue
+
+map-difficulty = Difficulty
@@ -0,0 +1,58 @@
+language-name = Français
+
+score-title-completed = Félicitations !
+score-title-failed = Oh non !
+score-label = Score
+achievement-unlocked = Succès débloqué
+grade-comment-s = Génial !
+grade-comment-a = Pas mal !
+grade-comment-b = Correct !
+grade-comment-c = C'est un début
+
+controls-left = Gauche
+controls-right = Droite
+controls-up = Haut
+controls-down = Bas
+
+mods-title = Packs de ressources (les derniers remplacent les premiers)
+mods-status-default = défaut
+mods-status-on = activé
+mods-status-off = désactivé
+mods-by = par
+mods-up = Monter
+mods-restart = Redémarrez le jeu pour appliquer l'affichage, les contrôles et les options des packs
+
+settings-title = Options
+settings-music-volume = Volume de la musique
+settings-sfx-volume = Volume des effets
+settings-fullscreen = Plein écran
+settings-vsync = VSync (au redémarrage)
+settings-scroll-speed = Vitesse de défilement
+settings-note-offset = Décalage des notes
+settings-ghost = Fantôme du meilleur essai
+settings-on = Oui
+settings-off = Non
+
+modifier-mirror = Miroir
+modifier-random = Aléatoire
+modifier-hidden = Caché
+modifier-sudden-death = Mort subite
+modifier-speed = Vitesse
+modifier-versus = Duel
+
+versus-player = Joueur
+versus-wins = gagne !
+versus-draw = Égalité !
+versus-max-combo = combo max
+
+courses-title = Parcours
+course-song = Chanson
+course-health = Vie
+course-score = Score
+course-total = Total
+course-completed = Parcours terminé !
+course-failed = Parcours échoué
+course-high-scores = Meilleurs scores
+course-continue = Appuyez sur Entrée pour continuer
+
+map-difficulty = Difficulté
@@ -0,0 +1,166 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use amethyst_extra::ShouldSave;
+
+use data::*;
+
+/// File of the asset packs defining the achievements.
+pub const ACHIEVEMENTS_FILE: &str = "config/achievements.ron";
+/// File in the user data directory holding the stats and the unlocked achievements.
+pub const PROGRESS_FILE: &str = "progress.ron";
+/// Seconds an achievement notification stays on the score screen.
+pub const ACHIEVEMENT_TOAST_TIME: f64 = 4.0;
+
+/// What a run or the stats must reach to unlock an achievement.
+/// Conditions with a `map` apply to any map when it is `None`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum AchievementCondition {
+    /// Completes a map hitting every beat point.
+    FullCombo(Option<String>),
+    /// Gets at least this grade on a map.
+    Grade { grade: Grade, map: Option<String> },
+    /// Has gotten at least this grade on every map.
+    GradeOnAllMaps(Grade),
+    /// Scores at least this much on a map.
+    Score { score: u32, map: Option<String> },
+    /// Has played this many maps, completed or not.
+    TotalPlays(u32),
+    /// Has completed this many maps.
+    TotalCompletions(u32),
+}
+
+/// An achievement, as defined in `config/achievements.ron`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Achievement {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub condition: AchievementCondition,
+    /// Hidden asset packs enabled when the achievement is unlocked, holding extra maps or skins.
+    #[serde(default)]
+    pub unlocks: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct AchievementList {
+    pub achievements: Vec<Achievement>,
+}
+
+/// Local stats of the player and the achievements unlocked so far, saved in the user data
+/// directory.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlayerProgress {
+    pub plays: u32,
+    pub completions: u32,
+    pub best_grades: BTreeMap<String, Grade>,
+    pub best_scores: BTreeMap<String, u32>,
+    /// Maps completed hitting every beat point.
+    pub full_combos: BTreeSet<String>,
+    /// Ids of the unlocked achievements.
+    pub achievements: BTreeSet<String>,
+    /// Hidden asset packs unlocked by the achievements.
+    pub unlocked_packs: BTreeSet<String>,
+}
+
+impl ShouldSave for PlayerProgress {
+    fn save_ready(&self) -> bool {
+        true
+    }
+
+    fn set_save_ready(&mut self, _ready: bool) {}
+}
+
+/// Whether the run hit every beat point of the map.
+fn is_full_combo(result: &GameplayResult) -> bool {
+    result.status == GameplayStatus::Completed
+        && !result.results.is_empty()
+        && result.max_combo as usize == result.results.len()
+}
+
+fn matches_map(map: &Option<String>, played: &str) -> bool {
+    map.as_ref().map(|m| m == played).unwrap_or(true)
+}
+
+impl PlayerProgress {
+    /// Adds a finished run of the map to the stats.
+    pub fn record(&mut self, map: &str, result: &GameplayResult) {
+        self.plays += 1;
+        if result.status != GameplayStatus::Completed {
+            return;
+        }
+        self.completions += 1;
+        let grade = Grade::of(result);
+        let best = self.best_grades.entry(map.to_owned()).or_insert(grade);
+        *best = (*best).max(grade);
+        let score = self.best_scores.entry(map.to_owned()).or_insert(0);
+        *score = (*score).max(result.score());
+        if is_full_combo(result) {
+            self.full_combos.insert(map.to_owned());
+        }
+    }
+
+    /// Whether the condition is met by the run of the map or by the stats.
+    /// `maps` are all the maps the player can play.
+    pub fn is_met(
+        &self,
+        condition: &AchievementCondition,
+        map: &str,
+        result: &GameplayResult,
+        maps: &[String],
+    ) -> bool {
+        match condition {
+            AchievementCondition::FullCombo(m) => matches_map(m, map) && is_full_combo(result),
+            AchievementCondition::Grade { grade, map: m } => {
+                matches_map(m, map) && Grade::of(result) >= *grade
+            }
+            AchievementCondition::GradeOnAllMaps(grade) => {
+                !maps.is_empty() && maps.iter().all(|m| {
+                    self.best_grades
+                        .get(m)
+                        .map(|g| g >= grade)
+                        .unwrap_or(false)
+                })
+            }
+            AchievementCondition::Score { score, map: m } => {
+                matches_map(m, map)
+                    && result.status == GameplayStatus::Completed
+                    && result.score() >= *score
+            }
+            AchievementCondition::TotalPlays(plays) => self.plays >= *plays,
+            AchievementCondition::TotalCompletions(completions) => {
+                self.completions >= *completions
+            }
+        }
+    }
+
+    /// Records the run, then unlocks the achievements it meets along with their packs.
+    /// Returns the newly unlocked achievements.
+    pub fn finish_run<'a>(
+        &mut self,
+        achievements: &'a [Achievement],
+        map: &str,
+        result: &GameplayResult,
+        maps: &[String],
+    ) -> Vec<&'a Achievement> {
+        self.record(map, result);
+        let unlocked = achievements
+            .iter()
+            .filter(|a| !self.achievements.contains(&a.id))
+            .filter(|a| self.is_met(&a.condition, map, result, maps))
+            .collect::<Vec<_>>();
+        for achievement in &unlocked {
+            self.achievements.insert(achievement.id.clone());
+            self.unlocked_packs
+                .extend(achievement.unlocks.iter().cloned());
+        }
+        unlocked
+    }
+}
+
+/// Names of the achievements unlocked since the last score screen, shown there as notifications.
+#[derive(Default)]
+pub struct AchievementToasts {
+    pub names: Vec<String>,
+}
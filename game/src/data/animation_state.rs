@@ -1,9 +1,4 @@
-#[derive(PartialEq, Eq, Default)]
-pub struct AnimationStateRes {
-    pub state: AnimationState,
-}
-
-#[derive(PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum AnimationState {
     Running,
     Jumping,
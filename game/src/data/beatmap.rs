@@ -1,7 +1,9 @@
 use amethyst::assets::Handle;
 use amethyst::audio::Source;
+use amethyst::core::{Clock, Clocks, GAME_CLOCK};
+use amethyst::ecs::World;
 
-use data::BeatPoint;
+use data::{BeatPoint, TimedEvent};
 use std::collections::VecDeque;
 
 #[derive(Deserialize)]
@@ -10,6 +12,9 @@ pub struct BeatMapData {
     pub music_path: String,
     pub audio_offset: f64,
     //pub beat_points: VecDeque<BeatPoint>,
+    /// Storyboard of the map, run by the `StoryboardSystem`.
+    #[serde(default)]
+    pub events: Vec<TimedEvent>,
 }
 
 pub struct BeatMap {
@@ -17,6 +22,17 @@ pub struct BeatMap {
     pub music: Handle<Source>,
     pub audio_offset: f64,
     pub beat_points: VecDeque<BeatPoint>,
-    /// This needs to be changed to Time::absolute_time_seconds() + 3 when inserting the map into resources and starting the level.
+    /// Time of the game clock at which the chart starts, see `game_time`.
+    /// This needs to be changed to game_time() + 3 when inserting the map into resources and starting the level.
     pub runtime_start: f64,
+    pub events: Vec<TimedEvent>,
+}
+
+/// Seconds elapsed on the game clock, which stops while the game is paused. The chart is played
+/// on this clock, so that no beat point passes during a pause.
+pub fn game_time(world: &World) -> f64 {
+    world
+        .read_resource::<Clocks>()
+        .get(GAME_CLOCK)
+        .map_or(0.0, Clock::absolute_time_seconds)
 }
@@ -6,6 +6,9 @@ use data::Direction;
 pub struct BeatPoint {
     pub direction: Direction,
     pub time: f64,
+    /// Sound played instead of the hit sound, relative to the map directory.
+    #[serde(default)]
+    pub sample: Option<String>,
 }
 
 impl Component for BeatPoint {
@@ -0,0 +1,77 @@
+use std::collections::BTreeMap;
+
+use data::GameplayStatus;
+
+/// Number of scores kept per course in the high score table.
+pub const COURSE_HIGH_SCORES: usize = 5;
+
+/// An ordered list of maps played back to back, read from `courses/<name>.ron`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CourseData {
+    pub name: String,
+    /// Names of the maps, as in `maps/<name>/`.
+    pub maps: Vec<String>,
+}
+
+/// The course being played, if any.
+/// While it is set, `GamePlayState` returns to the `CourseState` instead of showing the score.
+pub struct CourseProgress {
+    pub course: CourseData,
+    /// Index of the map being played, or to be played next.
+    pub index: usize,
+    /// Health carried over from the previous map.
+    pub health: i32,
+    /// Map name, score and status of every map played so far.
+    pub results: Vec<(String, u32, GameplayStatus)>,
+}
+
+impl CourseProgress {
+    pub fn new(course: CourseData, health: i32) -> Self {
+        CourseProgress {
+            course,
+            index: 0,
+            health,
+            results: vec![],
+        }
+    }
+
+    pub fn total_score(&self) -> u32 {
+        self.results.iter().map(|(_, score, _)| score).sum()
+    }
+
+    pub fn current_map(&self) -> Option<&String> {
+        self.course.maps.get(self.index)
+    }
+
+    /// Whether every map was played, or the last one was failed.
+    pub fn is_over(&self) -> bool {
+        self.current_map().is_none()
+            || self
+                .results
+                .last()
+                .map(|(_, _, status)| *status == GameplayStatus::Failed)
+                .unwrap_or(false)
+    }
+}
+
+/// Best total scores of every course, saved in the user data directory.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct CourseScores {
+    pub scores: BTreeMap<String, Vec<u32>>,
+}
+
+impl CourseScores {
+    /// Inserts a score in the table of the course, keeping the best `COURSE_HIGH_SCORES`.
+    /// Returns the rank of the score if it made it into the table.
+    pub fn insert(&mut self, course: &str, score: u32) -> Option<usize> {
+        let table = self.scores.entry(course.to_owned()).or_insert_with(Vec::new);
+        let rank = table.iter().position(|s| *s < score).unwrap_or(table.len());
+        table.insert(rank, score);
+        table.truncate(COURSE_HIGH_SCORES);
+        if rank < COURSE_HIGH_SCORES {
+            Some(rank)
+        } else {
+            None
+        }
+    }
+}
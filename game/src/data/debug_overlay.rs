@@ -0,0 +1,27 @@
+use std::time::Instant;
+
+use amethyst::ecs::Entity;
+use amethyst::renderer::SpriteSheetHandle;
+use amethyst::ui::FontHandle;
+
+/// Number of upcoming beat points shown with their time to hit.
+pub const DEBUG_NEXT_NOTES: usize = 3;
+/// Number of upcoming beat points with their hit window drawn.
+pub const DEBUG_HIT_WINDOWS: usize = 8;
+/// Number of the last hit errors shown.
+pub const DEBUG_HIT_ERRORS: usize = 5;
+
+/// The timing diagnostics shown over the gameplay, toggled with F3.
+#[derive(Default)]
+pub struct DebugOverlay {
+    pub enabled: bool,
+    pub font: Option<FontHandle>,
+    /// One text entity per line of the overlay.
+    pub lines: Vec<Entity>,
+    /// The bars drawn over the hit windows of the next beat points.
+    pub bars: Vec<Entity>,
+    /// Sprite sheet with the single white pixel the bars are drawn with.
+    pub bar_sprite: Option<SpriteSheetHandle>,
+    /// Wall clock and song time when the overlay was shown, to measure their drift.
+    pub clock_start: Option<(Instant, f64)>,
+}
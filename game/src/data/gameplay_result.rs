@@ -3,14 +3,114 @@ use data::*;
 #[derive(Clone)]
 pub struct GameplayResult {
     pub results: Vec<(f64, HitResult)>,
+    /// Song time at which each of the `results` was judged.
+    pub judge_times: Vec<f64>,
     pub status: GameplayStatus,
+    /// The modifiers the map was played with.
+    pub modifiers: Modifiers,
+    /// Health of the player at the end of the map.
+    pub health: i32,
+    /// Time of each key press minus the time of its beat point, in seconds.
+    pub hit_errors: Vec<f64>,
+    /// Number of beat points hit in a row.
+    pub combo: u32,
+    pub max_combo: u32,
 }
 
 impl Default for GameplayResult {
     fn default() -> Self {
         GameplayResult {
             results: vec![],
+            judge_times: vec![],
             status: GameplayStatus::Running,
+            modifiers: Modifiers::default(),
+            health: Player::default().health,
+            hit_errors: vec![],
+            combo: 0,
+            max_combo: 0,
+        }
+    }
+}
+
+impl GameplayResult {
+    /// Records the judgement of the beat point at `time`, made at the song time `judged_at`,
+    /// and updates the combo.
+    pub fn judge(&mut self, time: f64, hit: HitResult, judged_at: f64) {
+        match hit {
+            HitResult::Hit => {
+                self.combo += 1;
+                self.max_combo = self.max_combo.max(self.combo);
+            }
+            _ => self.combo = 0,
+        }
+        self.results.push((time, hit));
+        self.judge_times.push(judged_at);
+    }
+
+    /// The run as replayed by the ghost runner.
+    pub fn ghost_run(&self) -> GhostRun {
+        GhostRun {
+            score: self.score(),
+            judgements: self
+                .results
+                .iter()
+                .map(|(time, _)| *time)
+                .zip(self.judge_times.iter().cloned())
+                .collect(),
+        }
+    }
+
+    pub fn score(&self) -> u32 {
+        let mut score = 0;
+        for (_, h) in &self.results {
+            score += match h {
+                HitResult::Hit => 1000,
+                HitResult::MissKey => 100,
+                HitResult::MissEarly | HitResult::MissLate => 10,
+            };
+        }
+        (score as f32 * self.modifiers.score_multiplier()) as u32
+    }
+}
+
+/// The results of every player of the map, indexed by `Player::index`.
+#[derive(Clone, Default)]
+pub struct GameplayResults {
+    pub players: Vec<GameplayResult>,
+}
+
+impl GameplayResults {
+    /// The map runs until every player completed or failed it.
+    /// It counts as completed if any player completed it.
+    pub fn status(&self) -> GameplayStatus {
+        if self
+            .players
+            .iter()
+            .any(|p| p.status == GameplayStatus::Running)
+        {
+            GameplayStatus::Running
+        } else if self
+            .players
+            .iter()
+            .any(|p| p.status == GameplayStatus::Completed)
+        {
+            GameplayStatus::Completed
+        } else {
+            GameplayStatus::Failed
+        }
+    }
+
+    /// Index of the player with the best score, or `None` on a draw.
+    pub fn winner(&self) -> Option<usize> {
+        let best = self.players.iter().map(|p| p.score()).max()?;
+        let mut best_players = self
+            .players
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| p.score() == best);
+        match (best_players.next(), best_players.next()) {
+            (Some((i, _)), None) => Some(i),
+            _ => None,
         }
     }
 }
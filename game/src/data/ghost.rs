@@ -0,0 +1,58 @@
+use std::collections::{BTreeMap, VecDeque};
+
+use amethyst::core::cgmath::Vector3;
+use amethyst::ecs::{Component, DenseVecStorage};
+
+/// File in the user data directory holding the best run of every map.
+pub const MAP_SCORES_FILE: &str = "map_scores.ron";
+/// Opacity of the ghost runner.
+pub const GHOST_ALPHA: f32 = 0.4;
+
+/// The best run of a map, replayed by the ghost runner.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct GhostRun {
+    pub score: u32,
+    /// Time of each judged beat point and the song time it was judged at.
+    pub judgements: Vec<(f64, f64)>,
+}
+
+/// Best completed run of every map, saved in the user data directory.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct MapScores {
+    pub maps: BTreeMap<String, GhostRun>,
+}
+
+impl MapScores {
+    /// Keeps the run if it beats the best score of the map. Returns whether it did.
+    pub fn insert(&mut self, map: &str, run: GhostRun) -> bool {
+        let best = self.maps.get(map).map(|r| r.score < run.score).unwrap_or(true);
+        if best {
+            self.maps.insert(map.to_owned(), run);
+        }
+        best
+    }
+}
+
+/// The translucent runner replaying the best run of the map.
+pub struct Ghost {
+    /// Time of the beat points still to reach, with the song time they were judged at.
+    pub beat_points: VecDeque<(f64, f64)>,
+    /// Position and time of the last beat point reached.
+    pub last_beatpoint: Option<(Vector3<f32>, f64)>,
+    /// Time spent in the running animation.
+    pub animation_time: f64,
+}
+
+impl Ghost {
+    pub fn new(run: &GhostRun) -> Self {
+        Ghost {
+            beat_points: run.judgements.iter().cloned().collect(),
+            last_beatpoint: None,
+            animation_time: 0.0,
+        }
+    }
+}
+
+impl Component for Ghost {
+    type Storage = DenseVecStorage<Self>;
+}
@@ -0,0 +1,37 @@
+use data::*;
+
+/// Rank of a run, from the worst to the best.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Grade {
+    F,
+    C,
+    B,
+    A,
+    S,
+}
+
+impl Grade {
+    /// The grade of a run, from its ratio of hit beat points. A failed run is graded F.
+    pub fn of(result: &GameplayResult) -> Grade {
+        if result.status != GameplayStatus::Completed {
+            return Grade::F;
+        }
+        let mut successes = 0;
+        for (_, h) in &result.results {
+            successes += match h {
+                HitResult::Hit => 1,
+                _ => 0,
+            };
+        }
+        let ratio = successes as f32 / result.results.len() as f32;
+        if ratio < 0.40 {
+            Grade::C
+        } else if ratio < 0.70 {
+            Grade::B
+        } else if ratio < 0.97 {
+            Grade::A
+        } else {
+            Grade::S
+        }
+    }
+}
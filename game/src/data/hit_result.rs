@@ -1,3 +1,6 @@
+/// Seconds before and after its time during which a beat point can be hit.
+pub const HIT_WINDOW: f64 = 0.2;
+
 #[derive(Clone)]
 pub enum HitResult {
     Hit,
@@ -0,0 +1,54 @@
+use amethyst::audio::SourceHandle;
+
+use data::HitResult;
+
+/// Paths of the sounds played for each judgement, read from `config/hitsounds.ron`.
+/// A map can override it with its own `hitsounds.ron`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HitSoundsConfig {
+    pub hit: Option<String>,
+    pub miss_early: Option<String>,
+    pub miss_late: Option<String>,
+    pub miss_key: Option<String>,
+    /// Played for the misses without a sound of their own.
+    pub miss: Option<String>,
+}
+
+impl HitSoundsConfig {
+    pub fn path(&self, result: &HitResult) -> Option<&String> {
+        let specific = match *result {
+            HitResult::Hit => return self.hit.as_ref(),
+            HitResult::MissEarly => &self.miss_early,
+            HitResult::MissLate => &self.miss_late,
+            HitResult::MissKey => &self.miss_key,
+        };
+        specific.as_ref().or(self.miss.as_ref())
+    }
+}
+
+/// The hit sounds loaded for the current map.
+#[derive(Default)]
+pub struct HitSounds {
+    pub hit: Option<SourceHandle>,
+    pub miss_early: Option<SourceHandle>,
+    pub miss_late: Option<SourceHandle>,
+    pub miss_key: Option<SourceHandle>,
+    /// Samples of the beat points defining one, by beat point time.
+    pub samples: Vec<(f64, SourceHandle)>,
+}
+
+impl HitSounds {
+    pub fn judgement(&self, result: &HitResult) -> Option<&SourceHandle> {
+        match *result {
+            HitResult::Hit => self.hit.as_ref(),
+            HitResult::MissEarly => self.miss_early.as_ref(),
+            HitResult::MissLate => self.miss_late.as_ref(),
+            HitResult::MissKey => self.miss_key.as_ref(),
+        }
+    }
+
+    pub fn sample(&self, time: f64) -> Option<&SourceHandle> {
+        self.samples.iter().find(|(t, _)| *t == time).map(|(_, s)| s)
+    }
+}
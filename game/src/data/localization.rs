@@ -0,0 +1,56 @@
+use amethyst::assets::AssetStorage;
+use amethyst::ecs::{Component, DenseVecStorage};
+use amethyst::locale::{Locale, LocaleHandle};
+
+/// Language used when a key is missing from the selected language.
+pub const FALLBACK_LANGUAGE: &str = "en";
+
+/// The selected language and its loaded locale files.
+pub struct Localization {
+    pub language: String,
+    pub locale: Option<LocaleHandle>,
+    pub fallback: Option<LocaleHandle>,
+}
+
+impl Default for Localization {
+    fn default() -> Self {
+        Localization {
+            language: FALLBACK_LANGUAGE.to_owned(),
+            locale: None,
+            fallback: None,
+        }
+    }
+}
+
+impl Localization {
+    /// Looks up the key in the selected language, then in the fallback language.
+    pub fn get(&self, key: &str, storage: &AssetStorage<Locale>) -> Option<String> {
+        [&self.locale, &self.fallback]
+            .iter()
+            .filter_map(|h| h.as_ref().and_then(|h| storage.get(h)))
+            .filter_map(|l| {
+                l.context
+                    .get_message(key)
+                    .and_then(|msg| l.context.format(msg, None))
+            })
+            .next()
+    }
+
+    /// Same as `get`, but returns the key itself when no translation exists.
+    pub fn text(&self, key: &str, storage: &AssetStorage<Locale>) -> String {
+        self.get(key, storage).unwrap_or_else(|| {
+            warn!("Missing translation for {}", key);
+            key.to_owned()
+        })
+    }
+}
+
+/// Keeps the `UiText` of the entity in sync with the translation of `key`.
+#[derive(Debug, new)]
+pub struct LocalizedText {
+    pub key: String,
+}
+
+impl Component for LocalizedText {
+    type Storage = DenseVecStorage<Self>;
+}
@@ -1,25 +1,45 @@
+mod achievements;
 mod animation_state;
 mod beatmap;
 mod beatpoint;
+mod course;
+mod debug_overlay;
 mod direction;
 mod falling_object;
 mod gameplay_command;
 mod gameplay_result;
 mod gameplay_status;
+mod ghost;
+mod grade;
 mod hit_result;
+mod hit_sounds;
+mod localization;
+mod modifiers;
 mod player;
 mod result_entities;
 mod rotating_object;
+mod storyboard;
+mod user_settings;
 
+pub use self::achievements::*;
 pub use self::animation_state::*;
 pub use self::beatmap::*;
 pub use self::beatpoint::*;
+pub use self::course::*;
+pub use self::debug_overlay::*;
 pub use self::direction::*;
 pub use self::falling_object::*;
 pub use self::gameplay_command::*;
 pub use self::gameplay_result::*;
 pub use self::gameplay_status::*;
+pub use self::ghost::*;
+pub use self::grade::*;
 pub use self::hit_result::*;
+pub use self::hit_sounds::*;
+pub use self::localization::*;
+pub use self::modifiers::*;
 pub use self::player::*;
 pub use self::result_entities::*;
 pub use self::rotating_object::*;
+pub use self::storyboard::*;
+pub use self::user_settings::*;
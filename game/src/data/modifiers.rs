@@ -0,0 +1,186 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use data::{BeatPoint, Direction};
+
+/// Seconds before its time over which a beat point fades out with the `hidden` modifier.
+pub const HIDDEN_WINDOW: f64 = 0.3;
+
+/// The gameplay modifiers selected on the map selection screen.
+/// They are copied into the `GameplayResult` when a map starts.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Modifiers {
+    /// Swaps Left and Right.
+    pub mirror: bool,
+    /// Shuffles the directions using this seed.
+    pub random: Option<u64>,
+    /// Beat points fade out shortly before their time.
+    pub hidden: bool,
+    /// The map is failed on the first miss.
+    pub sudden_death: bool,
+    /// Multiplier of the spacing of the beat points on the track, and of their approach speed.
+    pub speed: f32,
+    /// Two players race on the same map, on one keyboard.
+    pub versus: bool,
+}
+
+impl Default for Modifiers {
+    fn default() -> Self {
+        Modifiers {
+            mirror: false,
+            random: None,
+            hidden: false,
+            sudden_death: false,
+            speed: 1.0,
+            versus: false,
+        }
+    }
+}
+
+/// The speeds the speed modifier cycles through.
+pub const SPEEDS: [f32; 4] = [1.0, 1.5, 2.0, 0.5];
+
+/// One of the modifiers of `Modifiers`, as shown on the map selection screen.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Modifier {
+    Mirror,
+    Random,
+    Hidden,
+    SuddenDeath,
+    Speed,
+    Versus,
+}
+
+pub const MODIFIERS: [Modifier; 6] = [
+    Modifier::Mirror,
+    Modifier::Random,
+    Modifier::Hidden,
+    Modifier::SuddenDeath,
+    Modifier::Speed,
+    Modifier::Versus,
+];
+
+impl Modifier {
+    pub fn label_key(&self) -> &'static str {
+        match *self {
+            Modifier::Mirror => "modifier-mirror",
+            Modifier::Random => "modifier-random",
+            Modifier::Hidden => "modifier-hidden",
+            Modifier::SuddenDeath => "modifier-sudden-death",
+            Modifier::Speed => "modifier-speed",
+            Modifier::Versus => "modifier-versus",
+        }
+    }
+
+    /// Short, language independent text showing the value of the modifier.
+    pub fn value_text(&self, modifiers: &Modifiers) -> String {
+        let flag = |b: bool| if b { "[x]" } else { "[ ]" }.to_owned();
+        match *self {
+            Modifier::Mirror => flag(modifiers.mirror),
+            Modifier::Random => flag(modifiers.random.is_some()),
+            Modifier::Hidden => flag(modifiers.hidden),
+            Modifier::SuddenDeath => flag(modifiers.sudden_death),
+            Modifier::Speed => format!("x{:.1}", modifiers.speed),
+            Modifier::Versus => flag(modifiers.versus),
+        }
+    }
+
+    /// Toggles the modifier, or switches to the next speed.
+    pub fn toggle(&self, modifiers: &mut Modifiers) {
+        match *self {
+            Modifier::Mirror => modifiers.mirror = !modifiers.mirror,
+            Modifier::Random => {
+                modifiers.random = match modifiers.random {
+                    Some(_) => None,
+                    None => Some(::rand::random()),
+                }
+            }
+            Modifier::Hidden => modifiers.hidden = !modifiers.hidden,
+            Modifier::SuddenDeath => modifiers.sudden_death = !modifiers.sudden_death,
+            Modifier::Speed => {
+                let next = SPEEDS
+                    .iter()
+                    .position(|s| *s == modifiers.speed)
+                    .map(|i| (i + 1) % SPEEDS.len())
+                    .unwrap_or(0);
+                modifiers.speed = SPEEDS[next];
+            }
+            Modifier::Versus => modifiers.versus = !modifiers.versus,
+        }
+    }
+}
+
+impl Modifiers {
+    /// Multiplier applied to the score of a map played with these modifiers.
+    pub fn score_multiplier(&self) -> f32 {
+        let mut multiplier = 1.0;
+        if self.mirror {
+            multiplier *= 1.05;
+        }
+        if self.random.is_some() {
+            multiplier *= 1.1;
+        }
+        if self.hidden {
+            multiplier *= 1.2;
+        }
+        if self.sudden_death {
+            multiplier *= 1.3;
+        }
+        if self.speed > 1.0 {
+            multiplier *= 1.0 + (self.speed - 1.0) * 0.2;
+        } else {
+            multiplier *= self.speed;
+        }
+        multiplier
+    }
+
+    /// The modifiers which differ from the defaults.
+    pub fn active(&self) -> Vec<Modifier> {
+        MODIFIERS
+            .iter()
+            .cloned()
+            .filter(|m| match *m {
+                Modifier::Mirror => self.mirror,
+                Modifier::Random => self.random.is_some(),
+                Modifier::Hidden => self.hidden,
+                Modifier::SuddenDeath => self.sudden_death,
+                Modifier::Speed => self.speed != 1.0,
+                Modifier::Versus => self.versus,
+            })
+            .collect()
+    }
+
+    /// Rewrites the directions of the beat points according to `mirror` and `random`.
+    pub fn apply<'a, I>(&self, beat_points: I)
+    where
+        I: IntoIterator<Item = &'a mut BeatPoint>,
+    {
+        let mut mapping = [
+            Direction::Left,
+            Direction::Right,
+            Direction::Up,
+            Direction::Down,
+        ];
+        if let Some(seed) = self.random {
+            let mut bytes = [0u8; 32];
+            for (i, b) in bytes.iter_mut().enumerate() {
+                *b = (seed >> ((i % 8) * 8)) as u8;
+            }
+            StdRng::from_seed(bytes).shuffle(&mut mapping);
+        }
+        for beat_point in beat_points {
+            let direction = match beat_point.direction {
+                Direction::Left => mapping[0],
+                Direction::Right => mapping[1],
+                Direction::Up => mapping[2],
+                Direction::Down => mapping[3],
+            };
+            beat_point.direction = match direction {
+                Direction::Left if self.mirror => Direction::Right,
+                Direction::Right if self.mirror => Direction::Left,
+                d => d,
+            };
+        }
+    }
+}
@@ -1,8 +1,22 @@
 use amethyst::ecs::{Component, VecStorage};
 
+use std::collections::VecDeque;
+
+use data::{AnimationState, BeatPoint, Direction};
+
+/// Number of players of the versus mode.
+pub const VERSUS_PLAYERS: usize = 2;
+/// Vertical distance between the players running the same track, in world units.
+pub const PLAYER_LANE_OFFSET: f32 = -0.06;
+
 pub struct Player {
+    /// Index of the player, and of its result in the `GameplayResults`.
+    pub index: usize,
     pub health: i32,
     pub velocity: f32,
+    pub animation: AnimationState,
+    /// The beat points the player still has to hit, sorted by time.
+    pub beat_points: VecDeque<BeatPoint>,
 }
 
 impl Component for Player {
@@ -11,6 +25,30 @@ impl Component for Player {
 
 impl Default for Player {
     fn default() -> Self {
-        Player { health: 10, velocity: 1.0 }
+        Player {
+            index: 0,
+            health: 10,
+            velocity: 1.0,
+            animation: AnimationState::None,
+            beat_points: VecDeque::new(),
+        }
     }
 }
+
+const ACTION_DIRECTIONS: [(&str, Direction); 4] = [
+    ("left", Direction::Left),
+    ("up", Direction::Up),
+    ("right", Direction::Right),
+    ("down", Direction::Down),
+];
+
+/// Reads the player index and the direction of a player input action, like `p1_left`,
+/// as bound in `config/input.ron`.
+pub fn parse_player_action(action: &str) -> Option<(usize, Direction)> {
+    let (player, name) = action.split_at(action.find('_')?);
+    let index = player.trim_left_matches('p').parse::<usize>().ok()?.checked_sub(1)?;
+    ACTION_DIRECTIONS
+        .iter()
+        .find(|(n, _)| *n == &name[1..])
+        .map(|(_, d)| (index, *d))
+}
@@ -0,0 +1,95 @@
+use amethyst::ecs::Entity;
+use amethyst::renderer::SpriteRender;
+use amethyst::ui::FontHandle;
+
+use std::collections::VecDeque;
+
+/// Something happening at a given time of a chart, besides the notes.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum ChartEvent {
+    /// Covers the screen with a colour. Use a low alpha to tint it, zero to remove the tint.
+    Tint([f32; 4]),
+    /// Covers the screen with a colour for a moment.
+    Flash { color: [f32; 4], duration: f64 },
+    /// Changes the sprite of the scene entities with this `Removal` id.
+    SpriteSwap { removal_id: i32, sprite_number: usize },
+    /// Shows the scene entities with this `Removal` id again.
+    Show(i32),
+    /// Hides the scene entities with this `Removal` id.
+    Hide(i32),
+    /// Zooms the camera in (above 1) or out (below 1).
+    CameraZoom(f32),
+    /// Multiplier of the approach speed of the beat points.
+    ScrollSpeed(f32),
+    /// Shows a text at the bottom of the screen.
+    Caption { text: String, duration: f64 },
+}
+
+impl ChartEvent {
+    /// Whether the event only shows something for a moment, instead of changing the state of the map.
+    pub fn is_transient(&self) -> bool {
+        match *self {
+            ChartEvent::Flash { .. } | ChartEvent::Caption { .. } => true,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TimedEvent {
+    /// Time in the chart, in seconds, like the beat points.
+    pub time: f64,
+    pub event: ChartEvent,
+}
+
+/// State of the storyboard of the map being played.
+pub struct Storyboard {
+    /// Events still to come, sorted by time.
+    pub events: VecDeque<TimedEvent>,
+    pub camera_zoom: f32,
+    pub scroll_speed: f32,
+    /// Font of the captions.
+    pub font: Option<FontHandle>,
+    /// The entity covering the screen for the current tint.
+    pub tint: Option<Entity>,
+    /// Sprites removed by a `Hide` event, with their `Removal` id.
+    pub hidden: Vec<(Entity, i32, SpriteRender)>,
+    /// The `Removal` ids of the scene entities the events act on.
+    pub removal_ids: Vec<i32>,
+}
+
+impl Default for Storyboard {
+    fn default() -> Self {
+        Storyboard {
+            events: VecDeque::new(),
+            camera_zoom: 1.0,
+            scroll_speed: 1.0,
+            font: None,
+            tint: None,
+            hidden: vec![],
+            removal_ids: vec![],
+        }
+    }
+}
+
+impl Storyboard {
+    pub fn new(mut events: Vec<TimedEvent>, font: Option<FontHandle>) -> Self {
+        events.sort_by(|a, b| a.time.partial_cmp(&b.time).unwrap());
+        let mut removal_ids = events
+            .iter()
+            .filter_map(|e| match e.event {
+                ChartEvent::SpriteSwap { removal_id, .. } => Some(removal_id),
+                ChartEvent::Show(id) | ChartEvent::Hide(id) => Some(id),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        removal_ids.sort();
+        removal_ids.dedup();
+        Storyboard {
+            events: events.into(),
+            font,
+            removal_ids,
+            ..Default::default()
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use amethyst_extra::ShouldSave;
+
+/// Options the player can change from the settings screen.
+/// Defaults come from `config/settings.ron` in the asset packs, the user's choices are saved
+/// separately and override them.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UserSettings {
+    pub music_volume: f32,
+    pub sfx_volume: f32,
+    /// Applied live.
+    pub fullscreen: bool,
+    /// Requires a restart.
+    pub vsync: bool,
+    /// Multiplier of the distance between beat points.
+    pub scroll_speed: f32,
+    /// Seconds added to the time of every note, to compensate for audio latency.
+    pub note_offset: f64,
+    /// Shows the ghost of the best run of the map.
+    pub ghost: bool,
+    pub language: String,
+}
+
+impl Default for UserSettings {
+    fn default() -> Self {
+        UserSettings {
+            music_volume: 1.0,
+            sfx_volume: 1.0,
+            fullscreen: false,
+            vsync: true,
+            scroll_speed: 1.0,
+            note_offset: 0.0,
+            ghost: true,
+            language: "en".to_owned(),
+        }
+    }
+}
+
+impl ShouldSave for UserSettings {
+    fn save_ready(&self) -> bool {
+        true
+    }
+
+    fn set_save_ready(&mut self, _ready: bool) {}
+}
@@ -4,13 +4,16 @@ extern crate amethyst;
 extern crate amethyst_extra;
 #[macro_use]
 extern crate derive_new;
+extern crate dirty;
 #[macro_use]
 extern crate log;
 extern crate rand;
+extern crate rodio;
 extern crate ron;
 #[macro_use]
 extern crate serde;
 extern crate floating_duration;
+extern crate zip;
 
 use amethyst::animation::*;
 use amethyst::assets::*;
@@ -19,14 +22,17 @@ use amethyst::core::cgmath::{Matrix4, Vector3};
 use amethyst::core::*;
 use amethyst::ecs::*;
 use amethyst::input::*;
+use amethyst::locale::Locale;
 use amethyst::prelude::*;
 use amethyst::renderer::mouse::set_mouse_cursor_none;
 use amethyst::renderer::*;
 use amethyst::ui::*;
+use amethyst::utils::fps_counter::FPSCounterBundle;
 use amethyst::utils::scene::BasicScenePrefab;
-use amethyst::Result;
+use amethyst::{LogLevelFilter, LoggerConfig, Result};
 use amethyst_extra::*;
 
+use dirty::Dirty;
 use std::env;
 
 mod data;
@@ -39,15 +45,65 @@ pub use states::*;
 pub use systems::*;
 pub use utils::*;
 
+/// Logger settings, next to the game.
+const LOGGER_FILE: &str = "logger.ron";
+/// Log file used when there are no logger settings.
+const LOG_FILE: &str = "game.log";
+
 fn main() -> Result<()> {
-    amethyst::start_logger(Default::default());
+    let log_buffer = amethyst::start_logger(
+        LoggerConfig::load_no_fallback(&user_data_path(LOGGER_FILE)).unwrap_or_else(|_| {
+            LoggerConfig {
+                module_levels: vec![("gfx_device_gl".to_string(), LogLevelFilter::Warn)],
+                log_file: Some(user_data_path(LOG_FILE).into()),
+                max_log_file_size: Some(1024 * 1024),
+                ..Default::default()
+            }
+        }),
+    );
 
-    let asset_loader =
+    // Prints the difficulty breakdown of every map instead of starting the game.
+    if env::args().any(|arg| arg == "--difficulty") {
+        print_difficulty_report(&format!("{}/assets", get_working_dir()));
+        return Ok(());
+    }
+
+    let mut asset_loader =
         AssetLoader::new(&format!("{}/assets", get_working_dir()).to_string(), "base");
-    let display_config_path = asset_loader.resolve_path("config/display.ron").unwrap();
-    let key_bindings_path = asset_loader.resolve_path("config/input.ron").unwrap();
+    asset_loader.load_pack_settings(&user_data_path(PACK_SETTINGS_FILE));
+    let progress = PlayerProgress::load_no_fallback(&user_data_path(PROGRESS_FILE))
+        .unwrap_or_default();
+    lock_hidden_packs(&mut asset_loader, &progress);
 
-    let config = DisplayConfig::load(&display_config_path);
+    // Prints the suggested tempo, offset and beat points of a map instead of starting the game.
+    let args = env::args().collect::<Vec<_>>();
+    if let Some(i) = args.iter().position(|arg| arg == "--detect-beats") {
+        match args.get(i + 1) {
+            Some(map) => print_beat_detection(&asset_loader, map),
+            None => error!("Usage: --detect-beats <map>"),
+        }
+        return Ok(());
+    }
+    // Writes a map folder into a map archive instead of starting the game.
+    if let Some(i) = args.iter().position(|arg| arg == "--export-map") {
+        match args.get(i + 1) {
+            Some(map) => {
+                let output = args
+                    .get(i + 2)
+                    .cloned()
+                    .unwrap_or_else(|| format!("{}.{}", map, MAP_ARCHIVE_EXTENSIONS[0]));
+                match export_map(&asset_loader, map, &output) {
+                    Ok(()) => info!("Exported map {} to {}", map, output),
+                    Err(err) => error!("Failed to export map {}: {}", map, err),
+                }
+            }
+            None => error!("Usage: --export-map <map> [output]"),
+        }
+        return Ok(());
+    }
+    let settings = load_user_settings(&asset_loader);
+    let display_config = load_display_config(&asset_loader, &settings, &args)?.value;
+    let frame_limit_config = load_frame_limit_config(&args)?;
 
     let pipe = Pipeline::build().with_stage(
         Stage::with_backbuffer()
@@ -56,9 +112,18 @@ fn main() -> Result<()> {
             .with_pass(DrawUi::new()),
     );
 
+    // Reloads the changed assets and charts every second.
+    let hot_reload = if env::args().any(|arg| arg == "--hot-reload") {
+        HotReloadStrategy::every(1)
+    } else {
+        HotReloadStrategy::never()
+    };
+
     let game_data_builder = GameDataBuilder::default()
+        .with_bundle(HotReloadBundle::new(hot_reload))?
         .with_bundle(
-            InputBundle::<String, String>::new().with_bindings_from_file(&key_bindings_path)?,
+            InputBundle::<String, String>::new()
+                .with_layered_bindings(&input_bindings_config(&asset_loader, &args))?,
         )?.with(
             FollowMouseSystem::<String, String>::default(),
             "follow_mouse",
@@ -79,7 +144,9 @@ fn main() -> Result<()> {
             "",
             &[],
         )
+        .with(Processor::<Locale>::new(), "locale_processor", &[])
         .with(TimedDestroySystem, "timed_destroy", &[])
+        .with_bundle(FPSCounterBundle)?
         .with(NormalOrthoCameraSystem::default(), "aspect_ratio", &[])
         .with(
             VisibilitySortingSystem::new(),
@@ -87,21 +154,90 @@ fn main() -> Result<()> {
             &["transform_system"],
         ).with(ChangeControlListener, "change_control", &[])
         .with(MapSelectionUiEventHandlerSystem::new(), "map_selection_ui_event_handler_system", &["ui_button_system"])
-        .with(ScoreMenuAnimation::new(), "score_menu_animation", &[])
-        .with(MakeObjectsFall, "make_objects_fall", &[])
-        .with(MakeObjectsRotate, "make_objects_rotate", &[])
         .with(AnimationVisual::new(), "animation_visual", &[])
-        .with_bundle(RenderBundle::new(pipe, Some(config)))?;
+        .with(
+            AutoSaveSystem::<UserSettings>::new(user_data_path(SETTINGS_FILE)),
+            "settings_autosave",
+            &[],
+        )
+        .with(
+            AutoSaveSystem::<PlayerProgress>::new(user_data_path(PROGRESS_FILE)),
+            "progress_autosave",
+            &[],
+        )
+        .with(
+            LocalizedTextSystem,
+            "localized_text",
+            &["locale_processor"],
+        )
+        .with(ConsoleSystem::new("console"), "console", &["input_system"])
+        .with_group(GAMEPLAY_SYSTEMS, false)
+        .with_in_group(GAMEPLAY_SYSTEMS, GameplayInputSystem::new(), "gameplay_input_system", &[])
+        .with_in_group(
+            GAMEPLAY_SYSTEMS,
+            HitSoundSystem::default(),
+            "hit_sounds",
+            &["gameplay_input_system"],
+        )
+        .with_in_group(GAMEPLAY_SYSTEMS, PlayerMovementSystem::new(), "player_movement", &[])
+        .with_in_group(GAMEPLAY_SYSTEMS, GhostSystem::default(), "ghost", &[])
+        .with_in_group(
+            GAMEPLAY_SYSTEMS,
+            CameraFollowPlayerSystem,
+            "camera_follow_player_system",
+            &["gameplay_input_system"],
+        )
+        .with_in_group(GAMEPLAY_SYSTEMS, BeatPointAnimationSystem, "beatpoint_animation", &[])
+        .with_in_group(GAMEPLAY_SYSTEMS, StoryboardSystem, "storyboard", &[])
+        .with_in_group(
+            GAMEPLAY_SYSTEMS,
+            DebugOverlaySystem,
+            "debug_overlay",
+            &["gameplay_input_system"],
+        )
+        .with_in_group(GAMEPLAY_SYSTEMS, MakeObjectsFall, "make_objects_fall", &[])
+        .with_in_group(GAMEPLAY_SYSTEMS, MakeObjectsRotate, "make_objects_rotate", &[])
+        .with_group(SCORE_SYSTEMS, false)
+        .with_in_group(SCORE_SYSTEMS, ScoreMenuAnimation::new(), "score_menu_animation", &[])
+        .with_bundle(RenderBundle::new(pipe, Some(display_config)))?;
 
     let resources_directory = format!("");
-    Application::build(resources_directory, MapSelectionState::new())?
+    Application::build(resources_directory.clone(), MapSelectionState::new())?
+        .with_default_source(ArchiveSource::new(resources_directory))
+        .with_frame_limit_config(frame_limit_config)
         .with_resource(asset_loader)
         .with_resource(::utils::Music::default())
         .with_resource(AssetLoaderInternal::<Texture>::new())
         .with_resource(AssetLoaderInternal::<FontAsset>::new())
         .with_resource(AssetLoaderInternal::<amethyst::audio::Source>::new())
+        .with_resource(AssetLoaderInternal::<Locale>::new())
+        .with_resource(Localization {
+            language: settings.language.clone(),
+            ..Default::default()
+        })
+        .with_resource({
+            // The loaded settings don't need to be saved again.
+            let mut settings = Dirty::new(settings);
+            settings.clear();
+            settings
+        })
+        .with_resource({
+            let mut progress = Dirty::new(progress);
+            progress.clear();
+            progress
+        })
+        .with_resource(AchievementToasts::default())
+        .with_resource(Modifiers::default())
+        .with_resource(HitSounds::default())
+        .with_resource(Storyboard::default())
+        .with_resource(DebugOverlay::default())
+        .with_resource(log_buffer)
+        .with_resource(console_commands())
         .with_resource::<Option<ResultEntities>>(None)
+        .with_resource::<Option<CourseProgress>>(None)
         .with_resource::<GameplayCommand>(GameplayCommand::BackToMenu)
+        // A failing system, e.g. a broken song in a map pack, shouldn't close the game.
+        .with_error_policy(amethyst::ErrorPolicy::default().continue_on_recoverable(true))
         .build(game_data_builder)?
         .run();
     Ok(())
@@ -6,9 +6,13 @@ use amethyst::ui::{
     Anchor, FontAsset, TtfFormat, UiButton, UiButtonBuilder, UiEvent, UiEventType, UiImage, UiText,
     UiTransform,
 };
+use amethyst::utils::state_scoped::StateScoped;
 use amethyst::{GameData, State, StateData, Trans};
 use amethyst_extra::{AssetLoader, AssetLoaderInternal};
 
+use data::LocalizedText;
+use utils::localize;
+
 #[derive(Default, new)]
 pub struct ChangeControlState {
     #[new(default)]
@@ -26,15 +30,9 @@ pub struct ChangeControlState {
     down_entity: Option<Entity>,
 }
 
-struct CleanupControl;
-impl Component for CleanupControl {
-    type Storage = VecStorage<CleanupControl>;
-}
-
 impl<'a, 'b> State<GameData<'a, 'b>> for ChangeControlState {
     fn on_start(&mut self, data: StateData<GameData>) {
         let mut world = data.world;
-        world.register::<CleanupControl>();
 
         let font = world
             .write_resource::<AssetLoader>()
@@ -58,19 +56,21 @@ impl<'a, 'b> State<GameData<'a, 'b>> for ChangeControlState {
             .with_size(130.0, 32.0)
             .build_from_world(world);
         world
-            .write_storage::<CleanupControl>()
-            .insert(back_button, CleanupControl)
+            .write_storage::<StateScoped>()
+            .insert(back_button, StateScoped::new())
             .unwrap();
         self.back_entity = Some(back_button);
 
+        let left_text = localize(world, "controls-left");
         let left_label = world
             .create_entity()
             .with(UiText::new(
                 font.clone(),
-                "Left".to_owned(),
+                left_text,
                 [0.0, 0.0, 0.0, 1.0],
                 25.0,
             ))
+            .with(LocalizedText::new("controls-left".to_owned()))
             .with(
                 UiTransform::new(
                     "left_label".to_owned(),
@@ -83,7 +83,7 @@ impl<'a, 'b> State<GameData<'a, 'b>> for ChangeControlState {
                     2,
                 ).as_transparent(),
             )
-            .with(CleanupControl)
+            .with(StateScoped::new())
             .build();
 
         let (change, change_hover, change_press) = load_button_images(&mut world, "change");
@@ -97,19 +97,21 @@ impl<'a, 'b> State<GameData<'a, 'b>> for ChangeControlState {
             .with_size(130.0, 32.0)
             .build_from_world(world);
         world
-            .write_storage::<CleanupControl>()
-            .insert(change_button, CleanupControl)
+            .write_storage::<StateScoped>()
+            .insert(change_button, StateScoped::new())
             .unwrap();
         self.left_entity = Some(change_button);
 
+        let right_text = localize(world, "controls-right");
         let right_label = world
             .create_entity()
             .with(UiText::new(
                 font.clone(),
-                "Right".to_owned(),
+                right_text,
                 [0.0, 0.0, 0.0, 1.0],
                 25.0,
             ))
+            .with(LocalizedText::new("controls-right".to_owned()))
             .with(
                 UiTransform::new(
                     "right_label".to_owned(),
@@ -122,7 +124,7 @@ impl<'a, 'b> State<GameData<'a, 'b>> for ChangeControlState {
                     2,
                 ).as_transparent(),
             )
-            .with(CleanupControl)
+            .with(StateScoped::new())
             .build();
 
         let change_button = UiButtonBuilder::new("right_change_button", "")
@@ -134,19 +136,21 @@ impl<'a, 'b> State<GameData<'a, 'b>> for ChangeControlState {
             .with_size(130.0, 32.0)
             .build_from_world(world);
         world
-            .write_storage::<CleanupControl>()
-            .insert(change_button, CleanupControl)
+            .write_storage::<StateScoped>()
+            .insert(change_button, StateScoped::new())
             .unwrap();
         self.right_entity = Some(change_button);
 
+        let up_text = localize(world, "controls-up");
         let up_label = world
             .create_entity()
             .with(UiText::new(
                 font.clone(),
-                "Up".to_owned(),
+                up_text,
                 [0.0, 0.0, 0.0, 1.0],
                 25.0,
             ))
+            .with(LocalizedText::new("controls-up".to_owned()))
             .with(
                 UiTransform::new(
                     "up_label".to_owned(),
@@ -159,7 +163,7 @@ impl<'a, 'b> State<GameData<'a, 'b>> for ChangeControlState {
                     2,
                 ).as_transparent(),
             )
-            .with(CleanupControl)
+            .with(StateScoped::new())
             .build();
 
         let change_button = UiButtonBuilder::new("up_change_button", "")
@@ -171,19 +175,21 @@ impl<'a, 'b> State<GameData<'a, 'b>> for ChangeControlState {
             .with_size(130.0, 32.0)
             .build_from_world(world);
         world
-            .write_storage::<CleanupControl>()
-            .insert(change_button, CleanupControl)
+            .write_storage::<StateScoped>()
+            .insert(change_button, StateScoped::new())
             .unwrap();
         self.up_entity = Some(change_button);
 
+        let down_text = localize(world, "controls-down");
         let down_label = world
             .create_entity()
             .with(UiText::new(
                 font.clone(),
-                "Down".to_owned(),
+                down_text,
                 [0.0, 0.0, 0.0, 1.0],
                 25.0,
             ))
+            .with(LocalizedText::new("controls-down".to_owned()))
             .with(
                 UiTransform::new(
                     "down_label".to_owned(),
@@ -196,7 +202,7 @@ impl<'a, 'b> State<GameData<'a, 'b>> for ChangeControlState {
                     2,
                 ).as_transparent(),
             )
-            .with(CleanupControl)
+            .with(StateScoped::new())
             .build();
 
         let change_button = UiButtonBuilder::new("down_change_button", "")
@@ -208,8 +214,8 @@ impl<'a, 'b> State<GameData<'a, 'b>> for ChangeControlState {
             .with_size(130.0, 32.0)
             .build_from_world(world);
         world
-            .write_storage::<CleanupControl>()
-            .insert(change_button, CleanupControl)
+            .write_storage::<StateScoped>()
+            .insert(change_button, StateScoped::new())
             .unwrap();
         self.down_entity = Some(change_button);
 
@@ -242,7 +248,7 @@ impl<'a, 'b> State<GameData<'a, 'b>> for ChangeControlState {
                     2,
                 ).as_transparent(),
             )
-            .with(CleanupControl)
+            .with(StateScoped::new())
             .build();
 
         self.ui_events = Some(
@@ -302,7 +308,6 @@ impl<'a, 'b> State<GameData<'a, 'b>> for ChangeControlState {
                 false
             },
         ) {
-            cleanup(data.world);
             Trans::Pop
         } else {
             Trans::None
@@ -315,16 +320,6 @@ impl<'a, 'b> State<GameData<'a, 'b>> for ChangeControlState {
     }
 }
 
-fn cleanup(world: &mut World) {
-    world.exec(
-        |(entities, cleanup_store): (Entities, ReadStorage<CleanupControl>)| {
-            for (e, _) in (&*entities, &cleanup_store).join() {
-                entities.delete(e).unwrap();
-            }
-        },
-    );
-}
-
 fn load_button_images(
     world: &mut World,
     name: &'static str,
@@ -0,0 +1,275 @@
+use amethyst::config::Config;
+use amethyst::core::Time;
+use amethyst::ecs::*;
+use amethyst::input::{get_key, is_close_requested};
+use amethyst::prelude::Builder;
+use amethyst::renderer::{ElementState, Event, VirtualKeyCode};
+use amethyst::ui::{Anchor, FontAsset, FontHandle, TtfFormat, UiText, UiTransform};
+use amethyst::{GameData, State, StateData, Trans};
+use amethyst_extra::{AssetLoader, AssetLoaderInternal};
+
+use data::{game_time, CourseProgress, CourseScores, GameplayResults, GameplayStatus, Player};
+use utils::{load_beatmap, load_course, localize, user_data_path};
+use GamePlayState;
+
+/// File in the user data directory holding the high scores of the courses.
+pub const COURSE_SCORES_FILE: &str = "course_scores.ron";
+
+/// Seconds between two maps of a course.
+const INTERLUDE_DURATION: f32 = 3.0;
+
+enum CoursePhase {
+    /// Waiting before the next map, with the seconds left.
+    Interlude(f32),
+    Playing,
+    Results,
+}
+
+/// Plays the maps of a course back to back, then shows the course results.
+#[derive(new)]
+pub struct CourseState {
+    course: String,
+    #[new(value = "CoursePhase::Interlude(INTERLUDE_DURATION)")]
+    phase: CoursePhase,
+    #[new(default)]
+    font: Option<FontHandle>,
+    /// All entities of the screen.
+    #[new(default)]
+    entities: Vec<Entity>,
+}
+
+impl CourseState {
+    fn create_lines(&mut self, world: &mut World, lines: Vec<String>) {
+        let font = self.font.as_ref().unwrap().clone();
+        for (i, line) in lines.into_iter().enumerate() {
+            let (size, color) = if i == 0 {
+                (35.0, [1.0, 1.0, 1.0, 1.0])
+            } else {
+                (25.0, [0.8, 0.8, 0.8, 1.0])
+            };
+            let entity = world
+                .create_entity()
+                .with(UiText::new(font.clone(), line, color, size))
+                .with(
+                    UiTransform::new(
+                        format!("course_line_{}", i),
+                        Anchor::TopMiddle,
+                        0.0,
+                        80.0 + 40.0 * i as f32,
+                        -3.0,
+                        700.0,
+                        size,
+                        2,
+                    ).as_transparent(),
+                )
+                .build();
+            self.entities.push(entity);
+        }
+    }
+
+    fn clear_screen(&mut self, world: &mut World) {
+        self.entities.drain(..).for_each(|entity| {
+            world
+                .delete_entity(entity)
+                .expect("Failed to delete course entity.");
+        });
+    }
+
+    /// Shows the next map and the current health and score.
+    fn show_interlude(&mut self, world: &mut World) {
+        self.clear_screen(world);
+        let song = localize(world, "course-song");
+        let health = localize(world, "course-health");
+        let score = localize(world, "course-score");
+        let lines = {
+            let course = world.read_resource::<Option<CourseProgress>>();
+            let course = course.as_ref().unwrap();
+            vec![
+                course.course.name.clone(),
+                format!(
+                    "{} {}/{}: {}",
+                    song,
+                    course.index + 1,
+                    course.course.maps.len(),
+                    course.current_map().unwrap()
+                ),
+                format!("{}: {}", health, course.health),
+                format!("{}: {}", score, course.total_score()),
+            ]
+        };
+        self.create_lines(world, lines);
+        self.phase = CoursePhase::Interlude(INTERLUDE_DURATION);
+    }
+
+    /// Saves the total score and shows the results of every map with the high scores.
+    fn show_results(&mut self, world: &mut World) {
+        self.clear_screen(world);
+        let completed = localize(world, "course-completed");
+        let failed = localize(world, "course-failed");
+        let total = localize(world, "course-total");
+        let high_scores = localize(world, "course-high-scores");
+        let hint = localize(world, "course-continue");
+
+        let mut lines = vec![];
+        let (name, score) = {
+            let course = world.read_resource::<Option<CourseProgress>>();
+            let course = course.as_ref().unwrap();
+            let all_completed = course.results.len() == course.course.maps.len() && course
+                .results
+                .iter()
+                .all(|(_, _, status)| *status == GameplayStatus::Completed);
+            lines.push(if all_completed { completed } else { failed });
+            for (map, score, _) in &course.results {
+                lines.push(format!("{}: {}", map, score));
+            }
+            lines.push(format!("{}: {}", total, course.total_score()));
+            (course.course.name.clone(), course.total_score())
+        };
+
+        let path = user_data_path(COURSE_SCORES_FILE);
+        let mut scores = CourseScores::load_no_fallback(&path).unwrap_or_default();
+        let rank = scores.insert(&name, score);
+        if let Err(e) = scores.write(&path) {
+            error!("Failed to save course scores to {}: {}", path, e);
+        }
+
+        lines.push(high_scores);
+        for (i, s) in scores.scores[&name].iter().enumerate() {
+            let new = if Some(i) == rank { " <" } else { "" };
+            lines.push(format!("{}. {}{}", i + 1, s, new));
+        }
+        lines.push(hint);
+
+        self.create_lines(world, lines);
+        self.phase = CoursePhase::Results;
+    }
+
+    /// Loads the current map of the course like the map selection does.
+    fn start_map<'a, 'b>(&mut self, world: &mut World) -> Trans<GameData<'a, 'b>> {
+        let map = world
+            .read_resource::<Option<CourseProgress>>()
+            .as_ref()
+            .and_then(|c| c.current_map().cloned())
+            .unwrap();
+        match load_beatmap(map, world) {
+            Some(mut beatmap) => {
+                beatmap.runtime_start = game_time(world) + 3.0;
+                world.add_resource(beatmap);
+                self.clear_screen(world);
+                self.phase = CoursePhase::Playing;
+                Trans::Push(Box::new(GamePlayState::new()))
+            }
+            None => {
+                error!("Failed to load a map of course {}, aborting", self.course);
+                Trans::Pop
+            }
+        }
+    }
+}
+
+impl<'a, 'b> State<GameData<'a, 'b>> for CourseState {
+    fn on_start(&mut self, data: StateData<GameData>) {
+        debug!("Starting CourseState");
+        let world = data.world;
+
+        let font = world
+            .write_resource::<AssetLoader>()
+            .load(
+                "ui/square.ttf",
+                TtfFormat,
+                (),
+                &mut world.write_resource::<AssetLoaderInternal<FontAsset>>(),
+                &mut world.write_resource(),
+                &mut world.read_resource(),
+            )
+            .expect("Failed to load font");
+        self.font = Some(font);
+
+        let course = load_course(&self.course, &world.read_resource::<AssetLoader>());
+        match course {
+            Some(course) if !course.maps.is_empty() => {
+                *world.write_resource::<Option<CourseProgress>>() =
+                    Some(CourseProgress::new(course, Player::default().health));
+                self.show_interlude(world);
+            }
+            _ => {
+                error!("Course {} has no maps", self.course);
+                let lines = vec![
+                    localize(world, "course-failed"),
+                    localize(world, "course-continue"),
+                ];
+                self.create_lines(world, lines);
+                self.phase = CoursePhase::Results;
+            }
+        }
+    }
+
+    fn on_resume(&mut self, data: StateData<GameData>) {
+        let world = data.world;
+        // Courses are played alone.
+        let result = world.read_resource::<GameplayResults>().players[0].clone();
+        {
+            let mut course = world.write_resource::<Option<CourseProgress>>();
+            let course = course.as_mut().unwrap();
+            let map = course.current_map().unwrap().clone();
+            // A map left before its end counts as failed.
+            let status = match result.status {
+                GameplayStatus::Running => GameplayStatus::Failed,
+                status => status,
+            };
+            course.results.push((map, result.score(), status));
+            course.health = result.health;
+            course.index += 1;
+        }
+
+        let over = world
+            .read_resource::<Option<CourseProgress>>()
+            .as_ref()
+            .unwrap()
+            .is_over();
+        if over {
+            self.show_results(world);
+        } else {
+            self.show_interlude(world);
+        }
+    }
+
+    fn on_stop(&mut self, data: StateData<GameData>) {
+        self.clear_screen(data.world);
+        *data.world.write_resource::<Option<CourseProgress>>() = None;
+    }
+
+    fn handle_event(&mut self, _data: StateData<GameData>, event: Event) -> Trans<GameData<'a, 'b>> {
+        if is_close_requested(&event) {
+            return Trans::Quit;
+        }
+        match (get_key(&event), &mut self.phase) {
+            (Some((VirtualKeyCode::Escape, ElementState::Pressed)), _) => Trans::Pop,
+            (Some((VirtualKeyCode::Return, ElementState::Pressed)), CoursePhase::Results) => {
+                Trans::Pop
+            }
+            (Some((VirtualKeyCode::Return, ElementState::Pressed)), CoursePhase::Interlude(t)) => {
+                *t = 0.0;
+                Trans::None
+            }
+            _ => Trans::None,
+        }
+    }
+
+    fn update(&mut self, data: StateData<GameData>) -> Trans<GameData<'a, 'b>> {
+        data.data.update(data.world);
+
+        let start = match self.phase {
+            CoursePhase::Interlude(ref mut t) => {
+                *t -= data.world.read_resource::<Time>().delta_seconds();
+                *t <= 0.0
+            }
+            _ => false,
+        };
+        if start {
+            self.start_map(data.world)
+        } else {
+            Trans::None
+        }
+    }
+}
@@ -1,24 +1,29 @@
 use amethyst::assets::*;
 use amethyst::audio::{AudioSink, Mp3Format, Source as AudioSource, SourceHandle};
 use amethyst::core::cgmath::{Matrix4, Ortho, Vector3};
-use amethyst::core::{GlobalTransform, Time, Transform};
+use amethyst::core::{Clocks, GlobalTransform, Time, Transform, GAME_CLOCK};
 use amethyst::ecs::prelude::*;
 use amethyst::input::{get_key, is_close_requested};
 use amethyst::renderer::{
     Camera, ElementState, Event, Projection, ScreenDimensions, SpriteRender, SpriteSheetSet,
     Transparent, VirtualKeyCode,
 };
+use amethyst::ui::{FontAsset, TtfFormat};
+use amethyst::utils::state_scoped::StateScoped;
 use amethyst::{GameData, State, StateData, Trans};
 use amethyst_extra::*;
+use dirty::Dirty;
 
 use std::collections::VecDeque;
+use std::time::SystemTime;
 
 use data::*;
 use systems::*;
-use utils::{Music, SpriteScenePrefab};
-use CameraFollowPlayerSystem;
-use GameplayInputSystem;
-use GameplayResult;
+use utils::{
+    apply_audio_settings, finish_run, load_beatmap_data, load_ghost_run, load_ghost_sprite_sheet,
+    load_hit_sounds, modified_time, resolve_map_file, save_ghost_run, Music, SpriteScenePrefab,
+};
+use GameplayResults;
 use GameplayStatus;
 use Player;
 use ScoreState;
@@ -26,15 +31,9 @@ use ScoreState;
 /// Where the player is running out of space
 #[derive(Default, new)]
 pub struct GamePlayState {
-    /// State specific dispatcher
-    #[new(default)]
-    dispatcher: Option<Dispatcher<'static, 'static>>,
     /// Whether or not the game is paused.
     #[new(value = "false")]
     paused: bool,
-    /// All entities in game.
-    #[new(default)]
-    entities: Vec<Entity>,
     /// Camera entity
     #[new(default)]
     camera: Option<Entity>,
@@ -47,32 +46,19 @@ pub struct GamePlayState {
     /// The handle to the music asset
     #[new(default)]
     music: Option<SourceHandle>,
+    /// The root entity of the scene prefab.
+    #[new(default)]
+    scene: Option<Entity>,
+    /// The `map.ron` and `scene.ron` of the map with their last modification, to reload the
+    /// chart when they change.
+    #[new(default)]
+    chart_files: Vec<(String, Option<SystemTime>)>,
+    /// The chart is being reloaded while the song keeps playing.
+    #[new(value = "false")]
+    reloading: bool,
 }
 
 impl GamePlayState {
-    fn initialize_dispatcher(&mut self, world: &mut World) {
-        let mut dispatcher_builder = DispatcherBuilder::new();
-
-        dispatcher_builder.add(GameplayInputSystem::new(), "gameplay_input_system", &[]);
-        dispatcher_builder.add(PlayerMovementSystem::new(), "player_movement", &[]);
-
-        //TODO test it
-        dispatcher_builder.add(
-            CameraFollowPlayerSystem,
-            "camera_follow_player_system",
-            &["gameplay_input_system"],
-        );
-        dispatcher_builder.add(BeatPointAnimationSystem, "beatpoint_animation", &[]);
-
-        let mut dispatcher = dispatcher_builder.build();
-        dispatcher.setup(&mut world.res);
-        self.dispatcher = Some(dispatcher);
-    }
-
-    fn terminate_dispatcher(&mut self) {
-        self.dispatcher = None;
-    }
-
     fn initialize_entities(&mut self, world: &mut World) {
         let mut progress_counter = ProgressCounter::default();
 
@@ -85,70 +71,67 @@ impl GamePlayState {
         {
             let beatmap = &world.read_resource::<BeatMap>();
             beatmap_name = beatmap.name.clone();
-            scene_path = world
-                .read_resource::<AssetLoader>()
-                .resolve_path(&format!("maps/{}/scene.ron", beatmap_name))
+            scene_path = resolve_map_file(&world.read_resource(), &beatmap_name, "scene.ron")
                 .expect(&format!(
                     "Please ensure map.ron::name == name of the folder containing map.ron for map {}",
                     beatmap_name
                 ));
             beat_points = beatmap.beat_points.clone();
         }
+        // The speed modifier scales the chart, the runners follow the beat points.
+        let scroll_speed = world
+            .read_resource::<Dirty<UserSettings>>()
+            .read()
+            .scroll_speed as f64
+            * world.read_resource::<Modifiers>().speed as f64;
 
         // BeatPoints
-        let mut beatpoint_entities = beat_points
-            .into_iter()
-            .map(|beat_point| {
-                let mut transform = Transform::default();
-                transform.translation =
-                    Vector3::new((beat_point.time * 220.0 + 50.) as f32, 140., 1.);
-                world
-                    .create_entity()
-                    .with(beat_point)
-                    .with(transform)
-                    .with(GlobalTransform::default())
-                    .build()
-            })
-            .collect::<Vec<Entity>>();
-
-        self.entities.extend(beatpoint_entities);
+        for beat_point in beat_points {
+            let mut transform = Transform::default();
+            transform.translation = Vector3::new(
+                (beat_point.time * 220.0 * scroll_speed + 50.) as f32,
+                140.,
+                1.,
+            );
+            world
+                .create_entity()
+                .with(beat_point)
+                .with(transform)
+                .with(GlobalTransform::default())
+                .with(StateScoped::new())
+                .build();
+        }
 
         // === Background prefab === //
 
         // Load the map background!
-        let prefab_handle = world.exec(|loader: PrefabLoader<SpriteScenePrefab>| {
-            // might fail with abs path??
-            loader.load(scene_path, RonFormat, (), &mut progress_counter)
-        });
-        let background_entity = world
-            .create_entity()
-            .with(prefab_handle)
-            .with(Transparent)
-            .build();
-        self.entities.push(background_entity);
+        self.load_scene(world, scene_path, &mut progress_counter);
 
-        // === Player === //
+        // === Players === //
         let player_prefab_path = world
             .read_resource::<AssetLoader>()
             .resolve_path("prefabs/player.ron")
             .expect("Please ensure prefabs/player.ron exists inside assets/<directory>/");
 
-        // Load the player!
-        let prefab_handle = world.exec(|loader: PrefabLoader<SpriteScenePrefab>| {
-            loader.load(player_prefab_path, RonFormat, (), &mut progress_counter)
-        });
-        let player = world
-            .create_entity()
-            .with(Player::default())
-            .with(Transparent)
-            .with(prefab_handle)
-            .build();
-
-        self.entities.push(player);
-
-        *world.write_resource::<AnimationStateRes>() = AnimationStateRes {
-            state: AnimationState::Running,
-        };
+        // Load the players!
+        let player_count = world.read_resource::<GameplayResults>().players.len();
+        for index in 0..player_count {
+            let prefab_handle = world.exec(|loader: PrefabLoader<SpriteScenePrefab>| {
+                loader.load(player_prefab_path.clone(), RonFormat, (), &mut progress_counter)
+            });
+            world
+                .create_entity()
+                .with(Player {
+                    index,
+                    health: starting_health(world),
+                    animation: AnimationState::Running,
+                    ..Default::default()
+                })
+                .with(Transparent)
+                .with(prefab_handle)
+                .with(StateScoped::new())
+                .build();
+        }
 
         let music = world.exec(
             |(resolver, loader, sources): (
@@ -156,7 +139,7 @@ impl GamePlayState {
                 ReadExpect<Loader>,
                 Read<AssetStorage<AudioSource>>,
             )| {
-                let path = resolver.resolve_path(&format!("maps/{}/audio.mp3", beatmap_name))
+                let path = resolve_map_file(&resolver, &beatmap_name, "audio.mp3")
                 // TODO use some fallback
                 .unwrap_or_else(|| "assets/base/maps/level1/audio.mp3".to_owned());
                 loader.load(path, Mp3Format, (), &mut progress_counter, &sources)
@@ -165,15 +148,164 @@ impl GamePlayState {
         self.music = Some(music);
 
         self.progress_counter = Some(progress_counter);
+        self.watch_chart_files(world);
     }
 
-    fn terminate_entities(&mut self, world: &mut World) {
-        self.entities.drain(..).for_each(|entity| {
-            world
-                .delete_entity(entity)
-                .expect("Failed to delete game entity.")
+    /// Adds the ghost of the best run of the map, when playing alone.
+    fn create_ghost(&mut self, world: &mut World) {
+        let enabled = world.read_resource::<Dirty<UserSettings>>().read().ghost;
+        if !enabled || world.read_resource::<GameplayResults>().players.len() != 1 {
+            return;
+        }
+        let name = world.read_resource::<BeatMap>().name.clone();
+        let run = match load_ghost_run(&name) {
+            Some(run) => run,
+            None => return,
+        };
+        let sprite_sheet = match load_ghost_sprite_sheet(world) {
+            Some(sprite_sheet) => sprite_sheet,
+            None => {
+                warn!("Failed to create the ghost sprite sheet");
+                return;
+            }
+        };
+        // The ghost starts where the player does.
+        let transform = (&world.read_storage::<Player>(), &world.read_storage::<Transform>())
+            .join()
+            .next()
+            .map(|(_, t)| t.clone())
+            .unwrap_or_default();
+        world
+            .create_entity()
+            .with(Ghost::new(&run))
+            .with(transform)
+            .with(GlobalTransform::default())
+            .with(SpriteRender {
+                sprite_sheet,
+                sprite_number: 0,
+                flip_horizontal: false,
+                flip_vertical: false,
+            })
+            .with(Transparent)
+            .with(StateScoped::new())
+            .build();
+    }
+
+    fn load_scene(&mut self, world: &mut World, path: String, progress: &mut ProgressCounter) {
+        let prefab_handle = world.exec(|loader: PrefabLoader<SpriteScenePrefab>| {
+            // might fail with abs path??
+            loader.load(path, RonFormat, (), progress)
         });
-        exec_removal(&world.read_resource(), &world.read_storage(), 1);
+        let background_entity = world
+            .create_entity()
+            .with(prefab_handle)
+            .with(Transparent)
+            .with(StateScoped::new())
+            .build();
+        self.scene = Some(background_entity);
+    }
+
+    /// Remembers the modification time of the chart files.
+    fn watch_chart_files(&mut self, world: &World) {
+        let name = world.read_resource::<BeatMap>().name.clone();
+        let asset_loader = world.read_resource::<AssetLoader>();
+        self.chart_files = ["map.ron", "scene.ron"]
+            .iter()
+            .filter_map(|file| resolve_map_file(&asset_loader, &name, file))
+            .map(|path| {
+                let modified = modified_time(&path);
+                (path, modified)
+            })
+            .collect();
+    }
+
+    fn chart_files_changed(&self) -> bool {
+        self.chart_files
+            .iter()
+            .any(|(path, modified)| modified_time(path) != *modified)
+    }
+
+    /// Rebuilds the beat points and the scene from the chart files, while the song keeps playing.
+    fn reload_chart(&mut self, world: &mut World) {
+        info!("The chart changed, reloading it.");
+        self.watch_chart_files(world);
+        let name = world.read_resource::<BeatMap>().name.clone();
+        if let Some(data) = load_beatmap_data(&name, &world.read_resource()) {
+            let mut beatmap = world.write_resource::<BeatMap>();
+            // Keeps the song position when the offset changed.
+            beatmap.runtime_start += data.audio_offset - beatmap.audio_offset;
+            beatmap.audio_offset = data.audio_offset;
+            beatmap.events = data.events;
+        }
+
+        let mut removal_ids = world.read_resource::<Storyboard>().removal_ids.clone();
+        removal_ids.push(1);
+        world.exec(
+            |(entities, removals, players): (
+                Entities,
+                ReadStorage<Removal<i32>>,
+                ReadStorage<Player>,
+            )| {
+                // The player shares the removal id of the scene.
+                for (entity, removal, _) in (&*entities, &removals, !&players).join() {
+                    if removal_ids.contains(removal.id()) {
+                        if let Err(err) = entities.delete(entity) {
+                            error!("Failed to delete scene entity: {:?}", err);
+                        }
+                    }
+                }
+            },
+        );
+        if let Some(scene) = self.scene.take() {
+            world
+                .delete_entity(scene)
+                .expect("Failed to delete the scene.");
+        }
+
+        let scene_path = resolve_map_file(&world.read_resource(), &name, "scene.ron");
+        match scene_path {
+            Some(path) => {
+                let mut progress_counter = ProgressCounter::default();
+                self.load_scene(world, path, &mut progress_counter);
+                self.progress_counter = Some(progress_counter);
+            }
+            None => {
+                error!("Failed to find the scene of map {}", name);
+                self.progress_counter = None;
+            }
+        }
+        self.loaded = false;
+        self.reloading = true;
+    }
+
+    /// Skips what was already played of the reloaded chart.
+    fn resume_chart(&mut self, world: &mut World) {
+        let rel_time = {
+            let beatmap = world.read_resource::<BeatMap>();
+            game_time(world) - beatmap.runtime_start
+        };
+        let results = world.read_resource::<GameplayResults>();
+        for player in (&mut world.write_storage::<Player>()).join() {
+            let judged = results.players[player.index]
+                .results
+                .last()
+                .map(|(time, _)| *time)
+                .unwrap_or(::std::f64::NEG_INFINITY);
+            player
+                .beat_points
+                .retain(|b| b.time > rel_time && b.time > judged);
+        }
+        // The past events changing the map are replayed on the next frame, the others are dropped.
+        world
+            .write_resource::<Storyboard>()
+            .events
+            .retain(|e| e.time > rel_time || !e.event.is_transient());
+    }
+
+    /// The entities of the map are `StateScoped`, the `StateMachine` deletes them after `on_stop`.
+    fn terminate_entities(&mut self, world: &mut World) {
+        self.scene = None;
+        world.add_resource(Storyboard::default());
     }
 }
 
@@ -182,14 +314,34 @@ impl<'a, 'b> State<GameData<'a, 'b>> for GamePlayState {
         debug!("Starting GamePlayState");
         // Reset game play result
         self.loaded = false;
-        data.world.add_resource(GameplayResult::default());
-        self.initialize_dispatcher(&mut data.world);
+        self.reloading = false;
+        let modifiers = data.world.read_resource::<Modifiers>().clone();
+        let health = starting_health(data.world);
+        // Courses are played alone.
+        let player_count = if modifiers.versus
+            && data.world.read_resource::<Option<CourseProgress>>().is_none()
+        {
+            VERSUS_PLAYERS
+        } else {
+            1
+        };
+        let result = GameplayResult {
+            modifiers,
+            health,
+            ..Default::default()
+        };
+        data.world.add_resource(GameplayResults {
+            players: vec![result; player_count],
+        });
         self.initialize_entities(&mut data.world);
     }
 
     fn on_stop(&mut self, mut data: StateData<GameData>) {
+        if let Some(clock) = data.world.write_resource::<Clocks>().get_mut(GAME_CLOCK) {
+            clock.resume();
+        }
+        data.data.disable_group(GAMEPLAY_SYSTEMS);
         self.terminate_entities(&mut data.world);
-        self.terminate_dispatcher();
     }
 
     fn handle_event(&mut self, data: StateData<GameData>, event: Event) -> Trans<GameData<'a, 'b>> {
@@ -201,42 +353,132 @@ impl<'a, 'b> State<GameData<'a, 'b>> for GamePlayState {
             Some((VirtualKeyCode::Escape, ElementState::Pressed)) => Trans::Pop,
             Some((VirtualKeyCode::Space, ElementState::Pressed)) => {
                 self.paused = !self.paused;
+                // The ui keeps running on its own clock.
+                if let Some(clock) = data.world.write_resource::<Clocks>().get_mut(GAME_CLOCK) {
+                    if self.paused {
+                        clock.pause();
+                    } else {
+                        clock.resume();
+                    }
+                }
+                // The song stops with the chart. The sink is missing without audio output.
+                if data.world.res.has_value::<AudioSink>() {
+                    let sink = data.world.read_resource::<AudioSink>();
+                    if self.paused {
+                        sink.pause();
+                    } else {
+                        sink.play();
+                    }
+                }
                 if self.paused {
                     info!("Game is paused.");
                 } else {
+                    // The drift is measured again from now on.
+                    data.world.write_resource::<DebugOverlay>().clock_start = None;
                     info!("Game is running.");
                 }
 
                 Trans::None
             }
+            Some((VirtualKeyCode::F3, ElementState::Pressed)) => {
+                let mut overlay = data.world.write_resource::<DebugOverlay>();
+                overlay.enabled = !overlay.enabled;
+                Trans::None
+            }
             _ => Trans::None,
         }
     }
 
     fn update(&mut self, mut data: StateData<GameData>) -> Trans<GameData<'a, 'b>> {
-        data.data.update(data.world);
-
         // TODO: Probably render something on screen to say "the game is paused"
         // Should we also add an entity with a `Paused` component that indicates the paused state?
-        if !self.paused && self.loaded {
-            self.dispatcher.as_mut().unwrap().dispatch(&data.world.res);
+        data.data
+            .set_group_enabled(GAMEPLAY_SYSTEMS, !self.paused && self.loaded);
+        data.data.update(data.world);
+
+        // Charters can edit the chart while playing it.
+        let check_files = {
+            let frame = data.world.read_resource::<Time>().frame_number();
+            data.world
+                .read_resource::<HotReloadStrategy>()
+                .needs_reload(frame)
+        };
+        if (self.loaded || self.reloading) && check_files && self.chart_files_changed() {
+            self.reload_chart(data.world);
+        }
+
+        let failed = self
+            .progress_counter
+            .as_ref()
+            .map(|p| p.complete() == Completion::Failed)
+            .unwrap_or(false);
+        if failed && self.reloading {
+            // Waits for the next change of the files.
+            error!(
+                "Failed to reload the scene: {:?}",
+                self.progress_counter.as_ref().unwrap().errors()
+            );
+            self.progress_counter = None;
         }
+        let complete = self
+            .progress_counter
+            .as_ref()
+            .map(|p| p.is_complete())
+            .unwrap_or(false);
 
         // Map beatpoint visual components to beatmap logical beatpoints
-        if self.progress_counter.as_ref().unwrap().is_complete() && !self.loaded {
+        if complete && !self.loaded {
             self.loaded = true;
+            let modifiers = data.world.read_resource::<Modifiers>().clone();
+            // The sprites read their direction from the components.
+            modifiers.apply((&mut data.world.write_storage::<BeatPoint>()).join());
             let mut beatpoints = Vec::<BeatPoint>::new();
             for (b,) in (&data.world.read_storage::<BeatPoint>(),).join() {
                 beatpoints.push(b.clone());
             }
             beatpoints.sort_by(|a, b| a.time.partial_cmp(&b.time).unwrap());
+            let map_name = data.world.read_resource::<BeatMap>().name.clone();
+            let hit_sounds = load_hit_sounds(&data.world.res, &map_name, &beatpoints);
+            data.world.add_resource(hit_sounds);
+            for player in (&mut data.world.write_storage::<Player>()).join() {
+                player.beat_points = beatpoints.iter().cloned().collect();
+            }
             data.world.write_resource::<BeatMap>().beat_points = beatpoints.into();
 
+            let font = data.world.write_resource::<AssetLoader>().load(
+                "ui/square.ttf",
+                TtfFormat,
+                (),
+                &mut data.world.write_resource::<AssetLoaderInternal<FontAsset>>(),
+                &mut data.world.write_resource(),
+                &mut data.world.read_resource(),
+            );
+            data.world.write_resource::<DebugOverlay>().font = font.clone();
+            let events = data.world.read_resource::<BeatMap>().events.clone();
+            data.world.add_resource(Storyboard::new(events, font));
+
+            if self.reloading {
+                self.reloading = false;
+                self.resume_chart(data.world);
+                return Trans::None;
+            }
+
+            self.create_ghost(data.world);
+
             // Play music
             data.world
                 .add_resource(Music::new(self.music.as_ref().unwrap().clone()));
-            let cur_time = data.world.read_resource::<Time>().absolute_time_seconds();
-            data.world.write_resource::<BeatMap>().runtime_start = cur_time;
+            let cur_time = game_time(data.world);
+            let note_offset = data
+                .world
+                .read_resource::<Dirty<UserSettings>>()
+                .read()
+                .note_offset;
+            {
+                // The chart starts `audio_offset` seconds into the song.
+                let mut beatmap = data.world.write_resource::<BeatMap>();
+                beatmap.runtime_start = cur_time + beatmap.audio_offset + note_offset;
+            }
 
             // ._. this doesn't work to restart the music on re-entering the game.
             // let source_store = data.world.read_resource::<AssetStorage<AudioSource>>();
@@ -246,21 +488,54 @@ impl<'a, 'b> State<GameData<'a, 'b>> for GamePlayState {
             //     .unwrap();
 
             // Resume music
+            apply_audio_settings(data.world);
             data.world.read_resource::<AudioSink>().play();
         }
 
-        let gameplay_result = &data.world.read_resource::<GameplayResult>();
-        match gameplay_result.status {
+        let status = data.world.read_resource::<GameplayResults>().status();
+        match status {
             GameplayStatus::Failed | GameplayStatus::Completed => {
+                // Solo runs count in the stats, and a completed one becomes the ghost of the
+                // map if it is the best one.
+                let solo = {
+                    let results = data.world.read_resource::<GameplayResults>();
+                    if results.players.len() == 1 {
+                        Some(results.players[0].clone())
+                    } else {
+                        None
+                    }
+                };
+                if let Some(result) = solo {
+                    let map = data.world.read_resource::<BeatMap>().name.clone();
+                    finish_run(data.world, &map, &result);
+                    if result.status == GameplayStatus::Completed {
+                        save_ghost_run(&map, result.ghost_run());
+                    }
+                }
+
                 // Stop playing music
                 data.world.read_resource::<AudioSink>().pause();
-                *data.world.write_resource::<AnimationStateRes>() = AnimationStateRes {
-                    state: AnimationState::None,
-                };
+                for player in (&mut data.world.write_storage::<Player>()).join() {
+                    player.animation = AnimationState::None;
+                }
 
-                Trans::Switch(Box::new(ScoreState::new()))
+                if data.world.read_resource::<Option<CourseProgress>>().is_some() {
+                    // The course shows the results once every map is played.
+                    Trans::Pop
+                } else {
+                    Trans::Switch(Box::new(ScoreState::new()))
+                }
             }
             _ => Trans::None,
         }
     }
 }
+
+/// Health of the player when the map starts, carried over from the previous map of a course.
+fn starting_health(world: &World) -> i32 {
+    world
+        .read_resource::<Option<CourseProgress>>()
+        .as_ref()
+        .map(|course| course.health)
+        .unwrap_or(Player::default().health)
+}
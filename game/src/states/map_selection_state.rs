@@ -1,5 +1,7 @@
-use amethyst::core::{GlobalTransform, Time, Transform};
+use amethyst::assets::{Handle, Loader};
+use amethyst::core::{GlobalTransform, Transform};
 use amethyst::ecs::prelude::*;
+use amethyst::prelude::Builder;
 use amethyst::input::{is_close_requested, is_key_down};
 use amethyst::renderer::{Camera, PngFormat, Projection, Texture};
 use amethyst::renderer::{ElementState, Event, VirtualKeyCode};
@@ -12,14 +14,26 @@ use amethyst::ui::{
 };
 use amethyst::{GameData, State, StateData, Trans};
 
-use amethyst_extra::{AssetLoader, AssetLoaderInternal};
+use amethyst_extra::{AssetLoader, AssetLoaderInternal, Console};
+use dirty::Dirty;
 
 use super::map_selection::*;
-use data::BeatPoint;
-use utils::{list_beatmaps, load_beatmap};
+use data::{game_time, BeatPoint, LocalizedText, Localization, Modifier, Modifiers,
+           UserSettings, MODIFIERS};
+use utils::{
+    apply_audio_settings, button_text_entity, list_beatmaps, list_courses, list_languages,
+    load_beatmap, load_chart, load_course, localize, localize_button, rate_chart, resolve_map_file,
+    set_language,
+};
+use CourseState;
 use GamePlayState;
+use ModsState;
+use SettingsState;
 use MapSelectionEvent;
 
+/// Name of the `MapSelectionState`, at the bottom of the state stack.
+pub const MAP_SELECTION_STATE: &str = "map_selection";
+
 /// Where the player chooses which song to play
 #[derive(Default, new)]
 pub struct MapSelectionState {
@@ -35,6 +49,17 @@ pub struct MapSelectionState {
     controls_button: Option<Entity>,
     #[new(default)]
     ui_events: Option<ReaderId<UiEvent>>,
+    #[new(default)]
+    language_button: Option<Entity>,
+    /// Buttons toggling a gameplay modifier, with the modifier they control.
+    #[new(default)]
+    modifier_buttons: Vec<(Entity, Modifier)>,
+    /// Labels of the modifier buttons.
+    #[new(default)]
+    modifier_labels: Vec<Entity>,
+    /// Buttons starting a course, with the course they start.
+    #[new(default)]
+    course_buttons: Vec<(Entity, String)>,
 }
 
 impl MapSelectionState {
@@ -50,6 +75,30 @@ impl MapSelectionState {
         self.map_selection_event_reader.take();
     }
 
+    /// Reloads the current language, which might come from a different asset pack now.
+    fn reload_language(&mut self, world: &mut World) {
+        let language = world.read_resource::<Localization>().language.clone();
+        set_language(world, &language);
+    }
+
+    /// Switches to the next available language.
+    fn next_language(&mut self, world: &mut World) {
+        let languages = list_languages(&world.read_resource::<AssetLoader>());
+        let current = world.read_resource::<Localization>().language.clone();
+        let next = languages
+            .iter()
+            .position(|l| l == &current)
+            .map(|i| (i + 1) % languages.len())
+            .and_then(|i| languages.get(i))
+            .or(languages.first())
+            .cloned();
+        if let Some(language) = next {
+            info!("Switching language to {}", language);
+            set_language(world, &language);
+            world.write_resource::<Dirty<UserSettings>>().write().language = language;
+        }
+    }
+
     /// Reloads the beatmaps and recreates the menu.
     fn reload_menu(&mut self, world: &mut World) {
         let beatmaps = list_beatmaps(&mut world.write_resource::<AssetLoader>());
@@ -92,6 +141,21 @@ impl MapSelectionState {
         self.controls_button = Some(controls_button);*/
 
         let font = self.font.as_ref().unwrap();
+
+        let language_text = localize(world, "language-name");
+        let language_button = UiButtonBuilder::new("language_button", language_text)
+            .with_position(-110.0, 36.0)
+            .with_text_color([0.7; 4])
+            .with_hover_text_color([1.0; 4])
+            .with_press_text_color([0.5; 4])
+            .with_font_size(25.0)
+            .with_anchor(Anchor::TopRight)
+            .with_font(font.clone())
+            .with_size(200.0, 40.0)
+            .build_from_world(world);
+        localize_button(world, language_button, "language-name");
+        self.language_button = Some(language_button);
+
         // let mut index = 0;
         self.buttons = beatmaps
             .iter()
@@ -99,36 +163,8 @@ impl MapSelectionState {
             .map(|(i, beatmap)| {
                 // index += 1;
                 debug!("Index...{}", i as f32);
-                let img = {
-                    world
-                        .write_resource::<AssetLoader>()
-                        .load(
-                            &format!("maps/level{}/level.png", (i as f32 + 1.0)),
-                            // "ui/level1.png",
-                            PngFormat,
-                            Default::default(),
-                            &mut world.write_resource::<AssetLoaderInternal<Texture>>(),
-                            &mut world.write_resource(),
-                            &mut world.read_resource(),
-                        )
-                        .expect("Failed to load retry")
-                        .clone()
-                };
-                let imghover = {
-                    world
-                        .write_resource::<AssetLoader>()
-                        .load(
-                            &format!("maps/level{}/levelhover.png", (i as f32 + 1.0)),
-                            // "ui/level1hover.png",
-                            PngFormat,
-                            Default::default(),
-                            &mut world.write_resource::<AssetLoaderInternal<Texture>>(),
-                            &mut world.write_resource(),
-                            &mut world.read_resource(),
-                        )
-                        .expect("Failed to load retry")
-                        .clone()
-                };
+                let img = load_map_icon(world, beatmap, "level.png");
+                let imghover = load_map_icon(world, beatmap, "levelhover.png");
 
                 let entity = UiButtonBuilder::new(beatmap, "")
                     .with_position(0.0,-400.0 + 100.0 * ((i as f32 + 1.0) * 2.0))
@@ -152,6 +188,39 @@ impl MapSelectionState {
                 entity
             })
             .collect::<Vec<Entity>>();
+
+        let difficulty = localize(world, "map-difficulty");
+        for (i, beatmap) in beatmaps.iter().enumerate() {
+            let rating = load_chart(beatmap, &world.read_resource::<AssetLoader>())
+                .map(|chart| format!("{} {:.1}", difficulty, rate_chart(&chart).rating))
+                .unwrap_or_default();
+            let text = world
+                .create_entity()
+                .with(UiText::new(
+                    font.clone(),
+                    rating,
+                    [1.0, 1.0, 1.0, 1.0],
+                    20.0,
+                ))
+                .with(
+                    UiTransform::new(
+                        format!("{}_difficulty", beatmap),
+                        Anchor::Middle,
+                        170.0,
+                        -400.0 + 100.0 * ((i as f32 + 1.0) * 2.0),
+                        -3.0,
+                        150.0,
+                        20.0,
+                        2,
+                    ).as_transparent(),
+                )
+                .build();
+            self.buttons.push(text);
+        }
+
+        self.create_modifier_buttons(world);
+        self.create_course_buttons(world);
+
         /*self.buttons.push(controls_button);
 
         self.ui_events = Some(
@@ -161,7 +230,115 @@ impl MapSelectionState {
         );*/
     }
 
+    /// Creates one label and one toggle button per gameplay modifier.
+    fn create_modifier_buttons(&mut self, world: &mut World) {
+        let font = self.font.as_ref().unwrap().clone();
+        let modifiers = world.read_resource::<Modifiers>().clone();
+        for (i, modifier) in MODIFIERS.iter().enumerate() {
+            let y = 100.0 + 40.0 * i as f32;
+
+            let label_text = localize(world, modifier.label_key());
+            let label = world
+                .create_entity()
+                .with(UiText::new(
+                    font.clone(),
+                    label_text,
+                    [1.0, 1.0, 1.0, 1.0],
+                    20.0,
+                ))
+                .with(LocalizedText::new(modifier.label_key().to_owned()))
+                .with(
+                    UiTransform::new(
+                        format!("{}_label", modifier.label_key()),
+                        Anchor::TopLeft,
+                        120.0,
+                        y,
+                        -3.0,
+                        200.0,
+                        20.0,
+                        2,
+                    ).as_transparent(),
+                )
+                .build();
+            self.modifier_labels.push(label);
+
+            let button = UiButtonBuilder::new(
+                format!("{}_button", modifier.label_key()),
+                modifier.value_text(&modifiers),
+            ).with_position(260.0, y)
+                .with_text_color([0.7; 4])
+                .with_hover_text_color([1.0; 4])
+                .with_press_text_color([0.5; 4])
+                .with_font_size(20.0)
+                .with_anchor(Anchor::TopLeft)
+                .with_font(font.clone())
+                .with_size(70.0, 30.0)
+                .build_from_world(world);
+            self.modifier_buttons.push((button, *modifier));
+        }
+    }
+
+    /// Creates one button per course, under the language button.
+    fn create_course_buttons(&mut self, world: &mut World) {
+        let font = self.font.as_ref().unwrap().clone();
+        let courses = {
+            let asset_loader = world.read_resource::<AssetLoader>();
+            list_courses(&asset_loader)
+                .into_iter()
+                .filter_map(|c| load_course(&c, &asset_loader).map(|data| (c, data.name)))
+                .collect::<Vec<_>>()
+        };
+        for (i, (course, name)) in courses.into_iter().enumerate() {
+            let button = UiButtonBuilder::new(format!("course_{}", course), name)
+                .with_position(-110.0, 100.0 + 50.0 * i as f32)
+                .with_text_color([0.7; 4])
+                .with_hover_text_color([1.0; 4])
+                .with_press_text_color([0.5; 4])
+                .with_font_size(25.0)
+                .with_anchor(Anchor::TopRight)
+                .with_font(font.clone())
+                .with_size(200.0, 40.0)
+                .build_from_world(world);
+            self.course_buttons.push((button, course));
+        }
+    }
+
+    /// Toggles the modifier and updates the text of its button.
+    fn toggle_modifier(&mut self, world: &mut World, button: Entity, modifier: Modifier) {
+        let text = {
+            let mut modifiers = world.write_resource::<Modifiers>();
+            modifier.toggle(&mut modifiers);
+            modifier.value_text(&modifiers)
+        };
+        info!("Modifiers: {:?}", *world.read_resource::<Modifiers>());
+        if let Some(e) = button_text_entity(world, button) {
+            if let Some(ui_text) = world.write_storage::<UiText>().get_mut(e) {
+                ui_text.text = text;
+            }
+        }
+    }
+
     fn clear_menu(&mut self, world: &mut World) {
+        for (button, _) in self.course_buttons.drain(..) {
+            world
+                .delete_entity(button)
+                .expect("Failed to delete button.");
+        }
+        for (button, _) in self.modifier_buttons.drain(..) {
+            world
+                .delete_entity(button)
+                .expect("Failed to delete button.");
+        }
+        for label in self.modifier_labels.drain(..) {
+            world
+                .delete_entity(label)
+                .expect("Failed to delete label.");
+        }
+        if let Some(button) = self.language_button.take() {
+            world
+                .delete_entity(button)
+                .expect("Failed to delete button.");
+        }
         self.buttons.drain(..).for_each(|button| {
             world
                 .delete_entity(button)
@@ -170,6 +347,30 @@ impl MapSelectionState {
     }
 }
 
+/// Loads an image of a map folder or map archive, or the one of the first level if it has none.
+fn load_map_icon(world: &World, map: &str, file: &str) -> Handle<Texture> {
+    match resolve_map_file(&world.read_resource(), map, file) {
+        Some(path) => world.read_resource::<Loader>().load(
+            path,
+            PngFormat,
+            Default::default(),
+            (),
+            &world.read_resource(),
+        ),
+        None => world
+            .write_resource::<AssetLoader>()
+            .load(
+                &format!("maps/level1/{}", file),
+                PngFormat,
+                Default::default(),
+                &mut world.write_resource::<AssetLoaderInternal<Texture>>(),
+                &mut world.write_resource(),
+                &mut world.read_resource(),
+            )
+            .expect("Failed to load the map icon"),
+    }
+}
+
 impl<'a, 'b> State<GameData<'a, 'b>> for MapSelectionState {
     fn on_start(&mut self, mut data: StateData<GameData>) {
         debug!("Starting MapSelectionState");
@@ -196,14 +397,33 @@ impl<'a, 'b> State<GameData<'a, 'b>> for MapSelectionState {
                 })))
                 .with(global_transform)
                 .build();
+
+            // The developer console stays available for the whole game.
+            let font = data.world.write_resource::<AssetLoader>().load(
+                "ui/square.ttf",
+                TtfFormat,
+                (),
+                &mut data.world.write_resource::<AssetLoaderInternal<FontAsset>>(),
+                &mut data.world.write_resource(),
+                &mut data.world.read_resource(),
+            );
+            data.world.write_resource::<Console>().font = font;
         }
 
         self.initialize_map_selection_event_channel(&mut data.world);
+        apply_audio_settings(&mut data.world);
+        self.reload_language(&mut data.world);
         self.reload_menu(&mut data.world);
+        self.ui_events = Some(
+            data.world
+                .write_resource::<EventChannel<UiEvent>>()
+                .register_reader(),
+        );
     }
 
     fn on_resume(&mut self, mut data: StateData<GameData>) {
         debug!("Resuming MapSelectionState");
+        self.reload_language(&mut data.world);
         self.reload_menu(&mut data.world);
     }
 
@@ -221,6 +441,14 @@ impl<'a, 'b> State<GameData<'a, 'b>> for MapSelectionState {
             return Trans::Quit;
         }
 
+        if is_key_down(&event, VirtualKeyCode::M) {
+            return Trans::Push(Box::new(ModsState::new()));
+        }
+
+        if is_key_down(&event, VirtualKeyCode::O) {
+            return Trans::Push(Box::new(SettingsState::new()));
+        }
+
         /*if data
             .world
             .exec(|channel: Read<EventChannel<UiEvent>>| {
@@ -250,6 +478,37 @@ impl<'a, 'b> State<GameData<'a, 'b>> for MapSelectionState {
     fn update(&mut self, mut data: StateData<GameData>) -> Trans<GameData<'a, 'b>> {
         data.data.update(data.world);
 
+        let mut language_clicked = false;
+        let mut modifiers_clicked = vec![];
+        let mut course_clicked = None;
+        for ev in data
+            .world
+            .read_resource::<EventChannel<UiEvent>>()
+            .read(self.ui_events.as_mut().unwrap())
+        {
+            if let UiEventType::Click = ev.event_type {
+                if Some(ev.target) == self.language_button {
+                    language_clicked = true;
+                }
+                if let Some(button) = self.modifier_buttons.iter().find(|(e, _)| *e == ev.target) {
+                    modifiers_clicked.push(*button);
+                }
+                if let Some((_, course)) = self.course_buttons.iter().find(|(e, _)| *e == ev.target) {
+                    course_clicked = Some(course.clone());
+                }
+            }
+        }
+        if language_clicked {
+            self.next_language(&mut data.world);
+        }
+        for (button, modifier) in modifiers_clicked {
+            self.toggle_modifier(&mut data.world, button, modifier);
+        }
+        if let Some(course) = course_clicked {
+            debug!("Course selected: {}", course);
+            return Trans::Push(Box::new(CourseState::new(course)));
+        }
+
         // sorry for bad memory management, but this is a game jam
         let beatmap_name = {
             let map_selection_event_channel = data
@@ -272,8 +531,7 @@ impl<'a, 'b> State<GameData<'a, 'b>> for MapSelectionState {
             let mut beatmap =
                 load_beatmap(beatmap_name, &mut data.world).expect("Failed to load beatmap :(");
             // Maps should start in 3 seconds from now.
-            beatmap.runtime_start =
-                data.world.read_resource::<Time>().absolute_time_seconds() + 3.0;
+            beatmap.runtime_start = game_time(data.world) + 3.0;
             data.world.add_resource(beatmap);
 
             Trans::Push(Box::new(GamePlayState::new()))
@@ -281,4 +539,8 @@ impl<'a, 'b> State<GameData<'a, 'b>> for MapSelectionState {
             Trans::None
         }
     }
+
+    fn name(&self) -> &str {
+        MAP_SELECTION_STATE
+    }
 }
@@ -1,13 +1,19 @@
+pub use self::course_state::{CourseState, COURSE_SCORES_FILE};
 pub use self::game_play_state::GamePlayState;
 pub use self::map_selection::*;
-pub use self::map_selection_state::MapSelectionState;
+pub use self::map_selection_state::{MapSelectionState, MAP_SELECTION_STATE};
+pub use self::mods_state::{ModsState, PACK_SETTINGS_FILE};
 pub use self::score_state::ScoreState;
+pub use self::settings_state::SettingsState;
 pub use self::test::TestState;
 pub use self::change_control_state::ChangeControlState;
 
+mod course_state;
 mod game_play_state;
 mod map_selection;
 mod map_selection_state;
+mod mods_state;
 mod score_state;
+mod settings_state;
 mod change_control_state;
 mod test;
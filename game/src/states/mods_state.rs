@@ -0,0 +1,297 @@
+use amethyst::audio::Source;
+use amethyst::ecs::*;
+use amethyst::input::{is_close_requested, is_key_down};
+use amethyst::prelude::Builder;
+use amethyst::renderer::{Event, Texture, VirtualKeyCode};
+use amethyst::shrev::{EventChannel, ReaderId};
+use amethyst::ui::{
+    Anchor, FontAsset, FontHandle, TtfFormat, UiButtonBuilder, UiEvent, UiEventType, UiText,
+    UiTransform,
+};
+use amethyst::{GameData, State, StateData, Trans};
+use amethyst::locale::Locale;
+use amethyst_extra::{AssetLoader, AssetLoaderInternal};
+use dirty::Dirty;
+
+use data::{LocalizedText, PlayerProgress};
+use utils::{is_pack_locked, localize, user_data_path};
+
+/// File in the user data directory holding the asset pack load order.
+pub const PACK_SETTINGS_FILE: &str = "packs.ron";
+
+/// Where the player enables, disables and reorders the asset packs.
+#[derive(Default, new)]
+pub struct ModsState {
+    #[new(default)]
+    ui_events: Option<ReaderId<UiEvent>>,
+    #[new(default)]
+    font: Option<FontHandle>,
+    /// Buttons toggling a pack on and off, with the pack they control.
+    #[new(default)]
+    toggle_buttons: Vec<(Entity, String)>,
+    /// Buttons raising the priority of a pack, with the pack they control.
+    #[new(default)]
+    up_buttons: Vec<(Entity, String)>,
+    /// All entities of the menu.
+    #[new(default)]
+    entities: Vec<Entity>,
+    /// Whether the pack settings were modified since the menu was opened.
+    #[new(value = "false")]
+    changed: bool,
+}
+
+impl ModsState {
+    /// Recreates the pack list from the current `AssetLoader` state.
+    fn reload_menu(&mut self, world: &mut World) {
+        self.clear_menu(world);
+
+        let font = self.font.as_ref().unwrap().clone();
+
+        let title_text = localize(world, "mods-title");
+        let title = world
+            .create_entity()
+            .with(UiText::new(
+                font.clone(),
+                title_text,
+                [1.0, 1.0, 1.0, 1.0],
+                25.0,
+            ))
+            .with(LocalizedText::new("mods-title".to_owned()))
+            .with(
+                UiTransform::new(
+                    "mods_title".to_owned(),
+                    Anchor::TopMiddle,
+                    0.0,
+                    40.0,
+                    -3.0,
+                    700.0,
+                    25.0,
+                    2,
+                ).as_transparent(),
+            )
+            .build();
+        self.entities.push(title);
+
+        let by = localize(world, "mods-by");
+        let status_default = localize(world, "mods-status-default");
+        let status_on = localize(world, "mods-status-on");
+        let status_off = localize(world, "mods-status-off");
+        let rows = {
+            let mut asset_loader = world.write_resource::<AssetLoader>();
+            let default_pack = asset_loader.default_pack().to_owned();
+            let packs = asset_loader.get_asset_packs().clone();
+            let progress = world.read_resource::<Dirty<PlayerProgress>>();
+            packs
+                .into_iter()
+                // Hidden packs are listed once an achievement unlocks them.
+                .filter(|pack| !is_pack_locked(&asset_loader, progress.read(), pack))
+                .map(|pack| {
+                    let label = match asset_loader.pack_info(&pack) {
+                        Some(info) => {
+                            format!("{} {} {} {}", info.name, info.version, by, info.author)
+                        }
+                        None => pack.clone(),
+                    };
+                    let status = if pack == default_pack {
+                        &status_default
+                    } else if asset_loader.is_pack_enabled(&pack) {
+                        &status_on
+                    } else {
+                        &status_off
+                    };
+                    (pack.clone(), format!("{} [{}]", label, status), pack == default_pack)
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let up_text = localize(world, "mods-up");
+        for (i, (pack, text, is_default)) in rows.into_iter().enumerate() {
+            let y = 100.0 + 50.0 * i as f32;
+            let toggle = UiButtonBuilder::new(format!("mods_toggle_{}", pack), text)
+                .with_position(-40.0, y)
+                .with_text_color([0.7; 4])
+                .with_hover_text_color([1.0; 4])
+                .with_press_text_color([0.5; 4])
+                .with_font_size(25.0)
+                .with_tab_order(i as i32 * 2)
+                .with_anchor(Anchor::TopMiddle)
+                .with_font(font.clone())
+                .with_size(600.0, 40.0)
+                .build_from_world(world);
+            self.entities.push(toggle);
+            self.toggle_buttons.push((toggle, pack.clone()));
+
+            // The default pack is always loaded first.
+            if !is_default && i > 1 {
+                let up = UiButtonBuilder::new(format!("mods_up_{}", pack), up_text.clone())
+                    .with_position(310.0, y)
+                    .with_text_color([0.7; 4])
+                    .with_hover_text_color([1.0; 4])
+                    .with_press_text_color([0.5; 4])
+                    .with_font_size(25.0)
+                    .with_tab_order(i as i32 * 2 + 1)
+                    .with_anchor(Anchor::TopMiddle)
+                    .with_font(font.clone())
+                    .with_size(60.0, 40.0)
+                    .build_from_world(world);
+                self.entities.push(up);
+                self.up_buttons.push((up, pack));
+            }
+        }
+
+        // Only the textures, fonts, sounds and locales are resolved again when leaving the menu.
+        if self.changed {
+            let notice_text = localize(world, "mods-restart");
+            let notice = world
+                .create_entity()
+                .with(UiText::new(font, notice_text, [1.0, 0.8, 0.3, 1.0], 20.0))
+                .with(LocalizedText::new("mods-restart".to_owned()))
+                .with(
+                    UiTransform::new(
+                        "mods_restart".to_owned(),
+                        Anchor::BottomMiddle,
+                        0.0,
+                        -40.0,
+                        -3.0,
+                        900.0,
+                        25.0,
+                        2,
+                    ).as_transparent(),
+                )
+                .build();
+            self.entities.push(notice);
+        }
+    }
+
+    fn clear_menu(&mut self, world: &mut World) {
+        self.toggle_buttons.clear();
+        self.up_buttons.clear();
+        self.entities.drain(..).for_each(|entity| {
+            world
+                .delete_entity(entity)
+                .expect("Failed to delete mods menu entity.");
+        });
+    }
+
+    /// Saves the pack settings and drops the cached handles so that assets are resolved again.
+    fn apply_changes(&mut self, world: &mut World) {
+        if !self.changed {
+            return;
+        }
+        self.changed = false;
+        world
+            .read_resource::<AssetLoader>()
+            .save_pack_settings(&user_data_path(PACK_SETTINGS_FILE));
+        world
+            .write_resource::<AssetLoaderInternal<Texture>>()
+            .assets
+            .clear();
+        world
+            .write_resource::<AssetLoaderInternal<FontAsset>>()
+            .assets
+            .clear();
+        world
+            .write_resource::<AssetLoaderInternal<Source>>()
+            .assets
+            .clear();
+        world
+            .write_resource::<AssetLoaderInternal<Locale>>()
+            .assets
+            .clear();
+    }
+}
+
+impl<'a, 'b> State<GameData<'a, 'b>> for ModsState {
+    fn on_start(&mut self, data: StateData<GameData>) {
+        debug!("Starting ModsState");
+        let world = data.world;
+
+        let font = world
+            .write_resource::<AssetLoader>()
+            .load(
+                "ui/square.ttf",
+                TtfFormat,
+                (),
+                &mut world.write_resource::<AssetLoaderInternal<FontAsset>>(),
+                &mut world.write_resource(),
+                &mut world.read_resource(),
+            )
+            .expect("Failed to load font");
+        self.font = Some(font);
+
+        self.reload_menu(world);
+
+        self.ui_events = Some(
+            world
+                .write_resource::<EventChannel<UiEvent>>()
+                .register_reader(),
+        );
+    }
+
+    fn on_stop(&mut self, data: StateData<GameData>) {
+        self.clear_menu(data.world);
+        self.apply_changes(data.world);
+    }
+
+    fn handle_event(&mut self, _data: StateData<GameData>, event: Event) -> Trans<GameData<'a, 'b>> {
+        if is_close_requested(&event) {
+            return Trans::Quit;
+        }
+        if is_key_down(&event, VirtualKeyCode::Escape) {
+            return Trans::Pop;
+        }
+        Trans::None
+    }
+
+    fn update(&mut self, data: StateData<GameData>) -> Trans<GameData<'a, 'b>> {
+        data.data.update(data.world);
+
+        let mut clicked = None;
+        for ev in data
+            .world
+            .read_resource::<EventChannel<UiEvent>>()
+            .read(self.ui_events.as_mut().unwrap())
+        {
+            if let UiEventType::Click = ev.event_type {
+                clicked = Some(ev.target);
+            }
+        }
+
+        if let Some(target) = clicked {
+            let toggle = self
+                .toggle_buttons
+                .iter()
+                .find(|(e, _)| *e == target)
+                .map(|(_, p)| p.clone());
+            let up = self
+                .up_buttons
+                .iter()
+                .find(|(e, _)| *e == target)
+                .map(|(_, p)| p.clone());
+
+            let mut modified = false;
+            if let Some(pack) = toggle {
+                let mut asset_loader = data.world.write_resource::<AssetLoader>();
+                let enabled = asset_loader.is_pack_enabled(&pack);
+                asset_loader.set_pack_enabled(&pack, !enabled);
+                modified = true;
+            } else if let Some(pack) = up {
+                let mut asset_loader = data.world.write_resource::<AssetLoader>();
+                let idx = asset_loader
+                    .get_asset_packs()
+                    .iter()
+                    .position(|p| p == &pack)
+                    .unwrap_or(0);
+                asset_loader.move_pack(&pack, idx.saturating_sub(1));
+                modified = true;
+            }
+
+            if modified {
+                self.changed = true;
+                self.reload_menu(data.world);
+            }
+        }
+
+        Trans::None
+    }
+}
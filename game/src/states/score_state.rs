@@ -5,10 +5,15 @@ use amethyst::renderer::{ElementState, Event, PngFormat, Texture, VirtualKeyCode
 use amethyst::shrev::EventChannel;
 use amethyst::ui::{Anchor, FontAsset, TtfFormat, UiButtonBuilder, UiEvent, UiEventType, UiImage,
                    UiText, UiTransform};
+use amethyst::utils::state_scoped::StateScoped;
 use amethyst::{GameData, State, StateData, Trans};
-use amethyst_extra::{AssetLoader, AssetLoaderInternal};
+use amethyst_extra::{AssetLoader, AssetLoaderInternal, DestroyInTime};
 
-use data::{GameplayCommand, GameplayResult, GameplayStatus, HitResult, ResultEntities};
+use data::{AchievementToasts, BeatMap, GameplayCommand, GameplayResults, GameplayStatus, Grade,
+           LocalizedText, ResultEntities, ACHIEVEMENT_TOAST_TIME};
+use systems::SCORE_SYSTEMS;
+use utils::{load_beatmap, localize};
+use GamePlayState;
 
 /// Where the player is running out of space
 #[derive(Default)]
@@ -29,59 +34,13 @@ impl ScoreState {
     }
 }
 
-fn compute_score(result: &GameplayResult) -> u32 {
-    let mut score = 0;
-    for (_, h) in &result.results {
-        score += match h {
-            HitResult::Hit => 1000,
-            HitResult::MissKey => 100,
-            HitResult::MissEarly | HitResult::MissLate => 10,
-        };
-    }
-    return score;
-}
-
-enum Grade {
-    A,
-    B,
-    C,
-    S,
-    F,
-}
-
-fn compute_grade(result: &GameplayResult) -> Grade {
-    let mut successes = 0;
-    for (_, h) in &result.results {
-        successes += match h {
-            HitResult::Hit => 1,
-            _ => 0,
-        };
-    }
-    let ratio = successes as f32 / result.results.len() as f32;
-    if ratio < 0.40 {
-        Grade::C
-    } else if ratio < 0.70 {
-        Grade::B
-    } else if ratio < 0.97 {
-        Grade::A
-    } else {
-        Grade::S
-    }
-}
-
-struct CleanupScore;
-impl Component for CleanupScore {
-    type Storage = VecStorage<CleanupScore>;
-}
-
 impl<'a, 'b> State<GameData<'a, 'b>> for ScoreState {
     fn on_start(&mut self, mut data: StateData<GameData>) {
         debug!("Starting ScoreState");
+        data.data.enable_group(SCORE_SYSTEMS);
 
         let world = data.world;
 
-        world.register::<CleanupScore>();
-
         let font = world
             .write_resource::<AssetLoader>()
             .load(
@@ -94,17 +53,20 @@ impl<'a, 'b> State<GameData<'a, 'b>> for ScoreState {
             )
             .expect("Failed to load font");
 
-        let result = world.read_resource::<GameplayResult>().clone();
+        let results = world.read_resource::<GameplayResults>().clone();
+        // The screen shows the result of the first player, and compares the players in versus.
+        let result = results.players[0].clone();
 
-        let (title_text, grade) = if result.status == GameplayStatus::Completed {
-            ("Congratulations!".to_owned(), compute_grade(&result))
+        let title_key = if result.status == GameplayStatus::Completed {
+            "score-title-completed"
         } else {
-            ("Oh no!".to_owned(), Grade::F)
+            "score-title-failed"
         };
+        let grade = Grade::of(&result);
 
         //let (title_text, grade) = ("Congratulations!".to_owned(), Grade::S);
 
-        let (grade, comment) = match grade {
+        let (grade, comment_key) = match grade {
             Grade::C => (
                 {
                     world
@@ -120,7 +82,7 @@ impl<'a, 'b> State<GameData<'a, 'b>> for ScoreState {
                         .expect("Failed to load rank C")
                         .clone()
                 },
-                "That's a start".to_owned(),
+                Some("grade-comment-c"),
             ),
             Grade::B => (
                 {
@@ -137,7 +99,7 @@ impl<'a, 'b> State<GameData<'a, 'b>> for ScoreState {
                         .expect("Failed to load rank B")
                         .clone()
                 },
-                "Okay!".to_owned(),
+                Some("grade-comment-b"),
             ),
             Grade::A => (
                 {
@@ -154,7 +116,7 @@ impl<'a, 'b> State<GameData<'a, 'b>> for ScoreState {
                         .expect("Failed to load rank A")
                         .clone()
                 },
-                "Not bad!".to_owned(),
+                Some("grade-comment-a"),
             ),
             Grade::S => (
                 {
@@ -171,7 +133,7 @@ impl<'a, 'b> State<GameData<'a, 'b>> for ScoreState {
                         .expect("Failed to load rank S")
                         .clone()
                 },
-                "Awesome!".to_owned(),
+                Some("grade-comment-s"),
             ),
             Grade::F => (
                 {
@@ -188,10 +150,13 @@ impl<'a, 'b> State<GameData<'a, 'b>> for ScoreState {
                         .expect("Failed to load rank F")
                         .clone()
                 },
-                "".to_owned(),
+                None,
             ),
         };
 
+        let title_text = localize(world, title_key);
+        let score_label = localize(world, "score-label");
+
         let title = world
             .create_entity()
             .with(UiText::new(
@@ -200,6 +165,7 @@ impl<'a, 'b> State<GameData<'a, 'b>> for ScoreState {
                 [1.0, 1.0, 1.0, 0.0],
                 50.0,
             ))
+            .with(LocalizedText::new(title_key.to_owned()))
             .with(
                 UiTransform::new(
                     "title".to_owned(),
@@ -212,17 +178,18 @@ impl<'a, 'b> State<GameData<'a, 'b>> for ScoreState {
                     2,
                 ).as_transparent(),
             )
-            .with(CleanupScore)
+            .with(StateScoped::new())
             .build();
 
         let score_text = world
             .create_entity()
             .with(UiText::new(
                 font.clone(),
-                "Score".to_owned(),
+                score_label,
                 [1.0, 1.0, 1.0, 0.0],
                 25.0,
             ))
+            .with(LocalizedText::new("score-label".to_owned()))
             .with(
                 UiTransform::new(
                     "score_text".to_owned(),
@@ -235,7 +202,7 @@ impl<'a, 'b> State<GameData<'a, 'b>> for ScoreState {
                     2,
                 ).as_transparent(),
             )
-            .with(CleanupScore)
+            .with(StateScoped::new())
             .build();
 
         let score = world
@@ -258,14 +225,17 @@ impl<'a, 'b> State<GameData<'a, 'b>> for ScoreState {
                     2,
                 ).as_transparent(),
             )
-            .with(CleanupScore)
+            .with(StateScoped::new())
             .build();
 
-        let comment = world
+        let comment_text = comment_key
+            .map(|key| localize(world, key))
+            .unwrap_or_default();
+        let mut comment = world
             .create_entity()
             .with(UiText::new(
                 font.clone(),
-                comment,
+                comment_text,
                 [1.0, 1.0, 1.0, 0.0],
                 25.0,
             ))
@@ -281,8 +251,123 @@ impl<'a, 'b> State<GameData<'a, 'b>> for ScoreState {
                     2,
                 ).as_transparent(),
             )
-            .with(CleanupScore)
-            .build();
+            .with(StateScoped::new());
+        if let Some(key) = comment_key {
+            comment = comment.with(LocalizedText::new(key.to_owned()));
+        }
+        let comment = comment.build();
+
+        let active_modifiers = result.modifiers.active();
+        if !active_modifiers.is_empty() {
+            let names = active_modifiers
+                .iter()
+                .map(|m| localize(world, m.label_key()))
+                .collect::<Vec<_>>();
+            let modifiers_text = format!(
+                "{} (x{:.2})",
+                names.join(", "),
+                result.modifiers.score_multiplier()
+            );
+            world
+                .create_entity()
+                .with(UiText::new(
+                    font.clone(),
+                    modifiers_text,
+                    [0.7, 0.7, 0.7, 1.0],
+                    20.0,
+                ))
+                .with(
+                    UiTransform::new(
+                        "modifiers".to_owned(),
+                        Anchor::Middle,
+                        20.0,
+                        60.0,
+                        -3.0,
+                        500.0,
+                        20.0,
+                        2,
+                    ).as_transparent(),
+                )
+                .with(StateScoped::new())
+                .build();
+        }
+
+        if results.players.len() > 1 {
+            let player_label = localize(world, "versus-player");
+            let max_combo_label = localize(world, "versus-max-combo");
+            let mut lines = results
+                .players
+                .iter()
+                .enumerate()
+                .map(|(i, r)| {
+                    format!(
+                        "{} {}: {} - {} {}",
+                        player_label,
+                        i + 1,
+                        r.score(),
+                        max_combo_label,
+                        r.max_combo
+                    )
+                })
+                .collect::<Vec<_>>();
+            lines.push(match results.winner() {
+                Some(i) => format!("{} {} {}", player_label, i + 1, localize(world, "versus-wins")),
+                None => localize(world, "versus-draw"),
+            });
+            for (i, line) in lines.into_iter().enumerate() {
+                world
+                    .create_entity()
+                    .with(UiText::new(font.clone(), line, [1.0; 4], 20.0))
+                    .with(
+                        UiTransform::new(
+                            format!("versus_line_{}", i),
+                            Anchor::Middle,
+                            20.0,
+                            90.0 + 25.0 * i as f32,
+                            -3.0,
+                            500.0,
+                            20.0,
+                            2,
+                        ).as_transparent(),
+                    )
+                    .with(StateScoped::new())
+                    .build();
+            }
+        }
+
+        let toasts = world
+            .write_resource::<AchievementToasts>()
+            .names
+            .drain(..)
+            .collect::<Vec<_>>();
+        let unlocked_label = localize(world, "achievement-unlocked");
+        for (i, name) in toasts.into_iter().enumerate() {
+            world
+                .create_entity()
+                .with(UiText::new(
+                    font.clone(),
+                    format!("{}: {}", unlocked_label, name),
+                    [1.0, 0.85, 0.2, 1.0],
+                    22.0,
+                ))
+                .with(
+                    UiTransform::new(
+                        format!("achievement_toast_{}", i),
+                        Anchor::TopMiddle,
+                        0.0,
+                        30.0 + 30.0 * i as f32,
+                        -3.0,
+                        700.0,
+                        22.0,
+                        2,
+                    ).as_transparent(),
+                )
+                .with(DestroyInTime {
+                    timer: ACHIEVEMENT_TOAST_TIME,
+                })
+                .with(StateScoped::new())
+                .build();
+        }
 
         let grade = world
             .create_entity()
@@ -299,7 +384,7 @@ impl<'a, 'b> State<GameData<'a, 'b>> for ScoreState {
                     2,
                 ).as_transparent(),
             )
-            .with(CleanupScore)
+            .with(StateScoped::new())
             .build();
 
         let retry = {
@@ -358,8 +443,8 @@ impl<'a, 'b> State<GameData<'a, 'b>> for ScoreState {
             .build_from_world(world);
 
         world
-            .write_storage::<CleanupScore>()
-            .insert(retry_button, CleanupScore)
+            .write_storage::<StateScoped>()
+            .insert(retry_button, StateScoped::new())
             .unwrap();
 
         let menu = {
@@ -418,8 +503,8 @@ impl<'a, 'b> State<GameData<'a, 'b>> for ScoreState {
             .build_from_world(world);
 
         world
-            .write_storage::<CleanupScore>()
-            .insert(menu_button, CleanupScore)
+            .write_storage::<StateScoped>()
+            .insert(menu_button, StateScoped::new())
             .unwrap();
 
         *world.write_resource::<Option<ResultEntities>>() = Some(ResultEntities {
@@ -431,7 +516,7 @@ impl<'a, 'b> State<GameData<'a, 'b>> for ScoreState {
             menu_button,
             retry_button,
 
-            target_score: compute_score(&result),
+            target_score: result.score(),
         });
 
         self.menu_button = Some(menu_button);
@@ -444,6 +529,10 @@ impl<'a, 'b> State<GameData<'a, 'b>> for ScoreState {
         );
     }
 
+    fn on_stop(&mut self, data: StateData<GameData>) {
+        data.data.disable_group(SCORE_SYSTEMS);
+    }
+
     fn handle_event(
         &mut self,
         mut data: StateData<GameData>,
@@ -478,22 +567,26 @@ impl<'a, 'b> State<GameData<'a, 'b>> for ScoreState {
             }
         }
 
-        if let Some(command) = new_command {
-            cleanup(&mut data.world);
-            *data.world.write_resource::<GameplayCommand>() = command;
-            Trans::Pop
-        } else {
-            Trans::None
+        match new_command {
+            // A fresh run of the same map replaces the score screen.
+            Some(GameplayCommand::Retry) => {
+                let name = data.world.read_resource::<BeatMap>().name.clone();
+                match load_beatmap(name, &mut data.world) {
+                    Some(beatmap) => {
+                        data.world.add_resource(beatmap);
+                        Trans::Switch(Box::new(GamePlayState::new()))
+                    }
+                    None => {
+                        error!("Failed to reload the beatmap to retry it.");
+                        Trans::Pop
+                    }
+                }
+            }
+            Some(command) => {
+                *data.world.write_resource::<GameplayCommand>() = command;
+                Trans::Pop
+            }
+            None => Trans::None,
         }
     }
 }
-
-fn cleanup(world: &mut World) {
-    world.exec(
-        |(entities, cleanup_store): (Entities, ReadStorage<CleanupScore>)| {
-            for (e, _) in (&*entities, &cleanup_store).join() {
-                entities.delete(e).unwrap();
-            }
-        },
-    );
-}
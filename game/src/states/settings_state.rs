@@ -0,0 +1,327 @@
+use amethyst::ecs::*;
+use amethyst::input::{is_close_requested, is_key_down};
+use amethyst::prelude::Builder;
+use amethyst::renderer::{Event, VirtualKeyCode};
+use amethyst::shrev::{EventChannel, ReaderId};
+use amethyst::ui::{
+    Anchor, FontAsset, FontHandle, TtfFormat, UiButtonBuilder, UiEvent, UiEventType, UiText,
+    UiTransform,
+};
+use amethyst::{GameData, State, StateData, Trans};
+use amethyst_extra::{AssetLoader, AssetLoaderInternal};
+use dirty::Dirty;
+
+use data::{LocalizedText, UserSettings};
+use utils::{apply_audio_settings, apply_fullscreen, localize};
+
+/// A value of `UserSettings` shown in the settings screen.
+#[derive(Clone, Copy, PartialEq)]
+enum Setting {
+    MusicVolume,
+    SfxVolume,
+    Fullscreen,
+    Vsync,
+    ScrollSpeed,
+    NoteOffset,
+    Ghost,
+}
+
+const SETTINGS: [Setting; 7] = [
+    Setting::MusicVolume,
+    Setting::SfxVolume,
+    Setting::Fullscreen,
+    Setting::Vsync,
+    Setting::ScrollSpeed,
+    Setting::NoteOffset,
+    Setting::Ghost,
+];
+
+impl Setting {
+    fn label_key(&self) -> &'static str {
+        match *self {
+            Setting::MusicVolume => "settings-music-volume",
+            Setting::SfxVolume => "settings-sfx-volume",
+            Setting::Fullscreen => "settings-fullscreen",
+            Setting::Vsync => "settings-vsync",
+            Setting::ScrollSpeed => "settings-scroll-speed",
+            Setting::NoteOffset => "settings-note-offset",
+            Setting::Ghost => "settings-ghost",
+        }
+    }
+
+    fn value_text(&self, settings: &UserSettings, on: &str, off: &str) -> String {
+        let flag = |b: bool| if b { on.to_owned() } else { off.to_owned() };
+        match *self {
+            Setting::MusicVolume => format!("{:.0}%", settings.music_volume * 100.0),
+            Setting::SfxVolume => format!("{:.0}%", settings.sfx_volume * 100.0),
+            Setting::Fullscreen => flag(settings.fullscreen),
+            Setting::Vsync => flag(settings.vsync),
+            Setting::ScrollSpeed => format!("{:.1}x", settings.scroll_speed),
+            Setting::NoteOffset => format!("{:.0} ms", settings.note_offset * 1000.0),
+            Setting::Ghost => flag(settings.ghost),
+        }
+    }
+
+    /// Increases (`step` > 0) or decreases the value. Flags are toggled either way.
+    fn change(&self, settings: &mut UserSettings, step: i32) {
+        let step_f = step as f32;
+        match *self {
+            Setting::MusicVolume => {
+                settings.music_volume = (settings.music_volume + 0.1 * step_f).max(0.0).min(1.0)
+            }
+            Setting::SfxVolume => {
+                settings.sfx_volume = (settings.sfx_volume + 0.1 * step_f).max(0.0).min(1.0)
+            }
+            Setting::Fullscreen => settings.fullscreen = !settings.fullscreen,
+            Setting::Vsync => settings.vsync = !settings.vsync,
+            Setting::ScrollSpeed => {
+                settings.scroll_speed = (settings.scroll_speed + 0.1 * step_f).max(0.5).min(3.0)
+            }
+            Setting::NoteOffset => {
+                settings.note_offset = (settings.note_offset + 0.005 * step as f64)
+                    .max(-0.5)
+                    .min(0.5)
+            }
+            Setting::Ghost => settings.ghost = !settings.ghost,
+        }
+    }
+}
+
+/// One line of the settings screen.
+struct SettingRow {
+    setting: Setting,
+    value: Entity,
+    minus: Entity,
+    plus: Entity,
+}
+
+/// Where the player changes the audio, display and gameplay options.
+#[derive(Default, new)]
+pub struct SettingsState {
+    #[new(default)]
+    ui_events: Option<ReaderId<UiEvent>>,
+    #[new(default)]
+    font: Option<FontHandle>,
+    #[new(default)]
+    rows: Vec<SettingRow>,
+    /// All entities of the menu.
+    #[new(default)]
+    entities: Vec<Entity>,
+}
+
+impl SettingsState {
+    fn create_menu(&mut self, world: &mut World) {
+        let font = self.font.as_ref().unwrap().clone();
+
+        let title_text = localize(world, "settings-title");
+        let title = world
+            .create_entity()
+            .with(UiText::new(
+                font.clone(),
+                title_text,
+                [1.0, 1.0, 1.0, 1.0],
+                25.0,
+            ))
+            .with(LocalizedText::new("settings-title".to_owned()))
+            .with(
+                UiTransform::new(
+                    "settings_title".to_owned(),
+                    Anchor::TopMiddle,
+                    0.0,
+                    40.0,
+                    -3.0,
+                    500.0,
+                    25.0,
+                    2,
+                ).as_transparent(),
+            )
+            .build();
+        self.entities.push(title);
+
+        for (i, setting) in SETTINGS.iter().enumerate() {
+            let y = 100.0 + 50.0 * i as f32;
+
+            let label_text = localize(world, setting.label_key());
+            let label = world
+                .create_entity()
+                .with(UiText::new(
+                    font.clone(),
+                    label_text,
+                    [1.0, 1.0, 1.0, 1.0],
+                    25.0,
+                ))
+                .with(LocalizedText::new(setting.label_key().to_owned()))
+                .with(
+                    UiTransform::new(
+                        format!("{}_label", setting.label_key()),
+                        Anchor::TopMiddle,
+                        -150.0,
+                        y,
+                        -3.0,
+                        400.0,
+                        25.0,
+                        2,
+                    ).as_transparent(),
+                )
+                .build();
+
+            let value = world
+                .create_entity()
+                .with(UiText::new(
+                    font.clone(),
+                    String::new(),
+                    [1.0, 1.0, 1.0, 1.0],
+                    25.0,
+                ))
+                .with(
+                    UiTransform::new(
+                        format!("{}_value", setting.label_key()),
+                        Anchor::TopMiddle,
+                        160.0,
+                        y,
+                        -3.0,
+                        150.0,
+                        25.0,
+                        2,
+                    ).as_transparent(),
+                )
+                .build();
+
+            let minus = UiButtonBuilder::new(format!("{}_minus", setting.label_key()), "-")
+                .with_position(260.0, y)
+                .with_text_color([0.7; 4])
+                .with_hover_text_color([1.0; 4])
+                .with_press_text_color([0.5; 4])
+                .with_font_size(25.0)
+                .with_tab_order(i as i32 * 2)
+                .with_anchor(Anchor::TopMiddle)
+                .with_font(font.clone())
+                .with_size(40.0, 40.0)
+                .build_from_world(world);
+
+            let plus = UiButtonBuilder::new(format!("{}_plus", setting.label_key()), "+")
+                .with_position(310.0, y)
+                .with_text_color([0.7; 4])
+                .with_hover_text_color([1.0; 4])
+                .with_press_text_color([0.5; 4])
+                .with_font_size(25.0)
+                .with_tab_order(i as i32 * 2 + 1)
+                .with_anchor(Anchor::TopMiddle)
+                .with_font(font.clone())
+                .with_size(40.0, 40.0)
+                .build_from_world(world);
+
+            self.entities.extend(&[label, value, minus, plus]);
+            self.rows.push(SettingRow {
+                setting: *setting,
+                value,
+                minus,
+                plus,
+            });
+        }
+
+        self.refresh_values(world);
+    }
+
+    fn refresh_values(&self, world: &mut World) {
+        let on = localize(world, "settings-on");
+        let off = localize(world, "settings-off");
+        let settings = world.read_resource::<Dirty<UserSettings>>();
+        let mut texts = world.write_storage::<UiText>();
+        for row in &self.rows {
+            if let Some(text) = texts.get_mut(row.value) {
+                text.text = row.setting.value_text(settings.read(), &on, &off);
+            }
+        }
+    }
+
+    fn clear_menu(&mut self, world: &mut World) {
+        self.rows.clear();
+        self.entities.drain(..).for_each(|entity| {
+            world
+                .delete_entity(entity)
+                .expect("Failed to delete settings menu entity.");
+        });
+    }
+}
+
+impl<'a, 'b> State<GameData<'a, 'b>> for SettingsState {
+    fn on_start(&mut self, data: StateData<GameData>) {
+        debug!("Starting SettingsState");
+        let world = data.world;
+
+        let font = world
+            .write_resource::<AssetLoader>()
+            .load(
+                "ui/square.ttf",
+                TtfFormat,
+                (),
+                &mut world.write_resource::<AssetLoaderInternal<FontAsset>>(),
+                &mut world.write_resource(),
+                &mut world.read_resource(),
+            )
+            .expect("Failed to load font");
+        self.font = Some(font);
+
+        self.create_menu(world);
+
+        self.ui_events = Some(
+            world
+                .write_resource::<EventChannel<UiEvent>>()
+                .register_reader(),
+        );
+    }
+
+    fn on_stop(&mut self, data: StateData<GameData>) {
+        self.clear_menu(data.world);
+    }
+
+    fn handle_event(&mut self, _data: StateData<GameData>, event: Event) -> Trans<GameData<'a, 'b>> {
+        if is_close_requested(&event) {
+            return Trans::Quit;
+        }
+        if is_key_down(&event, VirtualKeyCode::Escape) {
+            return Trans::Pop;
+        }
+        Trans::None
+    }
+
+    fn update(&mut self, data: StateData<GameData>) -> Trans<GameData<'a, 'b>> {
+        data.data.update(data.world);
+
+        let mut changes = Vec::new();
+        for ev in data
+            .world
+            .read_resource::<EventChannel<UiEvent>>()
+            .read(self.ui_events.as_mut().unwrap())
+        {
+            if let UiEventType::Click = ev.event_type {
+                for row in &self.rows {
+                    if ev.target == row.minus {
+                        changes.push((row.setting, -1));
+                    } else if ev.target == row.plus {
+                        changes.push((row.setting, 1));
+                    }
+                }
+            }
+        }
+
+        for (setting, step) in changes {
+            // Writing marks the settings dirty, the AutoSaveSystem saves them.
+            let fullscreen = {
+                let mut settings = data.world.write_resource::<Dirty<UserSettings>>();
+                let settings = settings.write();
+                setting.change(settings, step);
+                settings.fullscreen
+            };
+            match setting {
+                Setting::MusicVolume => apply_audio_settings(data.world),
+                Setting::Fullscreen => apply_fullscreen(data.world, fullscreen),
+                _ => {}
+            }
+            self.refresh_values(data.world);
+        }
+
+        Trans::None
+    }
+}
@@ -1,7 +1,7 @@
-use amethyst::ecs::{Join, Read, ReadExpect, ReadStorage, Resources, System, SystemData, Write, WriteExpect, WriteStorage};
+use amethyst::ecs::{Entities, Join, Read, ReadExpect, ReadStorage, Resources, System, SystemData, Write, WriteExpect, WriteStorage};
 use amethyst::core::{Transform, GlobalTransform};
-use amethyst::renderer::SpriteRender;
-use amethyst::core::timing::Time;
+use amethyst::renderer::{Rgba, SpriteRender, Transparent};
+use amethyst::core::{GameClock, ReadClock};
 
 use data::*;
 
@@ -11,17 +11,22 @@ pub struct BeatPointAnimationSystem;
 
 impl<'a> System<'a> for BeatPointAnimationSystem{
     // Don't forgot to add a trailing , in both parenthesis
-    type SystemData = (Read<'a, Time>,
+    type SystemData = (Entities<'a>,
+        ReadClock<'a, GameClock>,
         ReadExpect<'a, BeatMap>,
+        Read<'a, Modifiers>,
+        Read<'a, Storyboard>,
         ReadStorage<'a, BeatPoint>,
         WriteStorage<'a, SpriteRender>,
+        WriteStorage<'a, Rgba>,
+        WriteStorage<'a, Transparent>,
     );
     
-    fn run(&mut self,(time, beatmap, beatpoints, mut sprites): Self::SystemData) {
-        let rel_time = time.absolute_time_seconds() - beatmap.runtime_start;
-        let stage_length = 0.1;
+    fn run(&mut self,(entities, game_clock, beatmap, modifiers, storyboard, beatpoints, mut sprites, mut tints, mut transparents): Self::SystemData) {
+        let rel_time = game_clock.absolute_time_seconds() - beatmap.runtime_start;
+        let stage_length = 0.1 / (modifiers.speed * storyboard.scroll_speed) as f64;
         let max_stage = 4; // [0,4]
-        for (beatpoint, mut sprite) in (&beatpoints, &mut sprites).join() {
+        for (entity, beatpoint, mut sprite) in (&*entities, &beatpoints, &mut sprites).join() {
             let dir_number = match beatpoint.direction {
                 Direction::Up => 0,
                 Direction::Down => 1,
@@ -29,6 +34,16 @@ impl<'a> System<'a> for BeatPointAnimationSystem{
                 Direction::Left => 3,
             };
             let mut time_left = beatpoint.time - rel_time;
+            // The hidden modifier fades the sprite out over the window before the beat point's time.
+            if modifiers.hidden && time_left < HIDDEN_WINDOW {
+                let alpha = (time_left / HIDDEN_WINDOW).max(0.0) as f32;
+                tints
+                    .insert(entity, Rgba(1.0, 1.0, 1.0, alpha))
+                    .expect("Failed to insert beat point tint.");
+                transparents
+                    .insert(entity, Transparent)
+                    .expect("Failed to insert beat point transparency.");
+            }
             if time_left < 0.0 {
                 time_left = 0.0;
             }
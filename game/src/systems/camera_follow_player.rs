@@ -2,25 +2,32 @@ use std::cmp;
 
 use amethyst::core::cgmath::{Matrix4, Vector3};
 use amethyst::core::{GlobalTransform, Transform};
-use amethyst::ecs::{Join, ReadStorage, System, WriteStorage};
+use amethyst::ecs::{Join, Read, ReadStorage, System, WriteStorage};
 use amethyst::renderer::Camera;
-use data::Player;
+use data::{Player, Storyboard};
 
 pub struct CameraFollowPlayerSystem;
 
 impl<'a> System<'a> for CameraFollowPlayerSystem {
     type SystemData = (
+        Read<'a, Storyboard>,
         ReadStorage<'a, Player>,
         ReadStorage<'a, Camera>,
         ReadStorage<'a, Transform>,
         WriteStorage<'a, GlobalTransform>,
     );
-    fn run(&mut self, (players, cameras, transforms, mut global_transforms): Self::SystemData) {
+    fn run(&mut self, (storyboard, players, cameras, transforms, mut global_transforms): Self::SystemData) {
+        // Follows the players of the versus mode together, as far as the healthiest one allows.
         let mut position_x = 0.0;
         let mut hp = 0.0;
+        let mut count = 0;
         for (transform, player) in (&transforms, &players).join() {
-            position_x = transform.translation.x;
-            hp = player.health as f32;
+            position_x += transform.translation.x;
+            hp = f32::max(hp, player.health as f32);
+            count += 1;
+        }
+        if count > 0 {
+            position_x /= count as f32;
         }
 
         for (mut transform, _) in (&mut global_transforms, &cameras).join() {
@@ -42,9 +49,14 @@ impl<'a> System<'a> for CameraFollowPlayerSystem {
                 }
 
                 // expect 0.666
-                info!("Updating cam pos to {}", cam_x);
-
-                transform.0 = Matrix4::from_translation(Vector3::new(cam_x, 0.0, 100.0));
+                trace!("Updating cam pos to {}", cam_x);
+
+                // Zoom around the center of the view.
+                let zoom = 1.0 / storyboard.camera_zoom;
+                transform.0 = Matrix4::from_translation(Vector3::new(cam_x, 0.0, 100.0))
+                    * Matrix4::from_translation(Vector3::new(0.5, 0.5, 0.0))
+                    * Matrix4::from_nonuniform_scale(zoom, zoom, 1.0)
+                    * Matrix4::from_translation(Vector3::new(-0.5, -0.5, 0.0));
             }
         }
     }
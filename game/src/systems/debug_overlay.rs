@@ -0,0 +1,282 @@
+use std::time::Instant;
+
+use amethyst::assets::{AssetStorage, Loader};
+use amethyst::core::cgmath::Vector3;
+use amethyst::core::{GameClock, GlobalTransform, ReadClock, Transform};
+use amethyst::ecs::{
+    Entities, Join, Read, ReadExpect, ReadStorage, System, Write, WriteStorage,
+};
+use amethyst::renderer::{
+    MaterialTextureSet, Sprite, SpriteRender, SpriteSheet, Texture, TextureCoordinates,
+    TextureData, Transparent,
+};
+use amethyst::ui::{Anchor, UiText, UiTransform};
+use amethyst::utils::fps_counter::FPSCounter;
+use amethyst::utils::state_scoped::StateScoped;
+use amethyst_extra::Removal;
+use floating_duration::TimeAsFloat;
+
+use data::*;
+
+/// Id of the hit window bar texture in the `MaterialTextureSet`, away from the ids of the scenes.
+const DEBUG_BAR_TEXTURE_ID: u64 = 1000;
+/// Lines of the overlay: fps, clocks, the next notes, hit errors and animation state.
+const DEBUG_LINES: usize = 4 + DEBUG_NEXT_NOTES;
+/// Height of a hit window bar, in world units.
+const BAR_HEIGHT: f32 = 0.004;
+
+/// Shows the `DebugOverlay` when it is enabled.
+pub struct DebugOverlaySystem;
+
+impl<'a> System<'a> for DebugOverlaySystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadClock<'a, GameClock>,
+        ReadExpect<'a, BeatMap>,
+        Read<'a, GameplayResults>,
+        ReadStorage<'a, Player>,
+        Read<'a, FPSCounter>,
+        Write<'a, DebugOverlay>,
+        ReadExpect<'a, Loader>,
+        Read<'a, AssetStorage<Texture>>,
+        Read<'a, AssetStorage<SpriteSheet>>,
+        Write<'a, MaterialTextureSet>,
+        ReadStorage<'a, BeatPoint>,
+        WriteStorage<'a, Transform>,
+        WriteStorage<'a, GlobalTransform>,
+        WriteStorage<'a, SpriteRender>,
+        WriteStorage<'a, Transparent>,
+        WriteStorage<'a, UiTransform>,
+        WriteStorage<'a, UiText>,
+        WriteStorage<'a, Removal<i32>>,
+        WriteStorage<'a, StateScoped>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            game_clock,
+            beatmap,
+            results,
+            players,
+            fps,
+            mut overlay,
+            loader,
+            textures,
+            sprite_sheets,
+            mut material_textures,
+            beatpoints,
+            mut transforms,
+            mut global_transforms,
+            mut sprites,
+            mut transparents,
+            mut ui_transforms,
+            mut texts,
+            mut removals,
+            mut scoped,
+        ): Self::SystemData,
+    ) {
+        let overlay = &mut *overlay;
+        // Reloading the scene deletes the overlay with the other scene entities.
+        overlay.lines.retain(|e| entities.is_alive(*e));
+        overlay.bars.retain(|e| entities.is_alive(*e));
+
+        if !overlay.enabled {
+            for entity in overlay.lines.drain(..).chain(overlay.bars.drain(..)) {
+                if let Err(err) = entities.delete(entity) {
+                    error!("Failed to delete debug overlay entity: {:?}", err);
+                }
+            }
+            overlay.clock_start = None;
+            return;
+        }
+
+        let rel_time = game_clock.absolute_time_seconds() - beatmap.runtime_start;
+
+        // === Text === //
+
+        if let Some(font) = overlay.font.clone() {
+            while overlay.lines.len() < DEBUG_LINES {
+                let i = overlay.lines.len();
+                let line = entities.create();
+                ui_transforms
+                    .insert(
+                        line,
+                        UiTransform::new(
+                            format!("debug_line_{}", i),
+                            Anchor::TopLeft,
+                            260.0,
+                            20.0 + 24.0 * i as f32,
+                            5.0,
+                            500.0,
+                            22.0,
+                            0,
+                        ).as_transparent(),
+                    )
+                    .expect("Failed to insert debug line transform.");
+                texts
+                    .insert(
+                        line,
+                        UiText::new(font.clone(), String::new(), [1.0, 1.0, 0.3, 1.0], 20.0),
+                    )
+                    .expect("Failed to insert debug line text.");
+                removals
+                    .insert(line, Removal::new(1))
+                    .expect("Failed to insert debug line removal.");
+                scoped
+                    .insert(line, StateScoped::new())
+                    .expect("Failed to insert debug line state scope.");
+                overlay.lines.push(line);
+            }
+        }
+
+        let (wall_start, song_start) = *overlay
+            .clock_start
+            .get_or_insert((Instant::now(), rel_time));
+        let wall_time = wall_start.elapsed().as_fractional_secs();
+        let song_time = rel_time - song_start;
+
+        let mut lines = vec![
+            format!("FPS: {:.0}", fps.sampled_fps()),
+            format!(
+                "Song: {:.3}s  Wall: {:.3}s  Drift: {:+.1}ms",
+                rel_time,
+                wall_time,
+                (song_time - wall_time) * 1000.0
+            ),
+        ];
+        // The overlay follows the first player.
+        let player = (&players).join().find(|p| p.index == 0);
+        let next_notes = player.map(|p| &p.beat_points);
+        for i in 0..DEBUG_NEXT_NOTES {
+            lines.push(match next_notes.and_then(|b| b.get(i)) {
+                Some(b) => format!(
+                    "Next {}: {:?} in {:+.3}s",
+                    i + 1,
+                    b.direction,
+                    b.time - rel_time
+                ),
+                None => format!("Next {}: -", i + 1),
+            });
+        }
+        let errors = results
+            .players
+            .get(0)
+            .map(|r| &r.hit_errors[..])
+            .unwrap_or(&[])
+            .iter()
+            .rev()
+            .take(DEBUG_HIT_ERRORS)
+            .map(|e| format!("{:+.0}", e * 1000.0))
+            .collect::<Vec<_>>();
+        lines.push(format!("Hit errors (ms): {}", errors.join(" ")));
+        lines.push(format!(
+            "Animation: {:?}",
+            player.map(|p| p.animation).unwrap_or_default()
+        ));
+
+        for (line, text) in overlay.lines.iter().zip(lines.into_iter()) {
+            if let Some(ui_text) = texts.get_mut(*line) {
+                ui_text.text = text;
+            }
+        }
+
+        // === Hit windows === //
+
+        if overlay.bar_sprite.is_none() {
+            let texture = loader.load_from_data(
+                TextureData::color([0.3, 1.0, 0.3, 0.6]),
+                (),
+                &textures,
+            );
+            material_textures.insert(DEBUG_BAR_TEXTURE_ID, texture);
+            let sprite_sheet = SpriteSheet {
+                texture_id: DEBUG_BAR_TEXTURE_ID,
+                sprites: vec![Sprite {
+                    width: 1.0,
+                    height: 1.0,
+                    offsets: [0.5, 0.5],
+                    tex_coords: TextureCoordinates {
+                        left: 0.0,
+                        right: 1.0,
+                        bottom: 0.0,
+                        top: 1.0,
+                    },
+                }],
+            };
+            overlay.bar_sprite = Some(loader.load_from_data(sprite_sheet, (), &sprite_sheets));
+        }
+
+        let mut positions = (&beatpoints, &transforms)
+            .join()
+            .map(|(b, t)| (b.time, t.translation))
+            .collect::<Vec<_>>();
+        positions.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+        // The window is as wide as the distance the player runs during it.
+        let mut windows = vec![];
+        for (i, (time, position)) in positions.iter().enumerate() {
+            if *time + HIT_WINDOW < rel_time {
+                continue;
+            }
+            if windows.len() == DEBUG_HIT_WINDOWS {
+                break;
+            }
+            let neighbour = if i > 0 {
+                positions.get(i - 1)
+            } else {
+                positions.get(i + 1)
+            };
+            let speed = neighbour
+                .map(|(t, p)| {
+                    let dt = (time - t).abs().max(::std::f64::EPSILON);
+                    ((position - p).x.abs() as f64 / dt) as f32
+                })
+                .unwrap_or(0.0);
+            windows.push((*position, speed * 2.0 * HIT_WINDOW as f32));
+        }
+
+        while overlay.bars.len() < windows.len() {
+            let bar = entities.create();
+            transforms
+                .insert(bar, Transform::default())
+                .expect("Failed to insert bar transform.");
+            global_transforms
+                .insert(bar, GlobalTransform::default())
+                .expect("Failed to insert bar global transform.");
+            sprites
+                .insert(
+                    bar,
+                    SpriteRender {
+                        sprite_sheet: overlay.bar_sprite.clone().unwrap(),
+                        sprite_number: 0,
+                        flip_horizontal: false,
+                        flip_vertical: false,
+                    },
+                )
+                .expect("Failed to insert bar sprite.");
+            transparents
+                .insert(bar, Transparent)
+                .expect("Failed to insert bar transparency.");
+            removals
+                .insert(bar, Removal::new(1))
+                .expect("Failed to insert bar removal.");
+            scoped
+                .insert(bar, StateScoped::new())
+                .expect("Failed to insert bar state scope.");
+            overlay.bars.push(bar);
+        }
+        for bar in overlay.bars.drain(windows.len()..) {
+            if let Err(err) = entities.delete(bar) {
+                error!("Failed to delete hit window bar: {:?}", err);
+            }
+        }
+        for (bar, (position, width)) in overlay.bars.iter().zip(windows.into_iter()) {
+            if let Some(transform) = transforms.get_mut(*bar) {
+                transform.translation = position + Vector3::new(0.0, 0.0, 0.5);
+                transform.scale = Vector3::new(width, BAR_HEIGHT, 1.0);
+            }
+        }
+    }
+}
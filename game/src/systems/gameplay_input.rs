@@ -1,111 +1,155 @@
-use amethyst::ecs::{Join, Read, ReadStorage, Resources, System, SystemData, Write, WriteExpect,
-                    WriteStorage};
-use amethyst::renderer::{ElementState, Event, VirtualKeyCode};
-use amethyst::input::get_key;
-use amethyst::core::Time;
+use amethyst::ecs::{Join, Read, ReadExpect, Resources, System, SystemData, Write, WriteStorage};
+use amethyst::input::InputEvent;
+use amethyst::core::{GameClock, ReadClock};
 use amethyst::shrev::{EventChannel, ReaderId};
+use amethyst_extra::Console;
 
 use data::*;
 
 pub struct GameplayInputSystem {
-    input_reader: Option<ReaderId<Event>>,
+    input_reader: Option<ReaderId<InputEvent<String>>>,
+    /// Start of the run being played.
+    runtime_start: f64,
 }
 
 impl GameplayInputSystem {
     pub fn new() -> Self {
-        GameplayInputSystem { input_reader: None }
+        GameplayInputSystem {
+            input_reader: None,
+            runtime_start: 0.0,
+        }
     }
 }
 
 impl<'a> System<'a> for GameplayInputSystem {
     type SystemData = (
         WriteStorage<'a, Player>,
-        Write<'a, AnimationStateRes>,
-        Read<'a, EventChannel<Event>>,
-        Read<'a, Time>,
-        WriteExpect<'a, BeatMap>,
-        Write<'a, GameplayResult>,
+        Read<'a, EventChannel<InputEvent<String>>>,
+        ReadClock<'a, GameClock>,
+        ReadExpect<'a, BeatMap>,
+        Write<'a, GameplayResults>,
+        Read<'a, Modifiers>,
+        Read<'a, Console>,
     );
 
-    // TODO: Update player HP
     fn run(
         &mut self,
-        (mut players, mut anim, input, time, mut beatmap, mut gameplay_result): Self::SystemData,
+        (
+            mut players,
+            input,
+            game_clock,
+            beatmap,
+            mut results,
+            modifiers,
+            console,
+        ): Self::SystemData,
     ) {
-        let offset = 0.2;
-        let rel_time = time.absolute_time_seconds() - beatmap.runtime_start;
+        let offset = HIT_WINDOW;
+        let rel_time = game_clock.absolute_time_seconds() - beatmap.runtime_start;
 
-        let mut early_remove = 0;
-        // if too late
-        for (i, beatpoint) in beatmap.beat_points.iter().enumerate() {
-            // too late :(
-            if rel_time > beatpoint.time + offset {
-                early_remove = early_remove + 1;
-            }
-        }
-        for i in 0..early_remove {
-            let missed = beatmap.beat_points.pop_front().unwrap();
-            gameplay_result
-                .results
-                .push((missed.time, HitResult::MissLate));
-            anim.state = AnimationState::Falling;
+        // The keys pressed outside of the gameplay are not part of the run.
+        if beatmap.runtime_start != self.runtime_start {
+            self.runtime_start = beatmap.runtime_start;
+            input.read(&mut self.input_reader.as_mut().unwrap()).count();
+            return;
         }
 
-        // check input
+        // the directions pressed by each player this frame
+        let mut pressed = vec![vec![]; results.players.len()];
         for ev in input.read(&mut self.input_reader.as_mut().unwrap()) {
-            if let Some((key, ElementState::Pressed)) = get_key(&ev) {
-                // Only consider arrow keys
-                if key == VirtualKeyCode::Left || key == VirtualKeyCode::Right
-                    || key == VirtualKeyCode::Up || key == VirtualKeyCode::Down
-                {
-                    if let Some(beatpoint) = beatmap.beat_points.pop_front() {
-                        // too early
-                        if rel_time < beatpoint.time - offset {
-                            // ignore
-
-                            //gameplay_results.results.push((missed.time,HitResult::MissEarly));
-
-                            //temporary to keep it
-                            beatmap.beat_points.push_front(beatpoint);
+            // The keys typed in the developer console don't move the players.
+            if console.open {
+                continue;
+            }
+            if let InputEvent::ActionPressed(action) = ev {
+                if let Some((index, direction)) = parse_player_action(action) {
+                    if let Some(directions) = pressed.get_mut(index) {
+                        directions.push(direction);
+                    }
+                }
+            }
+        }
+
+        for player in (&mut players).join() {
+            let gameplay_result = match results.players.get_mut(player.index) {
+                Some(result) => result,
+                None => continue,
+            };
+            if gameplay_result.status != GameplayStatus::Running {
+                continue;
+            }
+
+            let mut misses = 0;
+            // too late :(
+            while player
+                .beat_points
+                .front()
+                .map(|b| rel_time > b.time + offset)
+                .unwrap_or(false)
+            {
+                let missed = player.beat_points.pop_front().unwrap();
+                gameplay_result.judge(missed.time, HitResult::MissLate, rel_time);
+                misses += 1;
+                player.animation = AnimationState::Falling;
+            }
+
+            // check input
+            for direction in &pressed[player.index] {
+                if let Some(beatpoint) = player.beat_points.pop_front() {
+                    // too early
+                    if rel_time < beatpoint.time - offset {
+                        // ignore
+
+                        //gameplay_results.results.push((missed.time,HitResult::MissEarly));
+
+                        //temporary to keep it
+                        player.beat_points.push_front(beatpoint);
+                    } else {
+                        //if ok
+
+                        // TODO: trigger animations (can be done by checking player dist to first beatpoint)
+                        gameplay_result.hit_errors.push(rel_time - beatpoint.time);
+                        if beatpoint.direction == *direction {
+                            gameplay_result.judge(beatpoint.time, HitResult::Hit, rel_time);
+
+                            // TODO: if next platform is higher -> jetpack else -> running
+
+                            player.animation = AnimationState::Running;
                         } else {
-                            //if ok
-
-                            let correct_key = match key {
-                                VirtualKeyCode::Left => beatpoint.direction == Direction::Left,
-                                VirtualKeyCode::Right => beatpoint.direction == Direction::Right,
-                                VirtualKeyCode::Up => beatpoint.direction == Direction::Up,
-                                VirtualKeyCode::Down => beatpoint.direction == Direction::Down,
-                                _ => false, // impossible
-                            };
-
-                            // TODO: trigger animations (can be done by checking player dist to first beatpoint)
-                            if correct_key {
-                                gameplay_result
-                                    .results
-                                    .push((beatpoint.time, HitResult::Hit));
-                                
-                                // TODO: if next platform is higher -> jetpack else -> running
-                                
-                                anim.state = AnimationState::Running;
-                            } else {
-                                gameplay_result
-                                    .results
-                                    .push((beatpoint.time, HitResult::MissKey));
-                                anim.state = AnimationState::Falling;
-                            }
+                            gameplay_result.judge(beatpoint.time, HitResult::MissKey, rel_time);
+                            misses += 1;
+                            player.animation = AnimationState::Falling;
                         }
                     }
                 }
             }
-        }
-        // if this is the last point, the game is done.
-        if beatmap.beat_points.len() == 0 {
-            gameplay_result.status = GameplayStatus::Completed;
+
+            player.health -= misses;
+            gameplay_result.health = player.health;
+            if player.health <= 0 {
+                gameplay_result.status = GameplayStatus::Failed;
+                continue;
+            }
+            // with sudden death, the first miss ends the game.
+            if modifiers.sudden_death && gameplay_result.results.iter().any(|(_, h)| match h {
+                HitResult::Hit => false,
+                _ => true,
+            }) {
+                gameplay_result.status = GameplayStatus::Failed;
+                continue;
+            }
+            // if this is the last point, the game is done.
+            if player.beat_points.is_empty() {
+                gameplay_result.status = GameplayStatus::Completed;
+            }
         }
     }
 
     fn setup(&mut self, mut res: &mut Resources) {
         Self::SystemData::setup(&mut res);
-        self.input_reader = Some(res.fetch_mut::<EventChannel<Event>>().register_reader());
+        self.input_reader = Some(
+            res.fetch_mut::<EventChannel<InputEvent<String>>>()
+                .register_reader(),
+        );
     }
 }
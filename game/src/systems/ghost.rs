@@ -0,0 +1,90 @@
+use amethyst::core::cgmath::Vector3;
+use amethyst::core::{GameClock, ReadClock, Transform};
+use amethyst::ecs::{Join, ReadExpect, ReadStorage, System, WriteStorage};
+use amethyst::renderer::SpriteRender;
+use floating_duration::TimeAsFloat;
+
+use data::*;
+use systems::{move_runner, Node};
+
+/// Duration of a frame of the running animation of the ghost.
+const GHOST_FRAME_TIME: f64 = 0.1;
+/// Number of frames of the running animation.
+const GHOST_FRAMES: usize = 4;
+
+/// Moves the ghost runner along the track like the player moved during the best run.
+#[derive(Default)]
+pub struct GhostSystem {
+    beat_points: Option<Vec<Node>>,
+    /// Start of the run the beat points were gathered for.
+    runtime_start: f64,
+}
+
+impl<'a> System<'a> for GhostSystem {
+    type SystemData = (
+        ReadClock<'a, GameClock>,
+        ReadExpect<'a, BeatMap>,
+        ReadStorage<'a, BeatPoint>,
+        WriteStorage<'a, Ghost>,
+        WriteStorage<'a, Transform>,
+        WriteStorage<'a, SpriteRender>,
+    );
+
+    fn run(
+        &mut self,
+        (game_clock, beatmap, beatpoints, mut ghosts, mut transforms, mut sprites): Self::SystemData,
+    ) {
+        let rel_time = game_clock.absolute_time_seconds() - beatmap.runtime_start;
+
+        // The system is kept from one run to the next.
+        if beatmap.runtime_start != self.runtime_start {
+            self.runtime_start = beatmap.runtime_start;
+            self.beat_points = None;
+        }
+
+        if self.beat_points.is_none() {
+            let mut v = (&transforms, &beatpoints)
+                .join()
+                .map(|(t, b)| (t.translation, b.time))
+                .collect::<Vec<Node>>();
+            if !v.is_empty() {
+                v.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+                self.beat_points = Some(v);
+            }
+            return;
+        }
+        let nodes = self.beat_points.as_ref().unwrap();
+
+        for (ghost, transform, sprite) in (&mut ghosts, &mut transforms, &mut sprites).join() {
+            // The player moves on to the next beat point once the current one is judged.
+            while ghost
+                .beat_points
+                .front()
+                .map(|(_, judged_at)| *judged_at <= rel_time)
+                .unwrap_or(false)
+            {
+                ghost.beat_points.pop_front();
+            }
+
+            let next = match ghost
+                .beat_points
+                .front()
+                .and_then(|(t, _)| nodes.iter().find(|n| n.1 == *t))
+            {
+                Some(next) => next,
+                None => continue,
+            };
+            move_runner(
+                transform,
+                &mut ghost.last_beatpoint,
+                next,
+                rel_time,
+                Vector3::new(0.0, 0.0, 0.0),
+            );
+
+            ghost.animation_time += game_clock.delta_time().as_fractional_secs();
+            sprite.sprite_number =
+                (ghost.animation_time / GHOST_FRAME_TIME) as usize % GHOST_FRAMES;
+        }
+    }
+}
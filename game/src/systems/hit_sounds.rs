@@ -0,0 +1,50 @@
+use amethyst::assets::AssetStorage;
+use amethyst::audio::output::Output;
+use amethyst::audio::Source;
+use amethyst::ecs::{Read, System};
+use dirty::Dirty;
+
+use std::ops::Deref;
+
+use data::*;
+use utils::play_sfx;
+
+/// Plays the hit sound of each new judgement in the `GameplayResults`.
+/// A hit beat point with its own sample plays the sample instead.
+#[derive(Default)]
+pub struct HitSoundSystem {
+    /// Number of results already played, by player index.
+    played: Vec<usize>,
+}
+
+impl<'a> System<'a> for HitSoundSystem {
+    type SystemData = (
+        Read<'a, GameplayResults>,
+        Read<'a, HitSounds>,
+        Read<'a, AssetStorage<Source>>,
+        Option<Read<'a, Output>>,
+        Read<'a, Dirty<UserSettings>>,
+    );
+
+    fn run(&mut self, (results, sounds, storage, output, settings): Self::SystemData) {
+        self.played.resize(results.players.len(), 0);
+        let volume = settings.read().sfx_volume;
+        let output = output.as_ref().map(|o| o.deref());
+        for (result, played) in results.players.iter().zip(self.played.iter_mut()) {
+            if result.results.len() < *played {
+                // The results were reset for a new game.
+                *played = 0;
+            }
+            for (time, hit) in &result.results[*played..] {
+                let sound = match hit {
+                    HitResult::Hit => sounds.sample(*time).or(sounds.judgement(hit)),
+                    _ => sounds.judgement(hit),
+                };
+                if let Some(sound) = sound {
+                    play_sfx(sound, &storage, output, volume);
+                }
+            }
+            *played = result.results.len();
+        }
+    }
+}
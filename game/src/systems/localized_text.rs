@@ -0,0 +1,29 @@
+use amethyst::assets::AssetStorage;
+use amethyst::ecs::*;
+use amethyst::locale::Locale;
+use amethyst::ui::UiText;
+
+use data::{LocalizedText, Localization};
+
+/// Updates the `UiText` of every `LocalizedText` entity with the selected language.
+pub struct LocalizedTextSystem;
+
+impl<'a> System<'a> for LocalizedTextSystem {
+    type SystemData = (
+        Read<'a, Localization>,
+        Read<'a, AssetStorage<Locale>>,
+        ReadStorage<'a, LocalizedText>,
+        WriteStorage<'a, UiText>,
+    );
+
+    fn run(&mut self, (localization, locales, localized, mut texts): Self::SystemData) {
+        for (l, text) in (&localized, &mut texts).join() {
+            // Locales are loaded asynchronously, keep the old text until they are ready.
+            if let Some(t) = localization.get(&l.key, &locales) {
+                if text.text != t {
+                    text.text = t;
+                }
+            }
+        }
+    }
+}
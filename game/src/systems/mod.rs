@@ -1,19 +1,34 @@
+/// System group running while a map is played, see `GamePlayState`.
+pub const GAMEPLAY_SYSTEMS: &str = "gameplay";
+/// System group animating the score screen, see `ScoreState`.
+pub const SCORE_SYSTEMS: &str = "score";
+
 mod animation_visual;
 mod change_controls;
 mod camera_follow_player;
+mod debug_overlay;
 mod gameplay_input;
+mod ghost;
+mod hit_sounds;
+mod localized_text;
 mod make_objects_fall;
 mod make_objects_rotate;
 mod score_menu_animation;
 mod player_movement;
 mod beatpoint_animation;
+mod storyboard;
 
 pub use self::animation_visual::*;
 pub use self::change_controls::{ChangeControl, ChangeControlListener};
 pub use self::camera_follow_player::*;
+pub use self::debug_overlay::*;
 pub use self::gameplay_input::*;
+pub use self::ghost::*;
+pub use self::hit_sounds::*;
+pub use self::localized_text::*;
 pub use self::make_objects_fall::*;
 pub use self::make_objects_rotate::*;
 pub use self::score_menu_animation::*;
 pub use self::player_movement::*;
 pub use self::beatpoint_animation::*;
+pub use self::storyboard::*;
@@ -1,26 +1,64 @@
 use amethyst::core::cgmath::Vector3;
-use amethyst::core::{Time, Transform};
-use amethyst::ecs::{
-    Join, Read, ReadExpect, ReadStorage, Resources, System, SystemData, Write, WriteExpect,
-    WriteStorage,
-};
-use amethyst::input::get_key;
-use amethyst::renderer::{ElementState, Event, VirtualKeyCode};
-use amethyst::shrev::{EventChannel, ReaderId};
+use amethyst::core::{GameClock, ReadClock, Transform};
+use amethyst::ecs::{Join, ReadExpect, ReadStorage, System, WriteStorage};
 
 use data::*;
-use std::collections::VecDeque;
+
+/// Position and time of a beat point.
+pub type Node = (Vector3<f32>, f64);
+
+/// Moves a runner from the last beat point it reached towards the next one, arriving at the
+/// time of the next one. `lane` is added to the position on the track.
+pub fn move_runner(
+    transform: &mut Transform,
+    last_beatpoint: &mut Option<Node>,
+    next: &Node,
+    rel_time: f64,
+    lane: Vector3<f32>,
+) {
+    let time_to_node_mult = 1.0; // is there a reason for this being 0.5 before?
+    if last_beatpoint.is_none() {
+        *last_beatpoint = Some((transform.translation - lane, 0.0));
+    }
+
+    // if in transition
+    // if last beatpoint != current, start transitionning
+    if last_beatpoint.as_ref() != Some(next) {
+        let trans_time_start = last_beatpoint.as_ref().unwrap().1;
+        let trans_time_stop = next.1;
+
+        let trans_duration = trans_time_stop - trans_time_start;
+
+        let dir = next.0 - last_beatpoint.as_ref().unwrap().0;
+        if ((rel_time - trans_time_start)*time_to_node_mult / trans_duration) as f32 >= 0.0 {
+            let new_pos = last_beatpoint.as_ref().unwrap().0
+                + dir * ((rel_time - trans_time_start)*time_to_node_mult / trans_duration) as f32;
+
+            transform.translation = new_pos + lane;
+        }
+
+        // if arrived, last beatpoint = current
+        if rel_time >= (trans_time_start + trans_duration) / time_to_node_mult {
+            *last_beatpoint = Some(next.clone());
+            transform.translation = next.0 + lane;
+        }
+    }
+}
 
 pub struct PlayerMovementSystem {
-    last_beatpoint: Option<(Vector3<f32>, f64)>,
-    beat_points: Option<VecDeque<(Vector3<f32>, f64)>>,
+    /// The last beat point reached by each player, by player index.
+    last_beatpoints: Vec<Option<Node>>,
+    beat_points: Option<Vec<Node>>,
+    /// Start of the run the beat points were gathered for.
+    runtime_start: f64,
 }
 
 impl PlayerMovementSystem {
     pub fn new() -> Self {
         PlayerMovementSystem {
-            last_beatpoint: None,
+            last_beatpoints: vec![],
             beat_points: None,
+            runtime_start: 0.0,
         }
     }
 }
@@ -29,80 +67,59 @@ impl<'a> System<'a> for PlayerMovementSystem {
     type SystemData = (
         ReadStorage<'a, Player>,
         WriteStorage<'a, Transform>,
-        Read<'a, Time>,
+        ReadClock<'a, GameClock>,
         ReadExpect<'a, BeatMap>,
         ReadStorage<'a, BeatPoint>,
-        Write<'a, GameplayResult>,
     );
 
-    fn run(
-        &mut self,
-        (players, mut transforms, time, beatmap, beatpoints, mut gameplay_result): Self::SystemData,
-    ) {
-        let time_to_node_mult = 1.0; // is there a reason for this being 0.5 before?
-        let rel_time = time.absolute_time_seconds() - beatmap.runtime_start;
+    fn run(&mut self, (players, mut transforms, game_clock, beatmap, beatpoints): Self::SystemData) {
+        let rel_time = game_clock.absolute_time_seconds() - beatmap.runtime_start;
+
+        // The system is kept from one run to the next.
+        if beatmap.runtime_start != self.runtime_start {
+            self.runtime_start = beatmap.runtime_start;
+            self.beat_points = None;
+            self.last_beatpoints.clear();
+        }
 
         if self.beat_points.is_none() {
-            let mut v = Vec::<(Vector3<f32>, f64)>::new();
+            let mut v = Vec::<Node>::new();
             for (transform, beatpoint) in (&transforms, &beatpoints).join() {
                 v.push((transform.translation.clone(), beatpoint.time));
             }
             if !v.is_empty() {
                 v.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
-                self.beat_points = Some(v.into());
+                self.beat_points = Some(v);
             }
-        } else {
-            // FIXME: Skips the first frame
+            return;
+        }
+        // FIXME: Skips the first frame
+        let nodes = self.beat_points.as_ref().unwrap();
 
-            while !self.beat_points.as_ref().unwrap().is_empty()
-                && self.beat_points.as_ref().unwrap().front().map(|t| t.1)
-                    != beatmap.beat_points.front().map(|b| b.time)
-            {
-                self.beat_points.as_mut().unwrap().pop_front();
-            }
+        for (transform, player) in (&mut transforms, &players).join() {
+            // The players of the versus mode run on the same track, one above the other.
+            let lane = Vector3::new(0.0, PLAYER_LANE_OFFSET * player.index as f32, 0.0);
 
-            for (mut transform, _) in (&mut transforms, &players).join() {
-                if self.last_beatpoint.is_none() {
-                    self.last_beatpoint = Some((transform.translation.clone(), 0.0));
-                }
-
-                if self.beat_points.as_ref().unwrap().is_empty() {
-                    return;
-                }
-
-                // if in transition
-                // if last beatpoint != current, start transitionning
-                if self.last_beatpoint.as_ref() != self.beat_points.as_ref().unwrap().front() {
-                    let trans_time_start = self.last_beatpoint.as_ref().unwrap().1;
-                    let trans_time_stop = self.beat_points.as_ref().unwrap().front().unwrap().1;
-
-                    let trans_duration = (trans_time_stop - trans_time_start);
-
-                    let dir = self.beat_points.as_ref().unwrap().front().unwrap().0
-                        - self.last_beatpoint.as_ref().unwrap().0;
-                    if ((rel_time - trans_time_start)*time_to_node_mult / trans_duration) as f32 >= 0.0 {
-                        let new_pos = self.last_beatpoint.as_ref().unwrap().0
-                            + dir * ((rel_time - trans_time_start)*time_to_node_mult / trans_duration) as f32;
-    
-                        transform.translation = new_pos;
-                    }
-
-                    // if arrived, last beatpoint = current
-                    if rel_time >= (trans_time_start + trans_duration) / time_to_node_mult {
-                        self.last_beatpoint =
-                            Some(self.beat_points.as_ref().unwrap().front().unwrap().clone());
-                        transform.translation = self
-                            .beat_points
-                            .as_ref()
-                            .unwrap()
-                            .front()
-                            .unwrap()
-                            .0
-                            .clone();
-
-                    }
-                }
+            if self.last_beatpoints.len() <= player.index {
+                self.last_beatpoints.resize(player.index + 1, None);
             }
+            // the next beat point of this player
+            let next = match player
+                .beat_points
+                .front()
+                .and_then(|b| nodes.iter().find(|n| n.1 == b.time))
+            {
+                Some(next) => next,
+                None => continue,
+            };
+
+            move_runner(
+                transform,
+                &mut self.last_beatpoints[player.index],
+                next,
+                rel_time,
+                lane,
+            );
         }
     }
 }
@@ -1,15 +1,16 @@
 use amethyst::assets::AssetStorage;
 use amethyst::audio::{Source, SourceHandle, output::Output};
-use amethyst::core::Time;
+use amethyst::core::{ReadClock, UiClock};
 use amethyst::ecs::*;
 use amethyst::input::InputHandler;
 use amethyst::renderer::VirtualKeyCode;
 use amethyst::ui::{Anchor, UiText, UiTransform};
-use amethyst_extra::{AssetLoader, AssetLoaderInternal};
+use dirty::Dirty;
 
 use std::ops::Deref;
 
-use data::ResultEntities;
+use data::{ResultEntities, UserSettings};
+use utils::{load_sound, play_sfx};
 
 enum State {
     Title(f32),
@@ -38,12 +39,13 @@ impl ScoreMenuAnimation {
 impl<'a> System<'a> for ScoreMenuAnimation {
     type SystemData = (
         Write<'a, Option<ResultEntities>>,
-        Read<'a, Time>,
+        ReadClock<'a, UiClock>,
         Option<Read<'a, Output>>,
         Read<'a, AssetStorage<Source>>,
         WriteStorage<'a, UiText>,
         WriteStorage<'a, UiTransform>,
         Write<'a, InputHandler<String, String>>,
+        Read<'a, Dirty<UserSettings>>,
     );
 
     fn run(
@@ -56,8 +58,10 @@ impl<'a> System<'a> for ScoreMenuAnimation {
             mut text_store,
             mut transf_store,
             input,
+            settings,
         ): Self::SystemData,
     ) {
+        let volume = settings.read().sfx_volume;
         if let Some(ref entities) = *entities_opt {
             if input.key_is_down(VirtualKeyCode::Space) {
                 // Skip animation
@@ -84,10 +88,11 @@ impl<'a> System<'a> for ScoreMenuAnimation {
                     butt.anchor = Anchor::Middle;
                 }
                 if let Some(ref ding) = self.ding {
-                    play_sound(
+                    play_sfx(
                         ding,
                         &*audio_storage,
                         audio_output.as_ref().map(|o| o.deref()),
+                        volume,
                     );
                 }
                 *entities_opt = None;
@@ -99,10 +104,11 @@ impl<'a> System<'a> for ScoreMenuAnimation {
                         *x += time.delta_seconds();
                         if *x > 1.0 {
                             if let Some(ref boom) = self.boom {
-                                play_sound(
+                                play_sfx(
                                     boom,
                                     audio_storage,
                                     audio_output.as_ref().map(|o| o.deref()),
+                                    volume,
                                 );
                             }
                             if let Some(text) = text_store.get_mut(entities.title) {
@@ -115,10 +121,11 @@ impl<'a> System<'a> for ScoreMenuAnimation {
                         *x += time.delta_seconds();
                         if *x > 1.0 {
                             if let Some(ref rising) = self.rising {
-                                play_sound(
+                                play_sfx(
                                     rising,
                                     audio_storage,
                                     audio_output.as_ref().map(|o| o.deref()),
+                                    volume,
                                 );
                             }
                             if let Some(text) = text_store.get_mut(entities.score_text) {
@@ -139,10 +146,11 @@ impl<'a> System<'a> for ScoreMenuAnimation {
                         }
                         if *x > 2.0 {
                             if let Some(ref ding) = self.ding {
-                                play_sound(
+                                play_sfx(
                                     ding,
                                     audio_storage,
                                     audio_output.as_ref().map(|o| o.deref()),
+                                    volume,
                                 );
                             }
                             if let Some(icon) = transf_store.get_mut(entities.grade) {
@@ -167,53 +175,15 @@ impl<'a> System<'a> for ScoreMenuAnimation {
     }
 
     fn setup(&mut self, mut res: &mut Resources) {
-        use amethyst::audio::OggFormat;
-
         Self::SystemData::setup(&mut res);
-        let loader = res.fetch::<AssetLoader>();
         self.boom = Some(
-            loader
-                .load(
-                    "audio/boom.ogg",
-                    OggFormat,
-                    (),
-                    &mut res.fetch_mut::<AssetLoaderInternal<Source>>(),
-                    &mut res.fetch_mut(),
-                    &mut res.fetch_mut(),
-                )
-                .expect("Failed to load 'boom' sound effect"),
+            load_sound(res, "audio/boom.ogg").expect("Failed to load 'boom' sound effect"),
         );
         self.rising = Some(
-            loader
-                .load(
-                    "audio/rising.ogg",
-                    OggFormat,
-                    (),
-                    &mut res.fetch_mut::<AssetLoaderInternal<Source>>(),
-                    &mut res.fetch_mut(),
-                    &mut res.fetch_mut(),
-                )
-                .expect("Failed to load 'rising' sound effect"),
+            load_sound(res, "audio/rising.ogg").expect("Failed to load 'rising' sound effect"),
         );
         self.ding = Some(
-            loader
-                .load(
-                    "audio/ding.ogg",
-                    OggFormat,
-                    (),
-                    &mut res.fetch_mut::<AssetLoaderInternal<Source>>(),
-                    &mut res.fetch_mut(),
-                    &mut res.fetch_mut(),
-                )
-                .expect("Failed to load 'ding' sound effect"),
+            load_sound(res, "audio/ding.ogg").expect("Failed to load 'ding' sound effect"),
         );
     }
 }
-
-fn play_sound(sound: &SourceHandle, storage: &AssetStorage<Source>, output: Option<&Output>) {
-    if let Some(ref output) = output.as_ref() {
-        if let Some(sound) = storage.get(sound) {
-            output.play_once(sound, 1.0);
-        }
-    }
-}
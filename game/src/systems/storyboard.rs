@@ -0,0 +1,179 @@
+use amethyst::assets::{AssetStorage, Loader};
+use amethyst::core::{GameClock, ReadClock};
+use amethyst::ecs::{Entities, Entity, Join, Read, ReadExpect, System, Write, WriteStorage};
+use amethyst::renderer::{SpriteRender, Texture, TextureData};
+use amethyst::ui::{Anchor, Stretch, UiImage, UiText, UiTransform};
+use amethyst::utils::state_scoped::StateScoped;
+use amethyst_extra::{DestroyInTime, Removal};
+
+use data::*;
+
+/// Runs the timed events of the chart against the song clock.
+pub struct StoryboardSystem;
+
+impl<'a> System<'a> for StoryboardSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadClock<'a, GameClock>,
+        ReadExpect<'a, BeatMap>,
+        Write<'a, Storyboard>,
+        ReadExpect<'a, Loader>,
+        Read<'a, AssetStorage<Texture>>,
+        WriteStorage<'a, Removal<i32>>,
+        WriteStorage<'a, SpriteRender>,
+        WriteStorage<'a, UiTransform>,
+        WriteStorage<'a, UiImage>,
+        WriteStorage<'a, UiText>,
+        WriteStorage<'a, DestroyInTime>,
+        WriteStorage<'a, StateScoped>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            game_clock,
+            beatmap,
+            mut storyboard,
+            loader,
+            textures,
+            mut removals,
+            mut sprites,
+            mut transforms,
+            mut images,
+            mut texts,
+            mut destroy,
+            mut scoped,
+        ): Self::SystemData,
+    ) {
+        let rel_time = game_clock.absolute_time_seconds() - beatmap.runtime_start;
+
+        // Creates an entity covering the screen, cleaned up with the rest of the map.
+        let mut cover = |color: [f32; 4], id: &str| -> Entity {
+            let texture = loader.load_from_data(TextureData::color(color), (), &textures);
+            let entity = entities.create();
+            transforms
+                .insert(
+                    entity,
+                    UiTransform::new(id.to_owned(), Anchor::Middle, 0.0, 0.0, 1.0, 0.0, 0.0, 0)
+                        .with_stretch(Stretch::XY {
+                            x_margin: 0.0,
+                            y_margin: 0.0,
+                        })
+                        .as_transparent(),
+                )
+                .expect("Failed to insert storyboard transform.");
+            images
+                .insert(entity, UiImage { texture })
+                .expect("Failed to insert storyboard image.");
+            removals
+                .insert(entity, Removal::new(1))
+                .expect("Failed to insert storyboard removal.");
+            scoped
+                .insert(entity, StateScoped::new())
+                .expect("Failed to insert storyboard state scope.");
+            entity
+        };
+
+        while storyboard
+            .events
+            .front()
+            .map(|e| e.time <= rel_time)
+            .unwrap_or(false)
+        {
+            let event = storyboard.events.pop_front().unwrap().event;
+            debug!("Storyboard event at {}: {:?}", rel_time, event);
+            match event {
+                ChartEvent::Tint(color) => {
+                    if let Some(tint) = storyboard.tint.take() {
+                        if let Err(err) = entities.delete(tint) {
+                            error!("Failed to delete tint: {:?}", err);
+                        }
+                    }
+                    if color[3] > 0.0 {
+                        storyboard.tint = Some(cover(color, "storyboard_tint"));
+                    }
+                }
+                ChartEvent::Flash { color, duration } => {
+                    let flash = cover(color, "storyboard_flash");
+                    destroy
+                        .insert(flash, DestroyInTime { timer: duration })
+                        .expect("Failed to insert storyboard timer.");
+                }
+                ChartEvent::SpriteSwap {
+                    removal_id,
+                    sprite_number,
+                } => {
+                    for (removal, sprite) in (&removals, &mut sprites).join() {
+                        if *removal.id() == removal_id {
+                            sprite.sprite_number = sprite_number;
+                        }
+                    }
+                }
+                ChartEvent::Hide(id) => {
+                    let hidden = (&*entities, &removals, &sprites)
+                        .join()
+                        .filter(|(_, r, _)| *r.id() == id)
+                        .map(|(e, _, _)| e)
+                        .collect::<Vec<_>>();
+                    for entity in hidden {
+                        if let Some(sprite) = sprites.remove(entity) {
+                            storyboard.hidden.push((entity, id, sprite));
+                        }
+                    }
+                }
+                ChartEvent::Show(id) => {
+                    let (shown, hidden) = storyboard
+                        .hidden
+                        .drain(..)
+                        .partition::<Vec<_>, _>(|(_, i, _)| *i == id);
+                    storyboard.hidden = hidden;
+                    for (entity, _, sprite) in shown {
+                        if let Err(err) = sprites.insert(entity, sprite) {
+                            error!("Failed to show storyboard entity: {:?}", err);
+                        }
+                    }
+                }
+                ChartEvent::CameraZoom(zoom) => storyboard.camera_zoom = zoom,
+                ChartEvent::ScrollSpeed(speed) => storyboard.scroll_speed = speed,
+                ChartEvent::Caption { text, duration } => {
+                    let font = match storyboard.font {
+                        Some(ref font) => font.clone(),
+                        None => {
+                            warn!("No font loaded for the caption \"{}\"", text);
+                            continue;
+                        }
+                    };
+                    let caption = entities.create();
+                    transforms
+                        .insert(
+                            caption,
+                            UiTransform::new(
+                                "storyboard_caption".to_owned(),
+                                Anchor::BottomMiddle,
+                                0.0,
+                                -60.0,
+                                2.0,
+                                800.0,
+                                30.0,
+                                0,
+                            ).as_transparent(),
+                        )
+                        .expect("Failed to insert caption transform.");
+                    texts
+                        .insert(caption, UiText::new(font, text, [1.0; 4], 30.0))
+                        .expect("Failed to insert caption text.");
+                    removals
+                        .insert(caption, Removal::new(1))
+                        .expect("Failed to insert caption removal.");
+                    scoped
+                        .insert(caption, StateScoped::new())
+                        .expect("Failed to insert caption state scope.");
+                    destroy
+                        .insert(caption, DestroyInTime { timer: duration })
+                        .expect("Failed to insert caption timer.");
+                }
+            }
+        }
+    }
+}
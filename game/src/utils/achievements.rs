@@ -0,0 +1,77 @@
+use amethyst::config::Config;
+use amethyst::ecs::World;
+use amethyst_extra::AssetLoader;
+use dirty::Dirty;
+
+use data::{
+    Achievement, AchievementList, AchievementToasts, GameplayResult, PlayerProgress,
+    ACHIEVEMENTS_FILE,
+};
+use states::PACK_SETTINGS_FILE;
+use utils::{list_beatmaps, user_data_path};
+
+/// Reads the achievements defined by the asset packs.
+pub fn load_achievements(asset_loader: &AssetLoader) -> Vec<Achievement> {
+    match asset_loader.resolve_path(ACHIEVEMENTS_FILE) {
+        Some(path) => match AchievementList::load_no_fallback(&path) {
+            Ok(list) => list.achievements,
+            Err(err) => {
+                error!("Failed to deserialize achievements {}: {:?}", path, err);
+                vec![]
+            }
+        },
+        None => vec![],
+    }
+}
+
+/// Whether the pack is hidden and was not unlocked yet.
+pub fn is_pack_locked(asset_loader: &AssetLoader, progress: &PlayerProgress, pack: &str) -> bool {
+    asset_loader
+        .pack_info(pack)
+        .map(|info| info.hidden)
+        .unwrap_or(false) && !progress.unlocked_packs.contains(pack)
+}
+
+/// Disables the hidden asset packs the player did not unlock.
+pub fn lock_hidden_packs(asset_loader: &mut AssetLoader, progress: &PlayerProgress) {
+    let packs = asset_loader.get_asset_packs().clone();
+    for pack in packs {
+        if is_pack_locked(asset_loader, progress, &pack) {
+            asset_loader.set_pack_enabled(&pack, false);
+        }
+    }
+}
+
+/// Adds a finished run to the stats and unlocks the achievements it meets.
+/// The unlocked packs are enabled, and the achievements are shown on the next score screen.
+pub fn finish_run(world: &mut World, map: &str, result: &GameplayResult) {
+    let achievements = load_achievements(&world.read_resource());
+    let maps = list_beatmaps(&world.read_resource());
+    let (names, packs) = {
+        let mut progress = world.write_resource::<Dirty<PlayerProgress>>();
+        let unlocked = progress
+            .write()
+            .finish_run(&achievements, map, result, &maps);
+        let names = unlocked.iter().map(|a| a.name.clone()).collect::<Vec<_>>();
+        let packs = unlocked
+            .iter()
+            .flat_map(|a| a.unlocks.iter().cloned())
+            .collect::<Vec<_>>();
+        (names, packs)
+    };
+
+    for name in &names {
+        info!("Achievement unlocked: {}", name);
+    }
+    if !packs.is_empty() {
+        let mut asset_loader = world.write_resource::<AssetLoader>();
+        for pack in &packs {
+            asset_loader.set_pack_enabled(pack, true);
+        }
+        asset_loader.save_pack_settings(&user_data_path(PACK_SETTINGS_FILE));
+    }
+    world
+        .write_resource::<AchievementToasts>()
+        .names
+        .extend(names);
+}
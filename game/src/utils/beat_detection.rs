@@ -0,0 +1,200 @@
+use std::io::Cursor;
+use std::path::Path;
+
+use amethyst_extra::AssetLoader;
+use rodio::{Decoder, Source};
+use ron::de::from_str;
+use ron::ser::{to_string_pretty, PrettyConfig};
+
+use data::{BeatMapData, BeatPoint, Direction};
+use utils::{read_asset_file, read_asset_to_string, resolve_map_file};
+
+/// Samples between two analysis frames.
+const HOP_SIZE: usize = 512;
+/// Samples of one analysis frame.
+const FRAME_SIZE: usize = 1024;
+/// Frames on each side used for the adaptive onset threshold.
+const THRESHOLD_RADIUS: usize = 8;
+/// Onsets closer than this are merged, in seconds.
+const MIN_ONSET_GAP: f64 = 0.1;
+const MIN_BPM: f64 = 60.0;
+const MAX_BPM: f64 = 200.0;
+
+/// What the beat detection found in a song.
+#[derive(Clone, Debug)]
+pub struct BeatAnalysis {
+    pub bpm: f64,
+    /// Time of the first beat in the song, in seconds. Suggested `audio_offset` of the map.
+    pub offset: f64,
+    /// Times of the detected onsets in the song, in seconds.
+    pub onsets: Vec<f64>,
+}
+
+/// Decodes a song with the decoders used by `amethyst_audio`, mixed down to mono.
+/// Returns the samples and the sample rate.
+pub fn decode_audio(path: &str) -> Option<(Vec<f32>, u32)> {
+    let bytes = match read_asset_file(path) {
+        Ok(bytes) => bytes,
+        Err(err) => {
+            error!("Failed to open {}: {}", path, err);
+            return None;
+        }
+    };
+    let decoder = match Decoder::new(Cursor::new(bytes)) {
+        Ok(decoder) => decoder,
+        Err(err) => {
+            error!("Failed to decode {}: {:?}", path, err);
+            return None;
+        }
+    };
+    let channels = decoder.channels().max(1) as usize;
+    let sample_rate = decoder.sample_rate();
+    let samples = decoder.collect::<Vec<i16>>();
+    let mono = samples
+        .chunks(channels)
+        .map(|c| c.iter().map(|s| *s as f32 / 32768.0).sum::<f32>() / c.len() as f32)
+        .collect();
+    Some((mono, sample_rate))
+}
+
+/// Computes the onset strength of every frame, as the positive change of its energy.
+fn onset_envelope(samples: &[f32]) -> Vec<f32> {
+    let mut previous = 0.0;
+    (0..samples.len().saturating_sub(FRAME_SIZE) / HOP_SIZE)
+        .map(|i| {
+            let frame = &samples[i * HOP_SIZE..i * HOP_SIZE + FRAME_SIZE];
+            let energy = frame.iter().map(|s| s * s).sum::<f32>().sqrt();
+            let flux = (energy - previous).max(0.0);
+            previous = energy;
+            flux
+        })
+        .collect()
+}
+
+/// Picks the frames whose onset strength is a local maximum above the local mean.
+fn pick_onsets(envelope: &[f32], frame_rate: f64) -> Vec<f64> {
+    let mut onsets: Vec<f64> = vec![];
+    for i in 0..envelope.len() {
+        let start = i.saturating_sub(THRESHOLD_RADIUS);
+        let end = (i + THRESHOLD_RADIUS + 1).min(envelope.len());
+        let window = &envelope[start..end];
+        let mean = window.iter().sum::<f32>() / window.len() as f32;
+        let is_peak = window.iter().all(|v| *v <= envelope[i]);
+        if is_peak && envelope[i] > mean * 1.5 + 0.01 {
+            let time = i as f64 / frame_rate;
+            if onsets.last().map(|t| time - t >= MIN_ONSET_GAP).unwrap_or(true) {
+                onsets.push(time);
+            }
+        }
+    }
+    onsets
+}
+
+/// Finds the beat period, in frames, with the strongest autocorrelation of the envelope.
+fn estimate_period(envelope: &[f32], frame_rate: f64) -> f64 {
+    let min_lag = (frame_rate * 60.0 / MAX_BPM) as usize;
+    let max_lag = ((frame_rate * 60.0 / MIN_BPM) as usize).min(envelope.len().saturating_sub(1));
+    let mut best = (min_lag.max(1), 0.0);
+    for lag in min_lag.max(1)..=max_lag {
+        let correlation = envelope[lag..]
+            .iter()
+            .zip(envelope.iter())
+            .map(|(a, b)| a * b)
+            .sum::<f32>()
+            / (envelope.len() - lag) as f32;
+        if correlation > best.1 {
+            best = (lag, correlation);
+        }
+    }
+    best.0 as f64
+}
+
+/// Finds the offset, in frames, of the beat grid which lands on the strongest onsets.
+fn estimate_phase(envelope: &[f32], period: f64) -> f64 {
+    let mut best = (0, 0.0);
+    for phase in 0..period.round() as usize {
+        let mut score = 0.0;
+        let mut frame = phase as f64;
+        while (frame as usize) < envelope.len() {
+            score += envelope[frame as usize];
+            frame += period;
+        }
+        if score > best.1 {
+            best = (phase, score);
+        }
+    }
+    best.0 as f64
+}
+
+/// Runs the onset detection and the tempo and phase estimation on decoded samples.
+pub fn analyze_beats(samples: &[f32], sample_rate: u32) -> BeatAnalysis {
+    let frame_rate = sample_rate as f64 / HOP_SIZE as f64;
+    let envelope = onset_envelope(samples);
+    let period = estimate_period(&envelope, frame_rate);
+    let phase = estimate_phase(&envelope, period);
+    BeatAnalysis {
+        bpm: 60.0 * frame_rate / period,
+        offset: phase / frame_rate,
+        onsets: pick_onsets(&envelope, frame_rate),
+    }
+}
+
+/// Turns the onsets into beat points snapped to a half beat grid, relative to the first beat.
+pub fn suggest_beat_points(analysis: &BeatAnalysis) -> Vec<BeatPoint> {
+    let directions = [
+        Direction::Left,
+        Direction::Up,
+        Direction::Right,
+        Direction::Down,
+    ];
+    let step = 30.0 / analysis.bpm;
+    let mut beat_points: Vec<BeatPoint> = vec![];
+    for onset in &analysis.onsets {
+        let slot = ((onset - analysis.offset) / step).round();
+        if slot < 0.0 {
+            continue;
+        }
+        let time = slot * step;
+        if beat_points.last().map(|b| b.time >= time).unwrap_or(false) {
+            continue;
+        }
+        beat_points.push(BeatPoint {
+            direction: directions[slot as usize % directions.len()],
+            time,
+            sample: None,
+        });
+    }
+    beat_points
+}
+
+/// Analyzes the song of a map and prints the suggested `audio_offset`, BPM and beat points.
+pub fn print_beat_detection(asset_loader: &AssetLoader, map: &str) {
+    let music_path = resolve_map_file(asset_loader, map, "map.ron")
+        .and_then(|path| read_asset_to_string(&path).ok())
+        .and_then(|contents| from_str::<BeatMapData>(&contents).ok())
+        .and_then(|data| {
+            asset_loader.resolve_path(&data.music_path).or_else(|| {
+                let file = Path::new(&data.music_path).file_name()?.to_str()?.to_owned();
+                resolve_map_file(asset_loader, map, &file)
+            })
+        });
+    let music_path = match music_path {
+        Some(path) => path,
+        None => {
+            error!("Failed to find the song of map {}", map);
+            return;
+        }
+    };
+
+    if let Some((samples, sample_rate)) = decode_audio(&music_path) {
+        let analysis = analyze_beats(&samples, sample_rate);
+        let beat_points = suggest_beat_points(&analysis);
+        println!("// {}: {} onsets found", music_path, analysis.onsets.len());
+        println!("// bpm: {:.2}", analysis.bpm);
+        println!("audio_offset: {:.3},", analysis.offset);
+        match to_string_pretty(&beat_points, PrettyConfig::default()) {
+            Ok(list) => println!("beat_points: {},", list),
+            Err(err) => error!("Failed to serialize the beat points: {}", err),
+        }
+    }
+}
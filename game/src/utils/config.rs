@@ -0,0 +1,101 @@
+use amethyst::config::{ConfigError, ConfigValue, FieldError, LayeredConfig, LoadedConfig};
+use amethyst::core::frame_limiter::{FrameRateLimitConfig, FrameRateLimitStrategy};
+use amethyst::input::Bindings;
+use amethyst::renderer::DisplayConfig;
+use amethyst_extra::AssetLoader;
+use std::time::Duration;
+
+use data::UserSettings;
+use utils::{user_data_path, SETTINGS_FILE};
+
+/// Files in the user data directory overriding the configs of the asset packs.
+pub const DISPLAY_FILE: &str = "display.ron";
+pub const INPUT_FILE: &str = "input.ron";
+pub const FRAME_LIMIT_FILE: &str = "frame_limit.ron";
+
+/// Prefix of the environment variables overriding the configs, e.g. `LD42_DISPLAY_VSYNC=false`.
+const ENV_PREFIX: &str = "LD42";
+
+/// Loads the display config of the asset packs, overridden by the user's file, the settings
+/// menu, the environment and the arguments (e.g. `--display.dimensions=(1280, 720)`).
+pub fn load_display_config(
+    asset_loader: &AssetLoader,
+    settings: &UserSettings,
+    args: &[String],
+) -> Result<LoadedConfig<DisplayConfig>, ConfigError> {
+    let config = LayeredConfig::<DisplayConfig>::new()
+        .with_file(asset_loader.resolve_path("config/display.ron").unwrap())
+        .with_optional_file(user_data_path(DISPLAY_FILE))
+        .with_value(SETTINGS_FILE, "fullscreen", ConfigValue::Bool(settings.fullscreen))
+        .with_value(SETTINGS_FILE, "vsync", ConfigValue::Bool(settings.vsync))
+        .with_env(&format!("{}_DISPLAY", ENV_PREFIX))
+        .with_args("display", args.iter().cloned())
+        .with_validation(validate_display_config)
+        .load()?;
+    for (path, source) in config.sources() {
+        debug!("Display config {} set by {}", path, source);
+    }
+    Ok(config)
+}
+
+/// The key bindings of the asset packs, overridden by the user's file, the environment and the
+/// arguments (e.g. `--input.actions.console=[Key(F1)]`).
+pub fn input_bindings_config(
+    asset_loader: &AssetLoader,
+    args: &[String],
+) -> LayeredConfig<Bindings<String, String>> {
+    LayeredConfig::new()
+        .with_file(asset_loader.resolve_path("config/input.ron").unwrap())
+        .with_optional_file(user_data_path(INPUT_FILE))
+        .with_env(&format!("{}_INPUT", ENV_PREFIX))
+        .with_args("input", args.iter().cloned())
+}
+
+/// Loads the frame limit from the user's file, the environment and the arguments (e.g.
+/// `--frame_limit.fps=60`), limiting to 144 fps by default.
+pub fn load_frame_limit_config(args: &[String]) -> Result<FrameRateLimitConfig, ConfigError> {
+    LayeredConfig::<FrameRateLimitConfig>::new()
+        .with_optional_file(user_data_path(FRAME_LIMIT_FILE))
+        .with_env(&format!("{}_FRAME_LIMIT", ENV_PREFIX))
+        .with_args("frame_limit", args.iter().cloned())
+        .with_validation(validate_frame_limit_config)
+        .load()
+        .map(|config| config.value)
+}
+
+fn validate_display_config(config: &DisplayConfig) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+    for &(path, dimensions) in &[
+        ("dimensions", config.dimensions),
+        ("min_dimensions", config.min_dimensions),
+        ("max_dimensions", config.max_dimensions),
+    ] {
+        match dimensions {
+            Some((0, _)) | Some((_, 0)) => {
+                errors.push(FieldError::new(path, "The width and height must not be 0"))
+            }
+            _ => {}
+        }
+    }
+    if config.multisampling != 0 && !config.multisampling.is_power_of_two() {
+        errors.push(FieldError::new(
+            "multisampling",
+            "The number of samples must be 0 or a power of two",
+        ));
+    }
+    errors
+}
+
+fn validate_frame_limit_config(config: &FrameRateLimitConfig) -> Vec<FieldError> {
+    match *config.strategy() {
+        FrameRateLimitStrategy::SleepAndYield(yield_time)
+            if config.fps() != 0 && yield_time >= Duration::from_secs(1) / config.fps() =>
+        {
+            vec![FieldError::new(
+                "strategy",
+                "The yield time must be shorter than a frame",
+            )]
+        }
+        _ => vec![],
+    }
+}
@@ -0,0 +1,74 @@
+use amethyst::assets::{HotReloadStrategy, PrefabLoader, RonFormat};
+use amethyst::ecs::World;
+use amethyst::prelude::Builder;
+use amethyst::renderer::Transparent;
+use amethyst::utils::state_scoped::StateScoped;
+use amethyst::{GameData, Trans, TransQueue};
+use amethyst_extra::{AssetLoader, ConsoleCommands};
+
+use data::*;
+use utils::{list_beatmaps, load_beatmap, SpriteScenePrefab};
+use {GamePlayState, MAP_SELECTION_STATE};
+
+/// The commands of the developer console: the default ones and those of the game.
+pub fn console_commands() -> ConsoleCommands {
+    let mut commands = ConsoleCommands::default();
+    commands.register(
+        "load_map",
+        "<map>",
+        "Plays a map, leaving the current one.",
+        |args, world| {
+            let name = args.get(0)?.to_string();
+            let mut beatmap = load_beatmap(name.clone(), world)
+                .ok_or_else(|| format!("Failed to load the map {}", name))?;
+            // Maps should start in 3 seconds from now.
+            beatmap.runtime_start = game_time(world) + 3.0;
+            world.add_resource(beatmap);
+            world
+                .write_resource::<TransQueue<GameData<'static, 'static>>>()
+                .push(|| {
+                    Trans::Sequence(vec![
+                        Trans::PopTo(Box::new(|state| state.name() == MAP_SELECTION_STATE)),
+                        Trans::Push(Box::new(GamePlayState::new())),
+                    ])
+                });
+            Ok(String::new())
+        },
+    );
+    commands.set_completion("load_map", |world: &World| {
+        list_beatmaps(&world.read_resource::<AssetLoader>())
+    });
+    commands.register(
+        "spawn",
+        "<prefab>",
+        "Spawns a prefab of the asset packs, e.g. prefabs/player.ron.",
+        |args, world| {
+            let path = args.get(0)?;
+            let path = world
+                .read_resource::<AssetLoader>()
+                .resolve_path(path)
+                .ok_or_else(|| format!("No asset pack has {}", path))?;
+            let prefab_handle = world.exec(|loader: PrefabLoader<SpriteScenePrefab>| {
+                loader.load(path, RonFormat, (), ())
+            });
+            let entity = world
+                .create_entity()
+                .with(prefab_handle)
+                .with(Transparent)
+                .with(StateScoped::new())
+                .build();
+            Ok(format!("Spawned {:?}", entity))
+        },
+    );
+    commands.register(
+        "reload_assets",
+        "",
+        "Reloads the assets changed on disk on the next frame.",
+        |_, world| {
+            world.write_resource::<HotReloadStrategy>().reload_once();
+            Ok(String::new())
+        },
+    );
+    commands.register_resource::<Modifiers>("modifiers");
+    commands
+}
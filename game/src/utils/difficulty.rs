@@ -0,0 +1,161 @@
+use std::fs;
+
+use data::BeatPoint;
+use utils::{is_map_archive, load_chart_file};
+
+/// Length of the sliding window used for the note density, in seconds.
+const DENSITY_WINDOW: f64 = 2.0;
+/// Length of a section of the strain graph, in seconds.
+pub const STRAIN_SECTION: f64 = 4.0;
+/// Notes closer than this belong to the same stream, in seconds.
+const STREAM_GAP: f64 = 0.3;
+/// Shortest gap taken into account, so that stacked notes don't explode the strain.
+const MIN_GAP: f64 = 0.05;
+
+/// The difficulty of a chart, with the values it was computed from.
+#[derive(Clone, Debug, Default)]
+pub struct DifficultyRating {
+    pub rating: f32,
+    pub notes: usize,
+    /// Notes per second over the whole chart.
+    pub average_density: f32,
+    /// Notes per second in the densest window of the chart.
+    pub peak_density: f32,
+    /// Part of the notes with a different direction than the previous note.
+    pub direction_changes: f32,
+    /// Shortest time between two notes, in seconds.
+    pub min_gap: f64,
+    /// Number of notes of the longest stream.
+    pub longest_stream: usize,
+    /// Strain of every `STRAIN_SECTION` seconds of the chart.
+    pub strain: Vec<f32>,
+}
+
+/// Computes the difficulty of a chart. The beat points must be sorted by time.
+pub fn rate_chart(beat_points: &[BeatPoint]) -> DifficultyRating {
+    if beat_points.is_empty() {
+        return DifficultyRating::default();
+    }
+    let first = beat_points[0].time;
+    let length = (beat_points[beat_points.len() - 1].time - first).max(DENSITY_WINDOW);
+    let notes = beat_points.len();
+
+    let mut peak_count = 0;
+    let mut end = 0;
+    for (start, b) in beat_points.iter().enumerate() {
+        while end < notes && beat_points[end].time - b.time < DENSITY_WINDOW {
+            end += 1;
+        }
+        peak_count = peak_count.max(end - start);
+    }
+
+    let mut min_gap = ::std::f64::INFINITY;
+    let mut changes = 0;
+    let mut stream = 1;
+    let mut longest_stream = 1;
+    let mut strain = vec![0.0; (length / STRAIN_SECTION) as usize + 1];
+    for (i, b) in beat_points.iter().enumerate() {
+        let (gap, changed) = if i == 0 {
+            (STREAM_GAP, false)
+        } else {
+            let prev = &beat_points[i - 1];
+            (b.time - prev.time, b.direction != prev.direction)
+        };
+        if i > 0 {
+            min_gap = min_gap.min(gap);
+            if changed {
+                changes += 1;
+            }
+            if gap <= STREAM_GAP {
+                stream += 1;
+                longest_stream = longest_stream.max(stream);
+            } else {
+                stream = 1;
+            }
+        }
+        let note_strain = 1.0 / gap.max(MIN_GAP) * if changed { 1.5 } else { 1.0 };
+        let section = ((b.time - first) / STRAIN_SECTION) as usize;
+        strain[section] += (note_strain / STRAIN_SECTION) as f32;
+    }
+
+    let average_density = (notes as f64 / length) as f32;
+    let peak_density = (peak_count as f64 / DENSITY_WINDOW) as f32;
+    let direction_changes = changes as f32 / (notes - 1).max(1) as f32;
+    let peak_strain = strain.iter().cloned().fold(0.0, f32::max);
+    let rating = 0.6 * peak_strain.sqrt()
+        + 0.3 * peak_density
+        + 0.1 * average_density
+        + 0.2 * (longest_stream as f32).sqrt()
+        + direction_changes;
+
+    DifficultyRating {
+        rating: (rating * 10.0).round() / 10.0,
+        notes,
+        average_density,
+        peak_density,
+        direction_changes,
+        min_gap: if notes > 1 { min_gap } else { 0.0 },
+        longest_stream,
+        strain,
+    }
+}
+
+/// Formats the breakdown of a rating, with the strain graph drawn in text.
+pub fn difficulty_report(name: &str, rating: &DifficultyRating) -> String {
+    let mut report = format!(
+        "{}: {:.1}\n  notes: {}\n  density: {:.2}/s average, {:.2}/s peak\n  direction changes: {:.0}%\n  minimum gap: {:.3}s\n  longest stream: {} notes\n  strain:\n",
+        name,
+        rating.rating,
+        rating.notes,
+        rating.average_density,
+        rating.peak_density,
+        rating.direction_changes * 100.0,
+        rating.min_gap,
+        rating.longest_stream,
+    );
+    let max = rating.strain.iter().cloned().fold(0.0, f32::max).max(::std::f32::EPSILON);
+    for (i, s) in rating.strain.iter().enumerate() {
+        report += &format!(
+            "  {:>5.0}s |{} {:.1}\n",
+            i as f64 * STRAIN_SECTION,
+            "#".repeat((s / max * 40.0).round() as usize),
+            s
+        );
+    }
+    report
+}
+
+/// Prints the difficulty of every map of every asset pack in the directory.
+pub fn print_difficulty_report(assets_dir: &str) {
+    let mut packs = match fs::read_dir(assets_dir) {
+        Ok(dir) => dir
+            .filter_map(|e| e.ok())
+            .filter(|e| e.path().is_dir())
+            .map(|e| e.file_name().to_string_lossy().into_owned())
+            .collect::<Vec<_>>(),
+        Err(err) => {
+            error!("Failed to read assets directory {}: {}", assets_dir, err);
+            return;
+        }
+    };
+    packs.sort();
+    for pack in packs {
+        let maps_dir = format!("{}/{}/maps", assets_dir, pack);
+        let mut maps = match fs::read_dir(&maps_dir) {
+            Ok(dir) => dir
+                .filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .filter(|p| p.is_dir() || is_map_archive(p))
+                .collect::<Vec<_>>(),
+            Err(_) => continue,
+        };
+        maps.sort();
+        for map in maps {
+            // Map archives are read through their path like folders.
+            if let Some(chart) = load_chart_file(&format!("{}/scene.ron", map.display())) {
+                let name = format!("{}/{}", pack, map.file_stem().unwrap().to_string_lossy());
+                println!("{}", difficulty_report(&name, &rate_chart(&chart)));
+            }
+        }
+    }
+}
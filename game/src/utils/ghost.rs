@@ -0,0 +1,90 @@
+use amethyst::assets::{AssetStorage, Loader};
+use amethyst::config::Config;
+use amethyst::ecs::World;
+use amethyst::renderer::{
+    MaterialTextureSet, PngFormat, SpriteSheet, SpriteSheetHandle, SpriteSheetSet, Texture,
+    TextureData, TextureMetadata,
+};
+use amethyst_extra::AssetLoader;
+
+use data::{GhostRun, MapScores, GHOST_ALPHA, MAP_SCORES_FILE};
+use utils::{read_asset_file, user_data_path};
+
+/// Id of the ghost texture in the `MaterialTextureSet`, away from the ids of the scenes.
+const GHOST_TEXTURE_ID: u64 = 1001;
+/// Id of the running sprite sheet of the player prefab.
+const RUNNING_SHEET_ID: u64 = 101;
+/// Texture of the running sprite sheet of the player prefab.
+const RUNNING_TEXTURE: &str = "sprites/astronaut_run.png";
+
+/// The best run of the map, if it was ever completed.
+pub fn load_ghost_run(map: &str) -> Option<GhostRun> {
+    MapScores::load_no_fallback(&user_data_path(MAP_SCORES_FILE))
+        .ok()?
+        .maps
+        .remove(map)
+}
+
+/// Saves the run as the ghost of the map if it is the best one so far.
+pub fn save_ghost_run(map: &str, run: GhostRun) {
+    let path = user_data_path(MAP_SCORES_FILE);
+    let mut scores = MapScores::load_no_fallback(&path).unwrap_or_default();
+    if scores.insert(map, run) {
+        if let Err(e) = scores.write(&path) {
+            error!("Failed to save map scores to {}: {}", path, e);
+        }
+    }
+}
+
+/// Creates a translucent copy of the running sprite sheet of the player.
+/// The player prefab must be loaded.
+pub fn load_ghost_sprite_sheet(world: &World) -> Option<SpriteSheetHandle> {
+    let sprites = {
+        let handle = world
+            .read_resource::<SpriteSheetSet>()
+            .handle(RUNNING_SHEET_ID)?;
+        world
+            .read_resource::<AssetStorage<SpriteSheet>>()
+            .get(&handle)?
+            .sprites
+            .clone()
+    };
+    let path = world
+        .read_resource::<AssetLoader>()
+        .resolve_path(RUNNING_TEXTURE)?;
+    let bytes = match read_asset_file(&path) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            error!("Failed to read the ghost texture {}: {}", path, e);
+            return None;
+        }
+    };
+    let texture_data = match PngFormat.from_data(bytes, TextureMetadata::default()) {
+        Ok(TextureData::Image(mut image, metadata)) => {
+            for pixel in image.raw.buf.chunks_mut(4) {
+                pixel[3] = (pixel[3] as f32 * GHOST_ALPHA) as u8;
+            }
+            TextureData::Image(image, metadata)
+        }
+        Ok(data) => data,
+        Err(e) => {
+            error!("Failed to decode the ghost texture {}: {}", path, e);
+            return None;
+        }
+    };
+
+    let loader = world.read_resource::<Loader>();
+    let texture = loader.load_from_data(
+        texture_data,
+        (),
+        &world.read_resource::<AssetStorage<Texture>>(),
+    );
+    world
+        .write_resource::<MaterialTextureSet>()
+        .insert(GHOST_TEXTURE_ID, texture);
+    let sprite_sheet = SpriteSheet {
+        texture_id: GHOST_TEXTURE_ID,
+        sprites,
+    };
+    Some(loader.load_from_data(sprite_sheet, (), &world.read_resource()))
+}
@@ -0,0 +1,72 @@
+use amethyst::assets::AssetStorage;
+use amethyst::core::Parent;
+use amethyst::ecs::{Entity, Join, World};
+use amethyst::locale::{Locale, LocaleFormat, LocaleHandle};
+use amethyst::ui::UiText;
+use amethyst_extra::{AssetLoader, AssetLoaderInternal};
+
+use data::{LocalizedText, Localization, FALLBACK_LANGUAGE};
+
+/// Loads `locale/<language>.ftl` from the asset packs.
+pub fn load_locale(world: &mut World, language: &str) -> Option<LocaleHandle> {
+    world.read_resource::<AssetLoader>().load(
+        &format!("locale/{}.ftl", language),
+        LocaleFormat,
+        (),
+        &mut world.write_resource::<AssetLoaderInternal<Locale>>(),
+        &mut world.write_resource(),
+        &world.read_resource(),
+    )
+}
+
+/// Selects the language used by every `LocalizedText` and loads its locale.
+pub fn set_language(world: &mut World, language: &str) {
+    let locale = load_locale(world, language);
+    if locale.is_none() {
+        error!("Failed to load the locale for language {}", language);
+    }
+    let fallback = load_locale(world, FALLBACK_LANGUAGE);
+    *world.write_resource::<Localization>() = Localization {
+        language: language.to_owned(),
+        locale,
+        fallback,
+    };
+}
+
+/// Lists the languages that have a locale file in one of the enabled asset packs.
+pub fn list_languages(asset_loader: &AssetLoader) -> Vec<String> {
+    asset_loader
+        .resolved_files()
+        .keys()
+        .filter(|f| f.starts_with("locale/") && f.ends_with(".ftl"))
+        .map(|f| f["locale/".len()..f.len() - ".ftl".len()].to_owned())
+        .collect()
+}
+
+/// Returns the translation of the key in the selected language.
+pub fn localize(world: &World, key: &str) -> String {
+    world
+        .read_resource::<Localization>()
+        .text(key, &world.read_resource::<AssetStorage<Locale>>())
+}
+
+/// Finds the entity holding the text of a button built with `UiButtonBuilder`.
+pub fn button_text_entity(world: &World, button: Entity) -> Option<Entity> {
+    let entities = world.entities();
+    let parents = world.read_storage::<Parent>();
+    let texts = world.read_storage::<UiText>();
+    (&*entities, &parents, &texts)
+        .join()
+        .find(|(_, p, _)| p.entity == button)
+        .map(|(e, _, _)| e)
+}
+
+/// Localizes the text of a button built with `UiButtonBuilder`, which lives in a child entity.
+pub fn localize_button(world: &mut World, button: Entity, key: &str) {
+    if let Some(e) = button_text_entity(world, button) {
+        world
+            .write_storage::<LocalizedText>()
+            .insert(e, LocalizedText::new(key.to_owned()))
+            .expect("Failed to insert localized text component.");
+    }
+}
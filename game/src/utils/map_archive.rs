@@ -0,0 +1,186 @@
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use amethyst::assets::{Directory, ErrorKind, Result, ResultExt, Source};
+use amethyst_extra::AssetLoader;
+use zip::write::FileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+/// Extensions of the files holding a whole map. The map is named after the file stem.
+pub const MAP_ARCHIVE_EXTENSIONS: [&str; 2] = ["ldmap", "zip"];
+
+pub fn is_map_archive(path: &Path) -> bool {
+    path.is_file() && path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| MAP_ARCHIVE_EXTENSIONS.contains(&e.to_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+/// Splits a path going through a map archive, like `assets/base/maps/song.ldmap/scene.ron`,
+/// into the archive and the name of the file inside of it.
+pub fn split_archive_path(path: &str) -> Option<(PathBuf, String)> {
+    let mut archive = PathBuf::new();
+    let mut components = Path::new(path).components();
+    while let Some(component) = components.next() {
+        archive.push(component);
+        if is_map_archive(&archive) {
+            let inner = components
+                .map(|c| c.as_os_str().to_string_lossy().into_owned())
+                .collect::<Vec<_>>()
+                .join("/");
+            return Some((archive, inner));
+        }
+    }
+    None
+}
+
+fn read_archive_file(archive: &Path, inner: &str) -> io::Result<Vec<u8>> {
+    let mut archive = ZipArchive::new(File::open(archive)?)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    let mut file = archive
+        .by_name(inner)
+        .map_err(|err| io::Error::new(io::ErrorKind::NotFound, err))?;
+    let mut bytes = Vec::with_capacity(file.size() as usize);
+    file.read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Reads a file from the disk, or from the map archive its path goes through.
+pub fn read_asset_file(path: &str) -> io::Result<Vec<u8>> {
+    match split_archive_path(path) {
+        Some((archive, inner)) => read_archive_file(&archive, &inner),
+        None => fs::read(path),
+    }
+}
+
+/// Same as `read_asset_file`, for text files.
+pub fn read_asset_to_string(path: &str) -> io::Result<String> {
+    read_asset_file(path).and_then(|bytes| {
+        String::from_utf8(bytes).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    })
+}
+
+/// Last modification of a file, or of the map archive its path goes through.
+pub fn modified_time(path: &str) -> Option<SystemTime> {
+    let path = match split_archive_path(path) {
+        Some((archive, _)) => archive,
+        None => PathBuf::from(path),
+    };
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+fn archive_contains(archive: &Path, inner: &str) -> bool {
+    File::open(archive)
+        .ok()
+        .and_then(|f| ZipArchive::new(f).ok())
+        .map(|mut a| a.by_name(inner).is_ok())
+        .unwrap_or(false)
+}
+
+/// Finds a file of a map, either in the map folder or in the map archive.
+/// The returned path can be read with `read_asset_file` and loaded with the `Loader`.
+pub fn resolve_map_file(asset_loader: &AssetLoader, map: &str, file: &str) -> Option<String> {
+    if let Some(path) = asset_loader.resolve_path(&format!("maps/{}/{}", map, file)) {
+        return Some(path);
+    }
+    MAP_ARCHIVE_EXTENSIONS
+        .iter()
+        .filter_map(|ext| asset_loader.resolve_path(&format!("maps/{}.{}", map, ext)))
+        .find(|archive| archive_contains(Path::new(archive), file))
+        .map(|archive| format!("{}/{}", archive, file))
+}
+
+/// Default asset source of the game. Loads from the disk like a `Directory`, and from
+/// inside the map archives for the paths going through one.
+pub struct ArchiveSource {
+    directory: Directory,
+    root: PathBuf,
+}
+
+impl ArchiveSource {
+    pub fn new<P: Into<PathBuf>>(root: P) -> Self {
+        let root = root.into();
+        ArchiveSource {
+            directory: Directory::new(root.clone()),
+            root,
+        }
+    }
+
+    fn split(&self, path: &str) -> Option<(PathBuf, String)> {
+        split_archive_path(&self.root.join(path).to_string_lossy())
+    }
+}
+
+impl Source for ArchiveSource {
+    fn modified(&self, path: &str) -> Result<u64> {
+        match self.split(path) {
+            Some((archive, _)) => Ok(fs::metadata(&archive)
+                .and_then(|m| m.modified())
+                .chain_err(|| format!("Failed to fetch metadata for {:?}", archive))?
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_secs()),
+            None => self.directory.modified(path),
+        }
+    }
+
+    fn load(&self, path: &str) -> Result<Vec<u8>> {
+        match self.split(path) {
+            Some((archive, inner)) => read_archive_file(&archive, &inner)
+                .chain_err(|| format!("Failed to read {} from {:?}", inner, archive))
+                .chain_err(|| ErrorKind::Source),
+            None => self.directory.load(path),
+        }
+    }
+}
+
+fn add_directory<W: Write + io::Seek>(
+    zip: &mut ZipWriter<W>,
+    dir: &Path,
+    prefix: &str,
+) -> io::Result<()> {
+    let mut entries = fs::read_dir(dir)?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .collect::<Vec<_>>();
+    entries.sort();
+    for path in entries {
+        let name = format!(
+            "{}{}",
+            prefix,
+            path.file_name().unwrap().to_string_lossy()
+        );
+        if path.is_dir() {
+            add_directory(zip, &path, &format!("{}/", name))?;
+        } else {
+            zip.start_file(
+                name,
+                FileOptions::default().compression_method(CompressionMethod::Deflated),
+            ).map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+            zip.write_all(&fs::read(&path)?)?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes the folder of a map into a map archive, with the files at the root of the archive.
+/// Assets referenced from outside the map folder, like the shared sprites, are not included.
+pub fn export_map(asset_loader: &AssetLoader, map: &str, output: &str) -> io::Result<()> {
+    let dir = asset_loader
+        .resolve_path(&format!("maps/{}", map))
+        .filter(|dir| Path::new(dir).is_dir())
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("No map folder named {}", map),
+            )
+        })?;
+    let mut zip = ZipWriter::new(File::create(output)?);
+    add_directory(&mut zip, Path::new(&dir), "")?;
+    zip.finish()
+        .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+    Ok(())
+}
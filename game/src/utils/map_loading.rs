@@ -1,14 +1,16 @@
 use std::collections::VecDeque;
 use std::fs;
-use std::fs::File;
-use std::io::Read;
+use std::path::Path;
 
+use amethyst::assets::{Loader, Prefab};
 use amethyst::audio::Mp3Format;
+use amethyst::config::Config;
 use amethyst::ecs::World;
 use amethyst_extra::AssetLoader;
 use ron::de::from_str;
 
 use data::*;
+use utils::{read_asset_to_string, resolve_map_file, SpriteScenePrefab, MAP_ARCHIVE_EXTENSIONS};
 
 pub fn list_directory(dir: &String) -> Vec<String> {
     fs::read_dir(dir)
@@ -26,49 +28,149 @@ pub fn list_directory(dir: &String) -> Vec<String> {
         .collect()
 }
 
+/// Lists the maps of the enabled asset packs, stored as folders or as map archives.
 pub fn list_beatmaps(asset_loader: &AssetLoader) -> Vec<String> {
-    if let Some(path) = asset_loader.resolve_path("maps") {
-        return list_directory(&path);
+    let mut maps = asset_loader
+        .resolved_files()
+        .keys()
+        .filter_map(|file| {
+            let mut parts = file.splitn(3, '/');
+            if parts.next() != Some("maps") {
+                return None;
+            }
+            let name = parts.next()?;
+            if parts.next().is_some() {
+                // A file of a map folder.
+                return Some(name.to_owned());
+            }
+            let path = Path::new(name);
+            let archive = path
+                .extension()
+                .and_then(|e| e.to_str())
+                .map(|e| MAP_ARCHIVE_EXTENSIONS.contains(&e.to_lowercase().as_str()))
+                .unwrap_or(false);
+            if archive {
+                path.file_stem().map(|s| s.to_string_lossy().into_owned())
+            } else {
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+    maps.sort();
+    maps.dedup();
+    maps
+}
+
+/// Lists the courses of the enabled asset packs.
+pub fn list_courses(asset_loader: &AssetLoader) -> Vec<String> {
+    asset_loader
+        .resolved_files()
+        .keys()
+        .filter(|f| f.starts_with("courses/") && f.ends_with(".ron"))
+        .map(|f| f["courses/".len()..f.len() - ".ron".len()].to_owned())
+        .collect()
+}
+
+pub fn load_course(name: &str, asset_loader: &AssetLoader) -> Option<CourseData> {
+    if let Some(path) = asset_loader.resolve_path(&format!("courses/{}.ron", name)) {
+        match CourseData::load_no_fallback(&path) {
+            Ok(course) => return Some(course),
+            Err(err) => error!("Failed to deserialize course {}: {:?}", name, err),
+        }
+    } else {
+        error!("Failed to find course {}", name);
+    }
+    None
+}
+
+/// Reads the beat points of a chart from its `scene.ron`, sorted by time.
+pub fn load_chart_file(path: &str) -> Option<Vec<BeatPoint>> {
+    let contents = match read_asset_to_string(path) {
+        Ok(contents) => contents,
+        Err(err) => {
+            error!("Failed to read chart {}: {}", path, err);
+            return None;
+        }
+    };
+    match from_str::<Prefab<SpriteScenePrefab>>(&contents) {
+        Ok(scene) => {
+            let mut beat_points = scene
+                .entities()
+                .filter_map(|e| e.data().and_then(|d| d.beat_point().cloned()))
+                .collect::<Vec<_>>();
+            beat_points.sort_by(|a, b| a.time.partial_cmp(&b.time).unwrap());
+            Some(beat_points)
+        }
+        Err(err) => {
+            error!("Failed to deserialize chart {}: {:?}", path, err);
+            None
+        }
+    }
+}
+
+/// Reads the beat points of a map from the asset packs.
+pub fn load_chart(name: &str, asset_loader: &AssetLoader) -> Option<Vec<BeatPoint>> {
+    resolve_map_file(asset_loader, name, "scene.ron").and_then(|path| load_chart_file(&path))
+}
+
+/// Reads the `map.ron` of a map, without loading its song.
+pub fn load_beatmap_data(name: &str, asset_loader: &AssetLoader) -> Option<BeatMapData> {
+    let path = match resolve_map_file(asset_loader, name, "map.ron") {
+        Some(path) => path,
+        None => {
+            error!("Failed to find map {}", name);
+            return None;
+        }
+    };
+    match read_asset_to_string(&path).map(|contents| from_str::<BeatMapData>(&contents)) {
+        Ok(Ok(data)) => Some(data),
+        Ok(Err(err)) => {
+            error!("Failed to deserialize map data: {:?}", err);
+            None
+        }
+        Err(err) => {
+            error!("Failed to read {}: {}", path, err);
+            None
+        }
     }
-    vec![]
 }
 
 pub fn load_beatmap(name: String, world: &mut World) -> Option<BeatMap> {
-    if let Some(path) = &world
+    let data = load_beatmap_data(&name, &world.read_resource::<AssetLoader>())?;
+    //load audio
+    let audio_handle = world
         .read_resource::<AssetLoader>()
-        .resolve_path(&format!("maps/{}/map.ron", name))
-    {
-        // don't fuck with file permissions thanks
-        let mut file = File::open(path).expect("Unable to open the file");
-        let mut contents = String::new();
-        file.read_to_string(&mut contents)
-            .expect("Unable to read the file");
-        match from_str::<BeatMapData>(&contents) {
-            Ok(data) => {
-                //load audio
-                if let Some(audio_handle) = world.read_resource::<AssetLoader>().load(
-                    &data.music_path,
-                    Mp3Format,
-                    (),
-                    &mut world.write_resource(),
-                    &mut world.write_resource(),
-                    &world.read_resource(),
-                ) {
-                    return Some(BeatMap {
-                        name: data.name,
-                        music: audio_handle,
-                        audio_offset: data.audio_offset,
-                        beat_points: VecDeque::new(),
-                        runtime_start: 0.0,
-                    });
-                } else {
-                    error!("Failed to load audio handle for {}", data.name);
-                }
-            }
-            Err(err) => error!("Failed to deserialize map data: {:?}", err),
+        .load(
+            &data.music_path,
+            Mp3Format,
+            (),
+            &mut world.write_resource(),
+            &mut world.write_resource(),
+            &world.read_resource(),
+        ).or_else(|| {
+            // Map archives hold the song next to map.ron.
+            let file = Path::new(&data.music_path).file_name()?.to_str()?.to_owned();
+            let path = resolve_map_file(&world.read_resource::<AssetLoader>(), &name, &file)?;
+            Some(world.read_resource::<Loader>().load(
+                path,
+                Mp3Format,
+                (),
+                (),
+                &world.read_resource(),
+            ))
+        });
+    match audio_handle {
+        Some(audio_handle) => Some(BeatMap {
+            name: data.name,
+            music: audio_handle,
+            audio_offset: data.audio_offset,
+            beat_points: VecDeque::new(),
+            runtime_start: 0.0,
+            events: data.events,
+        }),
+        None => {
+            error!("Failed to load audio handle for {}", data.name);
+            None
         }
-    } else {
-        error!("Failed to find map {}", name);
     }
-    None
 }
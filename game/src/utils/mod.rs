@@ -1,7 +1,29 @@
+mod achievements;
+mod beat_detection;
+mod config;
+mod console;
+mod difficulty;
+mod ghost;
+mod localization;
+mod map_archive;
 mod map_loading;
 mod music;
 pub mod prefabs;
+mod settings;
+mod sfx;
+mod user_data;
 
+pub use self::achievements::*;
+pub use self::beat_detection::*;
+pub use self::config::*;
+pub use self::console::*;
+pub use self::difficulty::*;
+pub use self::ghost::*;
+pub use self::localization::*;
+pub use self::map_archive::*;
 pub use self::map_loading::*;
 pub use self::music::Music;
 pub use self::prefabs::*;
+pub use self::settings::*;
+pub use self::sfx::*;
+pub use self::user_data::*;
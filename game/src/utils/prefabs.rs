@@ -2,6 +2,7 @@ use amethyst::assets::{AssetStorage, Loader, PrefabData, PrefabError, ProgressCo
 use amethyst::core::Transform;
 use amethyst::ecs::*;
 use amethyst::renderer::*;
+use amethyst::utils::state_scoped::StateScoped;
 
 use data::*;
 use amethyst_extra::RemovalPrefab;
@@ -244,6 +245,12 @@ impl Default for SpriteScenePrefab {
     }
 }
 
+impl SpriteScenePrefab {
+    pub fn beat_point(&self) -> Option<&BeatPoint> {
+        self.beat_point.as_ref()
+    }
+}
+
 impl<'a> PrefabData<'a> for SpriteScenePrefab {
     type SystemData = (
         (
@@ -257,6 +264,7 @@ impl<'a> PrefabData<'a> for SpriteScenePrefab {
         <Transform as PrefabData<'a>>::SystemData,
         <BeatPoint as PrefabData<'a>>::SystemData,
         <RemovalPrefab<i32> as PrefabData<'a>>::SystemData,
+        WriteStorage<'a, StateScoped>,
     );
     type Result = ();
 
@@ -275,6 +283,7 @@ impl<'a> PrefabData<'a> for SpriteScenePrefab {
             transform_system_data,
             beatpoint_system_data,
             removal_system_data,
+            scoped,
         ): &mut Self::SystemData,
         entities: &[Entity],
     ) -> Result<(), PrefabError> {
@@ -313,6 +322,10 @@ impl<'a> PrefabData<'a> for SpriteScenePrefab {
                 entities,
             )?;
         }
+        // The entities of the prefab belong to the state of its root entity.
+        if let Some(root_scope) = scoped.get(entities[0]).cloned() {
+            scoped.insert(entity, root_scope)?;
+        }
         Ok(())
     }
 
@@ -331,6 +344,7 @@ impl<'a> PrefabData<'a> for SpriteScenePrefab {
             transform_system_data,
             beatpoint_system_data,
             removal_system_data,
+            _,
         ): &mut Self::SystemData,
     ) -> Result<bool, PrefabError> {
         let mut ret = false;
@@ -0,0 +1,50 @@
+use amethyst::audio::AudioSink;
+use amethyst::config::Config;
+use amethyst::ecs::World;
+use amethyst::renderer::WindowMessages;
+use amethyst_extra::AssetLoader;
+use dirty::Dirty;
+
+use data::UserSettings;
+use utils::user_data_path;
+
+/// File in the user data directory holding the player's settings.
+pub const SETTINGS_FILE: &str = "settings.ron";
+
+/// Loads the settings of the asset packs, overridden by the user's saved settings if any.
+pub fn load_user_settings(asset_loader: &AssetLoader) -> UserSettings {
+    let user_path = user_data_path(SETTINGS_FILE);
+    match UserSettings::load_no_fallback(&user_path) {
+        Ok(settings) => settings,
+        Err(e) => {
+            info!("No user settings loaded from {} ({}), using defaults", user_path, e);
+            asset_loader
+                .resolve_path("config/settings.ron")
+                .map(|path| UserSettings::load(&path))
+                .unwrap_or_default()
+        }
+    }
+}
+
+/// Applies the audio settings to the music sink.
+pub fn apply_audio_settings(world: &mut World) {
+    let volume = world.read_resource::<Dirty<UserSettings>>().read().music_volume;
+    // The sink is missing when no audio output was found.
+    if world.res.has_value::<AudioSink>() {
+        world.write_resource::<AudioSink>().set_volume(volume);
+    }
+}
+
+/// Switches the window in or out of fullscreen.
+pub fn apply_fullscreen(world: &mut World, fullscreen: bool) {
+    world
+        .write_resource::<WindowMessages>()
+        .send_command(move |win| {
+            let monitor = if fullscreen {
+                Some(win.get_current_monitor())
+            } else {
+                None
+            };
+            win.set_fullscreen(monitor);
+        });
+}
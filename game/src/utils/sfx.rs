@@ -0,0 +1,85 @@
+use amethyst::assets::AssetStorage;
+use amethyst::audio::output::Output;
+use amethyst::audio::{AudioFormat, Source, SourceHandle};
+use amethyst::config::Config;
+use amethyst::ecs::Resources;
+use amethyst_extra::{AssetLoader, AssetLoaderInternal};
+
+use data::{BeatPoint, HitResult, HitSounds, HitSoundsConfig};
+
+/// Picks the audio format from the file extension, ogg if unknown.
+pub fn audio_format(path: &str) -> AudioFormat {
+    match path.rsplit('.').next().map(|ext| ext.to_lowercase()) {
+        Some(ref ext) if ext == "wav" => AudioFormat::Wav,
+        Some(ref ext) if ext == "flac" => AudioFormat::Flac,
+        Some(ref ext) if ext == "mp3" => AudioFormat::Mp3,
+        _ => AudioFormat::Ogg,
+    }
+}
+
+/// Loads a sound effect from the asset packs.
+pub fn load_sound(res: &Resources, path: &str) -> Option<SourceHandle> {
+    res.fetch::<AssetLoader>().load(
+        path,
+        audio_format(path),
+        (),
+        &mut res.fetch_mut::<AssetLoaderInternal<Source>>(),
+        &mut res.fetch_mut(),
+        &res.fetch(),
+    )
+}
+
+/// Plays a sound effect once, straight on the output so it doesn't wait behind the music.
+/// Does nothing if there is no audio output or if the sound isn't loaded yet.
+pub fn play_sfx(
+    sound: &SourceHandle,
+    storage: &AssetStorage<Source>,
+    output: Option<&Output>,
+    volume: f32,
+) {
+    if let Some(ref output) = output.as_ref() {
+        if let Some(sound) = storage.get(sound) {
+            output.play_once(sound, volume);
+        }
+    }
+}
+
+/// Loads the hit sounds of a map and the samples of its beat points.
+pub fn load_hit_sounds(res: &Resources, map: &str, beat_points: &[BeatPoint]) -> HitSounds {
+    let config = {
+        let asset_loader = res.fetch::<AssetLoader>();
+        asset_loader
+            .resolve_path(&format!("maps/{}/hitsounds.ron", map))
+            .or_else(|| asset_loader.resolve_path("config/hitsounds.ron"))
+            .map(|path| HitSoundsConfig::load(&path))
+            .unwrap_or_default()
+    };
+    let load = |result: HitResult| {
+        config.path(&result).and_then(|path| {
+            let sound = load_sound(res, path);
+            if sound.is_none() {
+                warn!("Failed to load hit sound {}", path);
+            }
+            sound
+        })
+    };
+    HitSounds {
+        hit: load(HitResult::Hit),
+        miss_early: load(HitResult::MissEarly),
+        miss_late: load(HitResult::MissLate),
+        miss_key: load(HitResult::MissKey),
+        samples: beat_points
+            .iter()
+            .filter_map(|b| {
+                b.sample.as_ref().and_then(|sample| {
+                    let path = format!("maps/{}/{}", map, sample);
+                    let sound = load_sound(res, &path);
+                    if sound.is_none() {
+                        warn!("Failed to load beat point sample {}", path);
+                    }
+                    sound.map(|s| (b.time, s))
+                })
+            })
+            .collect(),
+    }
+}
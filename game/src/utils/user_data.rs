@@ -0,0 +1,6 @@
+use amethyst_extra::get_working_dir;
+
+/// Returns the path of a file stored in the user data directory (next to the executable).
+pub fn user_data_path(file: &str) -> String {
+    format!("{}/{}", get_working_dir(), file)
+}